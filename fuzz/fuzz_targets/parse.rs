@@ -0,0 +1,22 @@
+//! Parsing and addressing must return an error, never panic, for any UTF-8 input.
+//!
+//! Run with `cargo +nightly fuzz run parse fuzz/seeds/parse` from the repository root; the
+//! seeds there are inputs that once crashed the parser.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use single_address_assembler::Parser;
+
+fuzz_target!(|data: &[u8]| {
+    let source = match std::str::from_utf8(data) {
+        Ok(source) => source,
+        Err(_) => return,
+    };
+
+    let mut parser = Parser::new(source);
+    if parser.parse_program().is_ok() {
+        if let Ok(program) = parser.address_program() {
+            program.validate();
+        }
+    }
+});
@@ -0,0 +1,18 @@
+//! The web entry point must describe any UTF-8 input as JSON, never panic: WebAssembly
+//! builds abort on a panic, taking the page's assembler down with them.
+//!
+//! Run with `cargo +nightly fuzz run web fuzz/seeds/parse` from the repository root.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use single_address_assembler::{json, web};
+
+fuzz_target!(|data: &[u8]| {
+    let source = match std::str::from_utf8(data) {
+        Ok(source) => source,
+        Err(_) => return,
+    };
+
+    let out = web::assemble(source);
+    assert!(json::parse(&out).is_ok(), "not JSON: {}", out);
+});
@@ -0,0 +1,2 @@
+.data .label x .number (-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~(-~1
+.text halt
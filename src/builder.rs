@@ -0,0 +1,107 @@
+//! Constructing programs in Rust instead of assembly text.
+
+use super::{AddressedProgram, DataWord, Instruction, ParseError, Parser, Section};
+
+/// Builds a program one label, instruction, and data word at a time, with the same
+/// duplicate-label, overflow, and unknown-label checks as the parser.
+///
+/// Each method takes and returns the builder so calls chain; the first error is kept and
+/// returned by [`build`](Self::build). Builder programs have no source text, so every span,
+/// including those inside an [`Instruction`], is only used for diagnostics and may be `0..0`.
+///
+/// ```
+/// use single_address_assembler::builder::ProgramBuilder;
+/// use single_address_assembler::{AddressedInstruction, Instruction, ParseError};
+///
+/// let program = ProgramBuilder::new()
+///     .data_word("x", 5)
+///     .label("loop")
+///     .instr(Instruction::Add("x", 0..0))
+///     .instr(Instruction::SubtractImmediate(1))
+///     .branch_zero("end")
+///     .branch("loop")
+///     .label("end")
+///     .instr(Instruction::Halt)
+///     .build()
+///     .unwrap();
+/// assert_eq!(program.text[0], AddressedInstruction::Add(0));
+/// assert_eq!(program.text[2], AddressedInstruction::BranchZero(4));
+///
+/// let err = ProgramBuilder::new().branch("nowhere").build().unwrap_err();
+/// assert!(matches!(err, ParseError::UnknownLabel(..)));
+/// ```
+#[derive(Debug)]
+pub struct ProgramBuilder<'a> {
+    parser: Parser<'a>,
+    error: Option<ParseError>,
+}
+
+impl Default for ProgramBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> ProgramBuilder<'a> {
+    pub fn new() -> Self {
+        Self {
+            parser: Parser::new(""),
+            error: None,
+        }
+    }
+
+    /// Records the outcome of one step, keeping only the first error.
+    fn step<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut Parser<'a>) -> Result<(), ParseError>,
+    {
+        if self.error.is_none() {
+            if let Err(err) = f(&mut self.parser) {
+                self.error = Some(err);
+            }
+        }
+        self
+    }
+
+    /// Defines a text label at the next instruction.
+    pub fn label(self, name: &'a str) -> Self {
+        self.step(|parser| parser.define_label(Section::Text, name, 0..0))
+    }
+
+    /// Appends an instruction.
+    pub fn instr(self, instr: Instruction<'a>) -> Self {
+        self.step(|parser| parser.push_instr(instr, 0..0))
+    }
+
+    /// Appends `beqz target`.
+    pub fn branch_zero(self, target: &'a str) -> Self {
+        self.instr(Instruction::BranchZero(target, 0..0))
+    }
+
+    /// Appends `br target`.
+    pub fn branch(self, target: &'a str) -> Self {
+        self.instr(Instruction::Branch(target, 0..0))
+    }
+
+    /// Defines a data label and places `value` at it.
+    pub fn data_word(self, name: &'a str, value: i16) -> Self {
+        self.step(|parser| {
+            parser.define_label(Section::Data, name, 0..0)?;
+            parser.add_data(DataWord::Value(value), 0..0)
+        })
+    }
+
+    /// Appends an unlabeled data word, e.g. to extend an array started by `data_word`.
+    pub fn data(self, value: i16) -> Self {
+        self.step(|parser| parser.add_data(DataWord::Value(value), 0..0))
+    }
+
+    /// Resolves every label and returns the finished program.
+    pub fn build(mut self) -> Result<AddressedProgram, ParseError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        self.parser.finish()?;
+        self.parser.address_program()
+    }
+}
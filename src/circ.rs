@@ -0,0 +1,247 @@
+//! Writes memory contents straight into a Logisim `.circ` file, for the `inject` subcommand.
+//!
+//! Components are found by their `label` attribute. Their `contents` attribute is replaced,
+//! or added, in the format Logisim saves embedded memories in: an `addr/data:` header with
+//! the address and data widths, then the values in hex, eight to a line, with runs written
+//! as `count*value` and trailing zeros left off.
+//!
+//! ```
+//! use single_address_assembler::circ;
+//!
+//! let circ = r#"<circuit name="main">
+//!   <comp lib="4" loc="(100,100)" name="ROM">
+//!     <a name="addrWidth" val="8"/>
+//!     <a name="dataWidth" val="16"/>
+//!     <a name="contents">addr/data: 8 16
+//! 0
+//! </a>
+//!     <a name="label" val="prog_rom"/>
+//!   </comp>
+//! </circuit>"#;
+//!
+//! let rom = circ::find(circ, "prog_rom").unwrap();
+//! assert_eq!((rom.addr_bits, rom.data_bits), (8, 16));
+//! let updated = circ::inject(circ, &rom, &[0x3000, 0x2000, 0x6002], 16).unwrap();
+//! assert!(updated.contains("<a name=\"contents\">addr/data: 8 16\n3000 2000 6002\n</a>"));
+//! assert!(updated.contains("<a name=\"label\" val=\"prog_rom\"/>"));
+//!
+//! let err = circ::find(circ, "data_ram").unwrap_err();
+//! assert_eq!(err.to_string(), "no component labeled `data_ram` in the circuit file");
+//! let err = circ::inject(circ, &rom, &[0; 300], 16).unwrap_err();
+//! assert_eq!(
+//!     err.to_string(),
+//!     "`prog_rom` has 8 address bits, for 256 values, but 300 are needed"
+//! );
+//! let err = circ::inject(circ, &rom, &[0x30, 0x00], 8).unwrap_err();
+//! assert_eq!(
+//!     err.to_string(),
+//!     "`prog_rom` has 16-bit data, but the image holds 8-bit values"
+//! );
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+
+/// A memory component in a circuit file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    pub label: String,
+    pub addr_bits: u32,
+    pub data_bits: u32,
+    /// Byte range of the component's `<comp>` element in the file.
+    pub element: Range<usize>,
+}
+
+/// Why contents could not be written into a circuit file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InjectError {
+    /// No component has the label.
+    NotFound(String),
+    /// More than one component has the label, with how many do.
+    Ambiguous(String, usize),
+    /// The component has no valid width attribute of the given name.
+    MissingWidth(String, &'static str),
+    /// The component's address width cannot reach every value, with how many there are.
+    TooSmall(String, u32, usize),
+    /// The component's data width does not hold a value, with the width needed.
+    DataWidth(String, u32, u32),
+}
+
+impl fmt::Display for InjectError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotFound(label) => {
+                write!(f, "no component labeled `{}` in the circuit file", label)
+            }
+            Self::Ambiguous(label, count) => write!(
+                f,
+                "{} components are labeled `{}`; give the memory a unique label",
+                count, label
+            ),
+            Self::MissingWidth(label, attribute) => write!(
+                f,
+                "`{}` has no valid `{}` attribute; is it a ROM or RAM?",
+                label, attribute
+            ),
+            Self::TooSmall(label, bits, needed) => write!(
+                f,
+                "`{}` has {} address bits, for {} values, but {} are needed",
+                label,
+                bits,
+                1u64 << bits,
+                needed
+            ),
+            Self::DataWidth(label, bits, needed) => write!(
+                f,
+                "`{}` has {}-bit data, but the image holds {}-bit values",
+                label, bits, needed
+            ),
+        }
+    }
+}
+
+/// Finds the one component labeled `label`.
+pub fn find(circ: &str, label: &str) -> Result<Component, InjectError> {
+    let wanted = format!("<a name=\"label\" val=\"{}\"/>", escape(label));
+    let mut found = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = circ[offset..].find("<comp ").map(|i| offset + i) {
+        let end = match circ[start..].find("</comp>") {
+            Some(i) => start + i + "</comp>".len(),
+            None => break,
+        };
+        // A self-closing `<comp .../>` has no attributes to hold a label.
+        let open_end = start + circ[start..].find('>').unwrap_or(0);
+        if circ[open_end - 1..=open_end].ends_with("/>") {
+            offset = open_end + 1;
+            continue;
+        }
+        if circ[start..end].contains(&wanted) {
+            found.push(start..end);
+        }
+        offset = end;
+    }
+
+    let element = match found.len() {
+        0 => return Err(InjectError::NotFound(label.to_owned())),
+        1 => found.remove(0),
+        count => return Err(InjectError::Ambiguous(label.to_owned(), count)),
+    };
+    let width = |name: &'static str| {
+        attribute(&circ[element.clone()], name)
+            .and_then(|value| value.parse::<u32>().ok())
+            .filter(|bits| (1..=32).contains(bits))
+            .ok_or_else(|| InjectError::MissingWidth(label.to_owned(), name))
+    };
+    Ok(Component {
+        label: label.to_owned(),
+        addr_bits: width("addrWidth")?,
+        data_bits: width("dataWidth")?,
+        element,
+    })
+}
+
+/// Checks that `component` holds `values` of `bits` bits each.
+pub fn check_fits(component: &Component, values: usize, bits: u32) -> Result<(), InjectError> {
+    if component.data_bits != bits {
+        return Err(InjectError::DataWidth(
+            component.label.clone(),
+            component.data_bits,
+            bits,
+        ));
+    }
+    if values as u64 > 1u64 << component.addr_bits {
+        return Err(InjectError::TooSmall(
+            component.label.clone(),
+            component.addr_bits,
+            values,
+        ));
+    }
+    Ok(())
+}
+
+/// `circ` with the contents of `component` replaced by `values` of `bits` bits each.
+pub fn inject(
+    circ: &str,
+    component: &Component,
+    values: &[u16],
+    bits: u32,
+) -> Result<String, InjectError> {
+    check_fits(component, values.len(), bits)?;
+    let element = &circ[component.element.clone()];
+    let contents = format!(
+        "<a name=\"contents\">{}</a>",
+        contents(values, component.addr_bits, component.data_bits)
+    );
+    let updated = match element.find("<a name=\"contents\">") {
+        Some(start) => {
+            let end = start + element[start..].find("</a>").unwrap_or(0) + "</a>".len();
+            format!("{}{}{}", &element[..start], contents, &element[end..])
+        }
+        None => {
+            let close = element.rfind("</comp>").unwrap();
+            format!(
+                "{}  {}\n  {}",
+                &element[..close],
+                contents,
+                &element[close..]
+            )
+        }
+    };
+    Ok(format!(
+        "{}{}{}",
+        &circ[..component.element.start],
+        updated,
+        &circ[component.element.end..]
+    ))
+}
+
+/// Memory contents in Logisim's embedded format.
+fn contents(values: &[u16], addr_bits: u32, data_bits: u32) -> String {
+    let mut out = format!("addr/data: {} {}\n", addr_bits, data_bits);
+    let used = values
+        .iter()
+        .rposition(|&value| value != 0)
+        .map_or(0, |i| i + 1);
+    let mut entries = Vec::new();
+    let mut index = 0;
+    while index < used {
+        let value = values[index];
+        let count = values[index..used]
+            .iter()
+            .take_while(|&&other| other == value)
+            .count();
+        // Logisim only collapses runs long enough to save space.
+        if count >= 4 {
+            entries.push(format!("{}*{:x}", count, value));
+            index += count;
+        } else {
+            entries.push(format!("{:x}", value));
+            index += 1;
+        }
+    }
+    if entries.is_empty() {
+        entries.push("0".to_owned());
+    }
+    for line in entries.chunks(8) {
+        out.push_str(&line.join(" "));
+        out.push('\n');
+    }
+    out
+}
+
+/// The value of the `<a name="NAME" val="..."/>` attribute in `element`.
+fn attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let prefix = format!("<a name=\"{}\" val=\"", name);
+    let start = element.find(&prefix)? + prefix.len();
+    let end = start + element[start..].find('"')?;
+    Some(&element[start..end])
+}
+
+/// `text` with the characters XML reserves escaped.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,733 @@
+use clap::{App, AppSettings, Arg, SubCommand};
+
+use super::{parse_address, parse_alias, parse_byte, parse_define, parse_word};
+use single_address_assembler::compare;
+use single_address_assembler::grade::Expectation;
+use single_address_assembler::{LabelStyle, Lint};
+
+/// The full command line: the subcommands, plus the `assemble` flags at the top level so
+/// that `assembler prog.s -t a -d b` keeps working without naming a subcommand.
+pub fn app() -> App<'static, 'static> {
+    App::new("One-Address CPU Assembler")
+        .version("1.0")
+        .about("Assembles input for use with the One-Address CPU")
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .after_help(
+            "EXIT CODES:\n    \
+             0  success\n    \
+             1  syntax or parse error, or a malformed command line\n    \
+             2  a label or constant that does not resolve\n    \
+             3  a program too big for text or data memory\n    \
+             4  an I/O error, such as a missing file\n    \
+             5  an internal error in the assembler\n    \
+             6  the program trapped under `run`, `grade`, or `vectors`\n    \
+             7  the program was still running at the step limit\n    \
+             8  a check failed: `grade`, `test`, `verify`, `run --compare`, `inject`, or `fmt --check`",
+        )
+        .arg(
+            Arg::with_name("color")
+                .help("when to color diagnostics; `auto` colors them when stderr is a terminal")
+                .long("color")
+                .takes_value(true)
+                .value_name("WHEN")
+                .possible_values(&["auto", "always", "never"])
+                .default_value("auto")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("log-level")
+                .help("log what the assembler does to stderr at this level and above; defaults to $ASSEMBLER_LOG, or off")
+                .long("log-level")
+                .takes_value(true)
+                .value_name("LEVEL")
+                .possible_values(&["off", "error", "warn", "info", "debug", "trace"])
+                .global(true),
+        )
+        .subcommand(
+            SubCommand::with_name("assemble")
+                .about("Assembles a program into Logisim memory images (the default)")
+                .args(&assemble_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("check")
+                .about("Assembles a program and reports diagnostics without writing any files")
+                .arg(input_arg())
+                .args(&source_args())
+                .args(&layout_args())
+                .args(&batch_args())
+                .arg(verbose_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("debug")
+                .about("Assembles a program and steps through it at an interactive prompt")
+                .after_help(
+                    "Commands are read from stdin, so a file of them can be piped in to script a \
+                     session. Type `help` at the prompt for the list.",
+                )
+                .arg(input_arg())
+                .args(&source_args())
+                .arg(max_steps_arg().help("most instructions a single `continue` may run")),
+        )
+        .subcommand(
+            SubCommand::with_name("disasm")
+                .about("Decodes a text image back into assembly")
+                .arg(
+                    Arg::with_name("input")
+                        .help("text image (v2.0 raw or v3.0 hex) to disassemble, or a program written by --emit-json as a .json file")
+                        .required(true)
+                        .value_name("TEXT")
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("data")
+                        .help("matching data image to include")
+                        .short("d")
+                        .long("data")
+                        .takes_value(true)
+                        .value_name("DATA"),
+                )
+                .arg(
+                    Arg::with_name("labels")
+                        .help("synthesize L_xx/D_xx labels at referenced addresses")
+                        .long("labels"),
+                )
+                .arg(data_width_arg())
+                .arg(cpu_spec_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("fmt")
+                .about("Rewrites assembly source in the canonical layout")
+                .arg(
+                    Arg::with_name("input")
+                        .help("source files to format in place; `-` formats stdin to stdout")
+                        .required(true)
+                        .multiple(true)
+                        .value_name("INPUT")
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("check")
+                        .help("change nothing, and fail if any file is not already formatted")
+                        .long("check"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("run")
+                .about("Assembles a program and runs it on a simulated One-Address CPU")
+                .after_help(
+                    "Exits with 6 when the program traps, 7 when it is still running at the \
+                     step limit, and 8 when --compare finds a difference.",
+                )
+                .arg(input_arg())
+                .args(&source_args())
+                .arg(max_steps_arg())
+                .arg(
+                    Arg::with_name("mem-size")
+                        .help("words of data RAM, overriding --cpu-spec; programs may not define more data than fits")
+                        .long("mem-size")
+                        .takes_value(true)
+                        .value_name("WORDS")
+                        .conflicts_with("max-data")
+                        .validator(memory_size),
+                )
+                .arg(
+                    Arg::with_name("dump")
+                        .help("print the contents of data memory when the program stops")
+                        .long("dump"),
+                )
+                .arg(
+                    Arg::with_name("break")
+                        .help("pause when the PC reaches this text label")
+                        .long("break")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .value_name("LABEL"),
+                )
+                .arg(
+                    Arg::with_name("watch-data")
+                        .help("pause when a `stor` changes the data word at this label")
+                        .long("watch-data")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .value_name("LABEL"),
+                )
+                .arg(
+                    Arg::with_name("trace")
+                        .help("print one line per executed instruction to stdout")
+                        .long("trace"),
+                )
+                .arg(
+                    Arg::with_name("trace-format")
+                        .help("layout of --trace output")
+                        .long("trace-format")
+                        .takes_value(true)
+                        .possible_values(&["text", "csv"])
+                        .requires("trace"),
+                )
+                .arg(
+                    Arg::with_name("trap-uninit")
+                        .help("stop with an error when the program reads a data word that was never initialized or stored to")
+                        .long("trap-uninit"),
+                )
+                .arg(
+                    Arg::with_name("on-div-zero")
+                        .help("what a divide or remainder by zero does: stop with an error, leave 0 in the accumulator, or saturate to the largest value of the accumulator's sign (remainders keep the accumulator)")
+                        .long("on-div-zero")
+                        .takes_value(true)
+                        .value_name("MODE")
+                        .possible_values(&["trap", "zero", "saturate"])
+                        .default_value("trap"),
+                )
+                .arg(
+                    Arg::with_name("profile")
+                        .help("report how often each instruction ran, estimated cycles, and the hottest source lines")
+                        .long("profile"),
+                )
+                .arg(
+                    Arg::with_name("profile-json")
+                        .help("write the --profile report as JSON to this file, or `-` for stdout")
+                        .long("profile-json")
+                        .takes_value(true)
+                        .value_name("JSON"),
+                )
+                .arg(
+                    Arg::with_name("snapshot-at")
+                        .help("save the machine state to FILE once STEP instructions have run")
+                        .long("snapshot-at")
+                        .takes_value(true)
+                        .number_of_values(2)
+                        .value_names(&["STEP", "FILE"]),
+                )
+                .arg(
+                    Arg::with_name("resume")
+                        .help("start from a state saved by --snapshot-at instead of the beginning")
+                        .long("resume")
+                        .takes_value(true)
+                        .value_name("SNAPSHOT"),
+                )
+                .arg(
+                    Arg::with_name("compare")
+                        .help("check the run against a CSV probe log exported from Logisim, reporting the first instruction boundary where the PC or accumulator differs; a run of rows with the same PC is one instruction, compared at its first row")
+                        .long("compare")
+                        .takes_value(true)
+                        .value_name("LOG")
+                        .conflicts_with_all(&["break", "watch-data", "trace", "profile", "snapshot-at"]),
+                )
+                .arg(
+                    Arg::with_name("compare-columns")
+                        .help("header names of the log's columns, as `cycle=NAME,pc=NAME,ac=NAME`; defaults to `cycle`, `pc`, and `ac`")
+                        .long("compare-columns")
+                        .takes_value(true)
+                        .value_name("COLUMNS")
+                        .requires("compare")
+                        .validator(|v| compare::Columns::parse(&v).map(|_| ())),
+                )
+                .arg(
+                    Arg::with_name("compare-radix")
+                        .help("base the log's PC and accumulator values are written in")
+                        .long("compare-radix")
+                        .takes_value(true)
+                        .possible_values(&["dec", "hex", "bin"])
+                        .default_value("dec"),
+                )
+                .arg(
+                    Arg::with_name("break-dump")
+                        .help("print the state at each pause and keep running instead of prompting")
+                        .long("break-dump"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("grade")
+                .about("Assembles and runs a program, then checks its final state")
+                .after_help(
+                    "Exits with 0 when every expectation holds, 6 when the program traps, 7 \
+                     when it is still running at the step limit, and 8 when an expectation \
+                     fails.",
+                )
+                .arg(input_arg())
+                .args(&source_args())
+                .arg(max_steps_arg())
+                .arg(
+                    Arg::with_name("expect")
+                        .help("a final value to check: `ac=VALUE`, `mem:LABEL=VALUE`, or `mem:ADDRESS=VALUE`")
+                        .long("expect")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .required(true)
+                        .value_name("EXPECTATION")
+                        .validator(|v| v.parse::<Expectation>().map(|_| ())),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("inject")
+                .about("Assembles a program and writes it into the memories of a Logisim .circ file")
+                .after_help(
+                    "Each memory is found by its label, and its contents are replaced with the \
+                     assembled text or data. The original file is kept as FILE.bak. A 16-bit \
+                     RAM holds data words; an 8-bit one holds each word high byte first.",
+                )
+                .arg(input_arg())
+                .args(&source_args())
+                .args(&layout_args())
+                .arg(
+                    Arg::with_name("circ")
+                        .help("the Logisim circuit file to update")
+                        .long("circ")
+                        .takes_value(true)
+                        .required(true)
+                        .value_name("FILE"),
+                )
+                .arg(
+                    Arg::with_name("rom")
+                        .help("label of the ROM to hold the text")
+                        .long("rom")
+                        .takes_value(true)
+                        .required_unless("ram")
+                        .value_name("LABEL"),
+                )
+                .arg(
+                    Arg::with_name("ram")
+                        .help("label of the RAM to hold the data")
+                        .long("ram")
+                        .takes_value(true)
+                        .value_name("LABEL"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("symbols")
+                .about("Assembles a program and prints its symbol table")
+                .arg(input_arg())
+                .args(&source_args())
+                .args(&layout_args())
+                .arg(
+                    Arg::with_name("output")
+                        .help("file to write the symbol table to instead of stdout")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("FILE"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("test")
+                .about("Runs every program in a directory that has a matching .expect file")
+                .after_help(
+                    "Each `NAME.s` with a sibling `NAME.expect` is a test case. The .expect file \
+                     holds `key = value` lines: `max_steps = N`, `init LOCATION = VALUE` to set \
+                     a value before the run, and `LOCATION = VALUE` to check one after it, where \
+                     LOCATION is `ac`, `mem:LABEL`, or `mem:ADDRESS`. Exits with 8 if any case \
+                     fails.",
+                )
+                .arg(
+                    Arg::with_name("dir")
+                        .help("directory of programs and their .expect files")
+                        .required(true)
+                        .value_name("DIR")
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("filter")
+                        .help("only run cases whose name contains this text")
+                        .long("filter")
+                        .takes_value(true)
+                        .value_name("NAME"),
+                )
+                .args(&source_args())
+                .arg(max_steps_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("vectors")
+                .about("Runs a program and writes a Logisim-evolution test vector file of its states")
+                .after_help(
+                    "The pin file is TOML with one `[[pin]]` table per column: `name` is the \
+                     circuit's output pin, `state` is `pc`, `ac`, `mem:LABEL`, or \
+                     `mem:ADDRESS`, and `width` is optional. A top-level `every = N` writes a \
+                     row every N steps; --every overrides it.",
+                )
+                .arg(input_arg())
+                .args(&source_args())
+                .arg(max_steps_arg())
+                .arg(
+                    Arg::with_name("pins")
+                        .help("TOML file mapping output pins to machine state")
+                        .long("pins")
+                        .takes_value(true)
+                        .required(true)
+                        .value_name("FILE"),
+                )
+                .arg(
+                    Arg::with_name("every")
+                        .help("steps between rows")
+                        .long("every")
+                        .takes_value(true)
+                        .value_name("N")
+                        .validator(|v| match v.parse::<usize>() {
+                            Ok(n) if n > 0 => Ok(()),
+                            _ => Err(format!("`{}` is not a positive step count", v)),
+                        }),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .help("file to write the vectors to instead of stdout")
+                        .short("o")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("FILE"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("verify")
+                .about("Checks that a program's text survives disassembly and reassembly unchanged")
+                .after_help(
+                    "Decodes every assembled instruction word, reassembles the disassembly with \
+                     the same CPU spec, and reports the first word that differs. Exits with 8 \
+                     if the words do not match.",
+                )
+                .arg(input_arg())
+                .args(&source_args()),
+        )
+        .args(&assemble_args())
+}
+
+fn max_steps_arg() -> Arg<'static, 'static> {
+    Arg::with_name("max-steps")
+        .help("stop after executing this many instructions")
+        .long("max-steps")
+        .takes_value(true)
+        .value_name("N")
+        .default_value("1000000")
+        .validator(|v| match v.parse::<usize>() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(format!("`{}` is not a valid step count", v)),
+        })
+}
+
+fn input_arg() -> Arg<'static, 'static> {
+    Arg::with_name("input")
+        .help("input files to assemble, in order, as if concatenated; `-` reads stdin")
+        .required(true)
+        .multiple(true)
+        .takes_value(true)
+        .value_name("INPUT")
+        .index(1)
+}
+
+fn verbose_arg() -> Arg<'static, 'static> {
+    Arg::with_name("verbose")
+        .help("print assembly statistics and the files written")
+        .short("v")
+        .long("verbose")
+}
+
+fn cpu_spec_arg() -> Arg<'static, 'static> {
+    Arg::with_name("cpu-spec")
+        .help("file giving the opcode and alu_op of each instruction for a CPU variant")
+        .long("cpu-spec")
+        .takes_value(true)
+        .value_name("SPEC")
+}
+
+/// Checks a `--max-text`, `--max-data`, or `--mem-size` value.
+fn memory_size(v: String) -> Result<(), String> {
+    match v.parse::<usize>() {
+        Ok(n) if (1..=0x10000).contains(&n) => Ok(()),
+        _ => Err(format!("`{}` is not a memory size from 1 to 65536", v)),
+    }
+}
+
+/// Flags that change how the source is parsed and which diagnostics are fatal.
+fn lint_code(v: String) -> Result<(), String> {
+    match Lint::find(&v) {
+        Some(_) => Ok(()),
+        None => Err(format!("`{}` is not a warning code", v)),
+    }
+}
+
+fn source_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        cpu_spec_arg(),
+        Arg::with_name("max-text")
+            .help("words of instruction ROM, overriding --cpu-spec")
+            .long("max-text")
+            .takes_value(true)
+            .value_name("WORDS")
+            .validator(memory_size),
+        Arg::with_name("max-data")
+            .help("words of data RAM, overriding --cpu-spec")
+            .long("max-data")
+            .takes_value(true)
+            .value_name("WORDS")
+            .validator(memory_size),
+        Arg::with_name("define")
+            .help("define a constant as if by `.equ NAME VALUE`")
+            .long("define")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .value_name("NAME=VALUE")
+            .validator(|v| parse_define(&v).map(|_| ())),
+        Arg::with_name("alias")
+            .help("spell a mnemonic another way, as if by `.alias NAME MNEMONIC`")
+            .long("alias")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .value_name("NAME=MNEMONIC")
+            .validator(|v| parse_alias(&v).map(|_| ())),
+        Arg::with_name("pad-byte")
+            .help("byte that completes an `.incbin` file of odd length")
+            .long("pad-byte")
+            .takes_value(true)
+            .value_name("BYTE")
+            .validator(|v| parse_byte(&v).map(|_| ())),
+        Arg::with_name("lossy")
+            .help("replace bytes that are not valid UTF-8 instead of rejecting the file")
+            .long("lossy"),
+        Arg::with_name("require-halt")
+            .help("treat a program that can run past its last instruction as an error, even with --allow W004 or `#![allow(w004)]`")
+            .long("require-halt"),
+        Arg::with_name("allow-div-zero")
+            .help("accept `divi 0` and `remi 0` instead of rejecting them")
+            .long("allow-div-zero"),
+        Arg::with_name("deny-warnings")
+            .help("treat every warning as an error")
+            .long("deny-warnings"),
+        Arg::with_name("allow")
+            .help("do not report the warning with this code, such as W001, unless a `#![warn(..)]` or `#![deny(..)]` comment in its file asks for it")
+            .long("allow")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .value_name("CODE")
+            .validator(lint_code),
+        Arg::with_name("label-style")
+            .help("warn about labels that do not all match this pattern, `[a-z][a-z0-9_]*` when the lint is turned on with --deny W007 alone; patterns use classes, `.`, `\\d`, `\\w`, `?`, `*`, and `+`")
+            .long("label-style")
+            .takes_value(true)
+            .value_name("PATTERN")
+            .validator(|v| LabelStyle::new(&v).map(|_| ())),
+        Arg::with_name("deny")
+            .help("treat the warning with this code as an error, unless a `#![allow(..)]` or `#![warn(..)]` comment in its file says otherwise")
+            .long("deny")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .value_name("CODE")
+            .validator(lint_code),
+        Arg::with_name("strict")
+            .help("treat problems found by post-assembly validation as errors, and warn about shorthand such as a missing `.text`")
+            .long("strict"),
+        Arg::with_name("optimize")
+            .help("remove unreachable and do-nothing instructions, fold immediate arithmetic, and shift or mask instead of multiplying or dividing by powers of two")
+            .short("O")
+            .long("optimize")
+            .overrides_with("no-optimize"),
+        Arg::with_name("no-optimize")
+            .help("assemble every instruction exactly as written (the default)")
+            .long("no-optimize")
+            .overrides_with("optimize"),
+    ]
+}
+
+/// Flags that place text and data in the CPU's address space.
+fn layout_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("data-base")
+            .help("address of the first data word (decimal or 0x hex); defaults to 0x80 with --combined, otherwise 0")
+            .long("data-base")
+            .takes_value(true)
+            .value_name("N")
+            .validator(|v| parse_address(&v).map(|_| ())),
+        Arg::with_name("text-base")
+            .help("address of the first instruction (decimal or 0x hex)")
+            .long("text-base")
+            .takes_value(true)
+            .value_name("N")
+            .validator(|v| parse_address(&v).map(|_| ())),
+    ]
+}
+
+fn data_width_arg() -> Arg<'static, 'static> {
+    Arg::with_name("data-width")
+        .help("bits per data memory cell; defaults to 8 for v2-raw and bin data images and 16 for v3-hex and --combined images")
+        .long("data-width")
+        .takes_value(true)
+        .value_name("BITS")
+        .possible_values(&["8", "16"])
+}
+
+/// Flags that control how the Logisim memory images are encoded.
+fn image_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        data_width_arg(),
+        Arg::with_name("text-width")
+            .help("bits per text memory cell; 8 writes each instruction as its high byte then its low byte")
+            .long("text-width")
+            .takes_value(true)
+            .value_name("BITS")
+            .possible_values(&["8", "16"])
+            .default_value("16"),
+        Arg::with_name("format")
+            .help("Logisim memory image format for the text and data outputs")
+            .long("format")
+            .takes_value(true)
+            .possible_values(&["v2-raw", "v3-hex", "bin"])
+            .default_value("v2-raw"),
+        Arg::with_name("data-endian")
+            .help("byte order of each data word in 8-bit data cells and binary data images")
+            .long("data-endian")
+            .takes_value(true)
+            .possible_values(&["big", "little"])
+            .default_value("big"),
+        Arg::with_name("rle")
+            .help("run-length encode repeated values as `count*value` (v2-raw only)")
+            .long("rle"),
+        Arg::with_name("annotate")
+            .help("write one value per line in the text and data images, with `#` comments naming labels and the instruction each text word encodes")
+            .long("annotate")
+            .conflicts_with("rle"),
+        Arg::with_name("header")
+            .help("write `#` comments after the format line naming the source, a hash of it, the assembler version, the command line, and the time")
+            .long("header")
+            .overrides_with("no-header"),
+        Arg::with_name("no-header")
+            .help("write no provenance comments, keeping images byte-identical for the same program (the default)")
+            .long("no-header")
+            .overrides_with("header")
+            .conflicts_with("reproducible"),
+        Arg::with_name("reproducible")
+            .help("like --header, but without the time, so the same input always gives the same images")
+            .long("reproducible"),
+        Arg::with_name("per-line")
+            .help("number of space-separated values on each v2-raw output line")
+            .long("per-line")
+            .takes_value(true)
+            .value_name("N")
+            .default_value("1")
+            .validator(|v| match v.parse::<usize>() {
+                Ok(n) if n > 0 => Ok(()),
+                _ => Err(format!("`{}` is not a positive number", v)),
+            }),
+        Arg::with_name("pad")
+            .help("pad the text and data images to the full size of text and data memory (256 words each unless --cpu-spec says otherwise)")
+            .long("pad"),
+        Arg::with_name("pad-value")
+            .help("word used to fill padding (decimal or 0x hex); defaults to 0")
+            .long("pad-value")
+            .takes_value(true)
+            .value_name("WORD")
+            .requires("pad")
+            .validator(|v| parse_word(&v).map(|_| ())),
+    ]
+}
+
+/// Flags that assemble each input on its own instead of as one program.
+fn batch_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("each")
+            .help("assemble each input as a separate program, several at once, instead of concatenating them; reports results in input order and a count of passes and failures")
+            .long("each"),
+        Arg::with_name("jobs")
+            .help("inputs to assemble at once with --each; defaults to the number of CPUs")
+            .short("j")
+            .long("jobs")
+            .takes_value(true)
+            .value_name("N")
+            .requires("each")
+            .validator(|v| match v.parse::<usize>() {
+                Ok(n) if n > 0 => Ok(()),
+                _ => Err(format!("`{}` is not a positive job count", v)),
+            }),
+    ]
+}
+
+/// Everything accepted by `assemble`, which is also the top-level default.
+fn assemble_args() -> Vec<Arg<'static, 'static>> {
+    let mut args = vec![
+        input_arg(),
+        Arg::with_name("data")
+            .help("data output file, or `-` for stdout")
+            .short("d")
+            .takes_value(true)
+            .value_name("DATA"),
+        Arg::with_name("text")
+            .help("text output file, or `-` for stdout")
+            .short("t")
+            .takes_value(true)
+            .value_name("TEXT"),
+        Arg::with_name("combined")
+            .help("write text and data into a single memory image instead of two files")
+            .long("combined")
+            .takes_value(true)
+            .value_name("OUT"),
+        Arg::with_name("split-text")
+            .help("write the high and low byte of each instruction to separate NAME.hi and NAME.lo text files")
+            .long("split-text")
+            .conflicts_with("combined"),
+        Arg::with_name("listing")
+            .help("listing output file")
+            .short("l")
+            .takes_value(true)
+            .value_name("LISTING"),
+        Arg::with_name("radix")
+            .help("base of the encoded words in the listing")
+            .long("radix")
+            .takes_value(true)
+            .possible_values(&["hex", "bin", "dec"])
+            .default_value("hex"),
+        Arg::with_name("annotate-bits")
+            .help("follow each instruction word in the listing with its opcode, alu_op, and value fields in binary")
+            .long("annotate-bits")
+            .requires("listing"),
+        Arg::with_name("symbols")
+            .help("symbol table output file")
+            .long("symbols")
+            .takes_value(true)
+            .value_name("SYMBOLS"),
+        Arg::with_name("xref")
+            .help("write where every label is defined and used, or `-` for stdout")
+            .long("xref")
+            .takes_value(true)
+            .value_name("XREF"),
+        Arg::with_name("xref-format")
+            .help("layout of --xref output")
+            .long("xref-format")
+            .takes_value(true)
+            .possible_values(&["text", "json"])
+            .requires("xref"),
+        Arg::with_name("map")
+            .help("write a map file with each label's section, name, address, size in words, and definition, or `-` for stdout")
+            .long("map")
+            .takes_value(true)
+            .value_name("MAP"),
+        Arg::with_name("debug-info")
+            .help("write the source file, line, and column of every text and data address as JSON, or `-` for stdout")
+            .long("debug-info")
+            .takes_value(true)
+            .value_name("DBG"),
+        Arg::with_name("emit-json")
+            .help("write the assembled program, data, and symbols as JSON")
+            .long("emit-json")
+            .takes_value(true)
+            .value_name("JSON"),
+        Arg::with_name("check")
+            .help("assemble and report diagnostics without writing any output files")
+            .long("check"),
+        Arg::with_name("watch")
+            .help(
+                "re-assemble whenever an input file changes, until interrupted; files read by \
+                 `.incbin` and `.csv` are not watched",
+            )
+            .long("watch")
+            .conflicts_with("each"),
+        verbose_arg(),
+    ];
+    args.extend(batch_args());
+    args.extend(image_args());
+    args.extend(source_args());
+    args.extend(layout_args());
+    args
+}
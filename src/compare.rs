@@ -0,0 +1,272 @@
+//! Checks an emulator run against a probe log exported from Logisim, for `run --compare`.
+//!
+//! The log is a CSV (or tab-separated) file with a header row naming its columns, of which
+//! three are used: the clock cycle, the PC, and the accumulator. A multi-cycle CPU holds
+//! the PC for several rows while it works on one instruction, so each run of rows with the
+//! same PC counts as one instruction, and only the first row of the run is compared: by
+//! then the PC and accumulator have both been updated by the instruction before, as in a
+//! design that loads them on the same clock edge. The first sample is compared with the
+//! machine before it runs anything, and each later one with the state one step on.
+//!
+//! ```
+//! use single_address_assembler::compare::{self, Columns};
+//! use single_address_assembler::emulator::Machine;
+//! use single_address_assembler::listing::Radix;
+//! use single_address_assembler::assemble;
+//!
+//! let program = assemble(".text clac addi 5 addi 2 halt").unwrap();
+//! let log = "Clock,PC,AC\n0,0,0\n1,0,0\n2,1,0\n3,2,5\n4,3,6\n";
+//! let columns = Columns::parse("cycle=clock").unwrap();
+//! let samples = compare::parse_log(log, &columns, Radix::Dec).unwrap();
+//! assert_eq!(samples.len(), 4);
+//!
+//! let divergence = compare::compare(&mut Machine::new(&program), &program, &samples);
+//! let divergence = divergence.unwrap_err();
+//! assert_eq!(
+//!     divergence.to_string(),
+//!     "cycle 4 (line 6): the log has pc 0x03, ac 6, but the emulator has pc 0x03, ac 7"
+//! );
+//! assert_eq!(divergence.after, Some(2));
+//! ```
+
+use crate::emulator::{Machine, StepResult, Stop, Trap};
+use crate::listing::Radix;
+use crate::AddressedProgram;
+use std::fmt;
+
+/// The header names of the columns holding each value, matched without regard to case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Columns {
+    pub cycle: String,
+    pub pc: String,
+    pub ac: String,
+}
+
+impl Default for Columns {
+    fn default() -> Self {
+        Self {
+            cycle: "cycle".to_owned(),
+            pc: "pc".to_owned(),
+            ac: "ac".to_owned(),
+        }
+    }
+}
+
+impl Columns {
+    /// Parses a `--compare-columns` value such as `cycle=Clock,ac=Acc`; columns it leaves
+    /// out keep their default names.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut columns = Self::default();
+        for entry in spec.split(',').filter(|entry| !entry.trim().is_empty()) {
+            let (key, name) = match entry.split_once('=') {
+                Some((key, name)) => (key.trim(), name.trim().to_owned()),
+                None => return Err(format!("`{}` is not of the form COLUMN=NAME", entry)),
+            };
+            match key {
+                "cycle" => columns.cycle = name,
+                "pc" => columns.pc = name,
+                "ac" => columns.ac = name,
+                _ => {
+                    return Err(format!(
+                        "unknown column `{}`; expected `cycle`, `pc`, or `ac`",
+                        key
+                    ))
+                }
+            }
+        }
+        Ok(columns)
+    }
+}
+
+/// The state a log records at the start of one instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sample {
+    /// Line of the log the sample was read from, counting the header as line 1.
+    pub line: usize,
+    /// The clock cycle, as written in the log.
+    pub cycle: String,
+    pub pc: usize,
+    pub ac: i16,
+}
+
+/// A log that could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Reads the samples of a probe log whose values are written in `radix`, keeping the first
+/// row of each run with the same PC.
+pub fn parse_log(contents: &str, columns: &Columns, radix: Radix) -> Result<Vec<Sample>, LogError> {
+    let contents = contents.trim_start_matches('\u{feff}');
+    let mut lines = contents
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|(_, line)| !line.trim().is_empty());
+    let header = match lines.next() {
+        Some((_, header)) => split_row(header),
+        None => {
+            return Err(LogError {
+                line: 1,
+                message: "the log is empty".to_owned(),
+            })
+        }
+    };
+    let find = |name: &str| {
+        header
+            .iter()
+            .position(|column| column.eq_ignore_ascii_case(name))
+            .ok_or_else(|| LogError {
+                line: 1,
+                message: format!(
+                    "no column named `{}` in the header; choose columns with --compare-columns",
+                    name
+                ),
+            })
+    };
+    let (cycle, pc, ac) = (
+        find(&columns.cycle)?,
+        find(&columns.pc)?,
+        find(&columns.ac)?,
+    );
+
+    let mut samples: Vec<Sample> = Vec::new();
+    for (line, row) in lines {
+        let cells = split_row(row);
+        let cell = |index: usize, name: &str| {
+            cells.get(index).copied().ok_or_else(|| LogError {
+                line,
+                message: format!("the row has no `{}` value", name),
+            })
+        };
+        let value = |index: usize, name: &str| {
+            let text = cell(index, name)?;
+            parse_value(text, radix).ok_or_else(|| LogError {
+                line,
+                message: format!("`{}` is not a valid {} value", text, name),
+            })
+        };
+        let sample = Sample {
+            line,
+            cycle: cell(cycle, &columns.cycle)?.to_owned(),
+            pc: value(pc, &columns.pc)? as usize,
+            ac: value(ac, &columns.ac)? as i16,
+        };
+        if samples.last().map(|last| last.pc) != Some(sample.pc) {
+            samples.push(sample);
+        }
+    }
+    Ok(samples)
+}
+
+/// The cells of one row, split at commas or tabs, without surrounding quotes.
+fn split_row(row: &str) -> Vec<&str> {
+    row.split([',', '\t'])
+        .map(|cell| cell.trim().trim_matches('"').trim())
+        .collect()
+}
+
+/// A 16-bit value as written in the log; binary values may be grouped with spaces, as
+/// Logisim writes them. Negative decimal values are taken as two's complement.
+fn parse_value(text: &str, radix: Radix) -> Option<u16> {
+    let digits: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    match radix {
+        Radix::Hex => u16::from_str_radix(digits.trim_start_matches("0x"), 16).ok(),
+        Radix::Bin => u16::from_str_radix(digits.trim_start_matches("0b"), 2).ok(),
+        Radix::Dec => match digits.parse::<i32>() {
+            Ok(value) if (i32::from(i16::MIN)..=i32::from(u16::MAX)).contains(&value) => {
+                Some(value as u16)
+            }
+            _ => None,
+        },
+    }
+}
+
+/// Where the emulator first parted ways with the log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub sample: Sample,
+    /// Text index of the instruction the emulator ran last, if any.
+    pub after: Option<usize>,
+    pub kind: DivergenceKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DivergenceKind {
+    /// The emulator's PC or accumulator differs from the sample.
+    State { pc: usize, ac: i16 },
+    /// The emulator stopped, but the log goes on.
+    Stopped(Stop),
+    /// The emulator trapped before reaching the sample.
+    Trapped(Trap),
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sample = &self.sample;
+        write!(f, "cycle {} (line {}): ", sample.cycle, sample.line)?;
+        match self.kind {
+            DivergenceKind::State { pc, ac } => write!(
+                f,
+                "the log has pc {:#04x}, ac {}, but the emulator has pc {:#04x}, ac {}",
+                sample.pc, sample.ac, pc, ac
+            ),
+            DivergenceKind::Stopped(stop) => write!(
+                f,
+                "the log goes on to pc {:#04x}, but the emulator {}",
+                sample.pc, stop
+            ),
+            DivergenceKind::Trapped(trap) => write!(
+                f,
+                "the log goes on to pc {:#04x}, but the emulator trapped: {}",
+                sample.pc, trap
+            ),
+        }
+    }
+}
+
+/// Steps `machine` through `program` in lockstep with `samples`, returning how many
+/// matched or the first that did not. PCs in the log include the program's text base.
+pub fn compare(
+    machine: &mut Machine,
+    program: &AddressedProgram,
+    samples: &[Sample],
+) -> Result<usize, Divergence> {
+    let mut after = None;
+    for (index, sample) in samples.iter().enumerate() {
+        if index > 0 {
+            let kind = match machine.step() {
+                Ok(StepResult::Executed(step)) => {
+                    after = Some(step.pc);
+                    None
+                }
+                Ok(StepResult::Stopped(stop)) => Some(DivergenceKind::Stopped(stop)),
+                Err(trap) => Some(DivergenceKind::Trapped(trap)),
+            };
+            if let Some(kind) = kind {
+                return Err(Divergence {
+                    sample: sample.clone(),
+                    after,
+                    kind,
+                });
+            }
+        }
+        let pc = program.text_base + machine.pc;
+        if pc != sample.pc || machine.ac != sample.ac {
+            return Err(Divergence {
+                sample: sample.clone(),
+                after,
+                kind: DivergenceKind::State { pc, ac: machine.ac },
+            });
+        }
+    }
+    Ok(samples.len())
+}
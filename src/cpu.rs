@@ -0,0 +1,446 @@
+//! Instruction encodings for variants of the One-Address CPU.
+//!
+//! A [`CpuSpec`] maps each mnemonic the CPU implements to its opcode and alu_op. Specs are
+//! written in a small subset of TOML, one inline table per mnemonic:
+//!
+//! ```toml
+//! [instructions]
+//! add = { opcode = 2, alu_op = 0 }
+//! stor = { opcode = 5 }
+//! ```
+//!
+//! An instruction with no `alu_op` encodes it as 0 and ignores it when decoding. Mnemonics
+//! left out do not exist on the variant, and `halt` is available whenever `br` is. Variants
+//! with indirect addressing list each indirect form under its own key, such as
+//! `add_indirect` for `add [ptr]` and `stor_indirect` for `stor [ptr]`.
+//!
+//! Variants with more than 256 words of instruction ROM say so in a `[memory]` table and
+//! give an opcode for `prefix`, which supplies the high byte of the next branch target:
+//!
+//! ```toml
+//! [instructions]
+//! prefix = { opcode = 7 }
+//!
+//! [memory]
+//! text_words = 512
+//! ```
+//!
+//! A `br` or `beqz` to an address above `0xff` then assembles to two words, `prefix` with
+//! the high byte of the target followed by the branch with the low byte, and every later
+//! address moves up by one. Branches within the first 256 words keep the one-word form, so
+//! a program that never reaches past them assembles exactly as it would without the table.
+//! Without `prefix`, a larger ROM still holds more code, but a branch to anything past
+//! `0xff` is an error.
+//!
+//! An instruction's table may also give `cycles`, how many clock cycles it takes, for the
+//! estimates made by `run --profile`. Without it, multiplies take 4 cycles, divides and
+//! remainders 8, and everything else 1.
+//!
+//! The `[memory]` table can also give `data_words`, the size of data RAM, which bounds how
+//! much data a program may define and which addresses the emulator lets it touch. Both
+//! sizes default to 256 words, and `--max-text` and `--max-data` override them.
+
+use crate::{AddressedInstruction, DecodeError, EncodeError, MAX_DATA, MAX_TEXT};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The spec for the CPU described in the course materials, used when none is given.
+pub const DEFAULT_SPEC: &str = "\
+# Encodings for the One-Address CPU from Implementing a One Address CPU in Logisim.
+[instructions]
+noop = { opcode = 0 }
+addi = { opcode = 1, alu_op = 0 }
+subi = { opcode = 1, alu_op = 1 }
+muli = { opcode = 1, alu_op = 2 }
+divi = { opcode = 1, alu_op = 3 }
+remi = { opcode = 1, alu_op = 4 }
+andi = { opcode = 1, alu_op = 5 }
+shift = { opcode = 1, alu_op = 6 }
+add = { opcode = 2, alu_op = 0 }
+sub = { opcode = 2, alu_op = 1 }
+mul = { opcode = 2, alu_op = 2 }
+div = { opcode = 2, alu_op = 3 }
+rem = { opcode = 2, alu_op = 4 }
+and = { opcode = 2, alu_op = 5 }
+clac = { opcode = 3 }
+stor = { opcode = 4 }
+beqz = { opcode = 5 }
+br = { opcode = 6 }
+";
+
+/// Where an instruction sits in the 16-bit word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Encoding {
+    pub opcode: u8,
+    /// `None` for instructions that do not use the alu_op field.
+    pub alu_op: Option<u8>,
+}
+
+impl Encoding {
+    /// Whether a word with these fields could be decoded as either instruction.
+    fn overlaps(self, other: Encoding) -> bool {
+        self.opcode == other.opcode
+            && match (self.alu_op, other.alu_op) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            }
+    }
+}
+
+/// A problem in a CPU spec file, with the 1-based line it was found on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpuSpecError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for CpuSpecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// The instruction set of one CPU variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpuSpec {
+    encodings: BTreeMap<&'static str, Encoding>,
+    text_words: usize,
+    data_words: usize,
+    /// Cycle counts given in the spec, by mnemonic.
+    cycles: BTreeMap<&'static str, u32>,
+}
+
+impl Default for CpuSpec {
+    /// The spec in [`DEFAULT_SPEC`], parsed once per thread and cloned after that, since every
+    /// [`Parser`](crate::Parser) starts with one.
+    fn default() -> Self {
+        thread_local! {
+            static DEFAULT: CpuSpec = CpuSpec::parse(DEFAULT_SPEC).unwrap();
+        }
+        DEFAULT.with(Clone::clone)
+    }
+}
+
+impl CpuSpec {
+    /// Reads a spec in the format described in the [module docs](self).
+    ///
+    /// ```
+    /// use single_address_assembler::cpu::CpuSpec;
+    ///
+    /// let spec = CpuSpec::parse("[instructions]\nclac = { opcode = 3 }\nbr = { opcode = 6 }").unwrap();
+    /// assert!(spec.has("halt"));
+    /// assert!(!spec.has("add"));
+    ///
+    /// let err = CpuSpec::parse("clac = { opcode = 3 }\nbr = { opcode = 3 }").unwrap_err();
+    /// assert_eq!(err.to_string(), "line 2: `br` has the same encoding as `clac`");
+    ///
+    /// let spec = CpuSpec::parse("br = { opcode = 6 }\n[memory]\ndata_words = 1024").unwrap();
+    /// assert_eq!((spec.text_words(), spec.data_words()), (256, 1024));
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, CpuSpecError> {
+        let mut encodings: BTreeMap<&'static str, Encoding> = BTreeMap::new();
+        let mut text_words = None;
+        let mut data_words = None;
+        let mut cycles = BTreeMap::new();
+        let mut in_memory = false;
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: String| CpuSpecError {
+                line: index + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') {
+                in_memory = match line {
+                    "[instructions]" => false,
+                    "[memory]" => true,
+                    _ => {
+                        return Err(error(format!(
+                            "unknown table `{}`; expected `[instructions]` or `[memory]`",
+                            line
+                        )))
+                    }
+                };
+                continue;
+            }
+            if in_memory {
+                let (key, words) = parse_memory_size(line).map_err(error)?;
+                let size = if key == "text_words" {
+                    &mut text_words
+                } else {
+                    &mut data_words
+                };
+                if size.replace((words, index + 1)).is_some() {
+                    return Err(error(format!("`{}` is given twice", key)));
+                }
+                continue;
+            }
+
+            let (name, table) = split_pair(line)
+                .ok_or_else(|| error("expected `mnemonic = { opcode = N }`".to_owned()))?;
+            let mnemonic = match (name, AddressedInstruction::from_mnemonic(name, 0)) {
+                (_, Some(instr)) => instr.mnemonic(),
+                ("halt", None) => {
+                    return Err(error(
+                        "`halt` is a pseudo-instruction assembled as `br`".to_owned(),
+                    ))
+                }
+                ("shl", None) | ("shr", None) => {
+                    return Err(error(format!(
+                        "`{}` is a form of `shift` and is available whenever it is",
+                        name
+                    )))
+                }
+                _ => return Err(error(format!("unknown mnemonic `{}`", name))),
+            };
+            let (encoding, cost) = parse_table(table).map_err(error)?;
+            if let Some((other, _)) = encodings.iter().find(|(_, other)| other.overlaps(encoding)) {
+                return Err(error(format!(
+                    "`{}` has the same encoding as `{}`",
+                    mnemonic, other
+                )));
+            }
+            if encodings.insert(mnemonic, encoding).is_some() {
+                return Err(error(format!("`{}` is listed twice", mnemonic)));
+            }
+            if let Some(cost) = cost {
+                cycles.insert(mnemonic, cost);
+            }
+        }
+
+        Ok(Self {
+            encodings,
+            text_words: text_words.map_or(MAX_TEXT, |(words, _)| words),
+            data_words: data_words.map_or(MAX_DATA, |(words, _)| words),
+            cycles,
+        })
+    }
+
+    /// Words of instruction ROM, which bounds the program and its branch targets.
+    pub fn text_words(&self) -> usize {
+        self.text_words
+    }
+
+    /// Words of data RAM, which bounds the program's data and the addresses it may use.
+    pub fn data_words(&self) -> usize {
+        self.data_words
+    }
+
+    /// Clock cycles `mnemonic` takes, from the spec or the defaults in the
+    /// [module docs](self).
+    ///
+    /// ```
+    /// use single_address_assembler::cpu::CpuSpec;
+    ///
+    /// let spec = CpuSpec::parse("add = { opcode = 2, cycles = 3 }\nmul = { opcode = 3 }").unwrap();
+    /// assert_eq!((spec.cycles("add"), spec.cycles("mul"), spec.cycles("br")), (3, 4, 1));
+    /// ```
+    pub fn cycles(&self, mnemonic: &str) -> u32 {
+        match self.cycles.get(mnemonic) {
+            Some(&cycles) => cycles,
+            None => match mnemonic.trim_end_matches("_indirect") {
+                "mul" | "muli" => 4,
+                "div" | "divi" | "rem" | "remi" => 8,
+                _ => 1,
+            },
+        }
+    }
+
+    /// Overrides the size of instruction ROM given by the spec.
+    ///
+    /// ```
+    /// use single_address_assembler::cpu::CpuSpec;
+    /// use single_address_assembler::{ParseError, Parser};
+    ///
+    /// let mut spec = CpuSpec::default();
+    /// spec.set_text_words(4);
+    /// let mut parser = Parser::new(".text clac clac clac clac halt");
+    /// parser.cpu = spec.clone();
+    /// let err = parser.parse_program().unwrap_err();
+    /// assert_eq!(err.to_string(), "too many instructions: `halt` exceeds the 4-word text limit");
+    ///
+    /// // Without a `prefix` opcode, the extra room is only reachable by falling into it.
+    /// spec.set_text_words(1024);
+    /// let source = format!(".text br far {} .label far halt", "noop ".repeat(300));
+    /// let mut parser = Parser::new(&source);
+    /// parser.cpu = spec;
+    /// parser.parse_program().unwrap();
+    /// let err = parser.address_program().unwrap_err();
+    /// assert!(matches!(err, ParseError::BranchOutOfReach(label, 0x12d, _) if label == "far"));
+    /// ```
+    pub fn set_text_words(&mut self, words: usize) {
+        self.text_words = words;
+    }
+
+    /// Overrides the size of data RAM given by the spec.
+    pub fn set_data_words(&mut self, words: usize) {
+        self.data_words = words;
+    }
+
+    /// Whether branches above `0xff` can be assembled with a `prefix` word.
+    pub fn has_long_branches(&self) -> bool {
+        self.encodings.contains_key("prefix")
+    }
+
+    /// Whether the variant implements `mnemonic`.
+    pub fn has(&self, mnemonic: &str) -> bool {
+        let mnemonic = if mnemonic == "halt" { "br" } else { mnemonic };
+        self.encodings.contains_key(mnemonic)
+    }
+
+    /// The opcode and alu_op of `mnemonic`, if the variant has it.
+    pub fn encoding(&self, mnemonic: &str) -> Option<Encoding> {
+        self.encodings.get(mnemonic).copied()
+    }
+
+    /// Encodes `instr` as the two bytes of its word. Instructions the variant lacks keep the
+    /// default encoding; [`AddressedProgram::validate`](crate::AddressedProgram::validate)
+    /// reports them, since they do not decode back to themselves.
+    ///
+    /// ```
+    /// use single_address_assembler::cpu::CpuSpec;
+    /// use single_address_assembler::AddressedInstruction::{self, *};
+    ///
+    /// let spec = CpuSpec::default();
+    /// for instr in [Add(3), Shift(-1), Store(9), Branch(2), NoOp, ClearAc].iter() {
+    ///     assert_eq!(spec.encode(instr), instr.bytes());
+    /// }
+    ///
+    /// let spec = CpuSpec::parse("stor = { opcode = 5 }\nbeqz = { opcode = 4 }").unwrap();
+    /// assert_eq!(spec.encode(&Store(9)), [0x50, 0x09]);
+    /// assert_eq!(spec.decode(0x4007), Ok(BranchZero(7)));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the operand does not fit in 8 bits; see [`try_encode`](Self::try_encode).
+    pub fn encode(&self, instr: &AddressedInstruction) -> [u8; 2] {
+        self.try_encode(instr)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Encodes `instr` as the two bytes of its word, or says which operand is too wide for
+    /// the 8-bit value field.
+    pub fn try_encode(&self, instr: &AddressedInstruction) -> Result<[u8; 2], EncodeError> {
+        match self.encoding(instr.mnemonic()) {
+            Some(encoding) => Ok([
+                (encoding.opcode << 4) | encoding.alu_op.unwrap_or(0),
+                instr.value_byte()?,
+            ]),
+            None => instr.try_bytes(),
+        }
+    }
+
+    /// Encodes `instr` as a 16-bit word.
+    pub fn word(&self, instr: &AddressedInstruction) -> u16 {
+        u16::from_be_bytes(self.encode(instr))
+    }
+
+    /// Decodes a word. An exact opcode and alu_op match wins over an instruction that
+    /// ignores the alu_op.
+    pub fn decode(&self, word: u16) -> Result<AddressedInstruction, DecodeError> {
+        let [high, value] = word.to_be_bytes();
+        let (opcode, alu_op) = (high >> 4, high & 0xf);
+
+        let with_opcode = || {
+            self.encodings
+                .iter()
+                .filter(move |(_, encoding)| encoding.opcode == opcode)
+        };
+        let mnemonic = with_opcode()
+            .find(|(_, encoding)| encoding.alu_op == Some(alu_op))
+            .or_else(|| with_opcode().find(|(_, encoding)| encoding.alu_op.is_none()))
+            .map(|(mnemonic, _)| *mnemonic);
+        match mnemonic {
+            Some(mnemonic) => Ok(AddressedInstruction::from_mnemonic(mnemonic, value).unwrap()),
+            None if with_opcode().next().is_some() => {
+                Err(DecodeError::InvalidAluOp(opcode, alu_op))
+            }
+            None => Err(DecodeError::InvalidOpcode(opcode)),
+        }
+    }
+}
+
+/// Splits `key = value` at the first `=`, trimming both sides.
+fn split_pair(text: &str) -> Option<(&str, &str)> {
+    let equals = text.find('=')?;
+    let key = text[..equals].trim();
+    let value = text[equals + 1..].trim();
+    if key.is_empty() || value.is_empty() {
+        None
+    } else {
+        Some((key, value))
+    }
+}
+
+/// Parses a `text_words = N` or `data_words = N` line of the `[memory]` table.
+fn parse_memory_size(line: &str) -> Result<(&str, usize), String> {
+    let (key, value) = split_pair(line).ok_or_else(|| {
+        format!(
+            "expected `text_words = N` or `data_words = N`, found `{}`",
+            line
+        )
+    })?;
+    if key != "text_words" && key != "data_words" {
+        return Err(format!(
+            "unknown field `{}`; expected `text_words` or `data_words`",
+            key
+        ));
+    }
+    // A prefix holds one byte, so targets past 16 bits cannot be reached; data pointers
+    // are no wider.
+    match value.parse() {
+        Ok(words) if (1..=0x10000).contains(&words) => Ok((key, words)),
+        _ => Err(format!("`{}` must be a number from 1 to 65536", key)),
+    }
+}
+
+/// Parses an inline table like `{ opcode = 2, alu_op = 0 }`, with its `cycles` if given.
+fn parse_table(text: &str) -> Result<(Encoding, Option<u32>), String> {
+    let inner = text
+        .strip_prefix('{')
+        .and_then(|rest| rest.strip_suffix('}'))
+        .ok_or_else(|| format!("expected an inline table, found `{}`", text))?;
+
+    let mut opcode = None;
+    let mut alu_op = None;
+    let mut cycles = None;
+    for field in inner.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+        let (key, value) = split_pair(field)
+            .ok_or_else(|| format!("expected `key = value`, found `{}`", field))?;
+        if key == "cycles" {
+            let cost = value
+                .parse()
+                .map_err(|_| "`cycles` must be a whole number".to_owned())?;
+            if cycles.replace(cost).is_some() {
+                return Err("`cycles` is given twice".to_owned());
+            }
+            continue;
+        }
+        let parsed = match value.strip_prefix("0x") {
+            Some(hex) => u8::from_str_radix(hex, 16),
+            None => value.parse(),
+        };
+        let value = match parsed {
+            Ok(value) if value <= 0xf => value,
+            _ => return Err(format!("`{}` must be a number from 0 to 15", key)),
+        };
+        let slot = match key {
+            "opcode" => &mut opcode,
+            "alu_op" => &mut alu_op,
+            other => {
+                return Err(format!(
+                    "unknown field `{}`; expected `opcode`, `alu_op`, or `cycles`",
+                    other
+                ))
+            }
+        };
+        if slot.replace(value).is_some() {
+            return Err(format!("`{}` is given twice", key));
+        }
+    }
+    let opcode = opcode.ok_or_else(|| "missing `opcode`".to_owned())?;
+    Ok((Encoding { opcode, alu_op }, cycles))
+}
@@ -0,0 +1,296 @@
+//! The command language of the `debug` subcommand, and a [`Debugger`] that runs it against a
+//! [`Machine`].
+//!
+//! ```
+//! use single_address_assembler::assemble;
+//! use single_address_assembler::debugger::{Command, Debugger};
+//! use single_address_assembler::diagnostic::SourceMap;
+//!
+//! let source = ".data .label n 3 .text .label loop clac add n subi 1 stor n beqz done br loop
+//!               .label done halt";
+//! let program = assemble(source).unwrap();
+//! let mut sources = SourceMap::new();
+//! sources.add_file("loop.s".to_owned(), source);
+//! let mut debugger = Debugger::new(&program, &sources, 1000);
+//!
+//! let mut run = |line: &str| debugger.execute(&line.parse::<Command>().unwrap());
+//! assert_eq!(run("b done").unwrap(), "breakpoint at 0x06 (`done`)");
+//! assert!(run("s 2").unwrap().contains("note: pc 0x02, ac = 3 (0x0003), 2 steps"));
+//! assert_eq!(run("p ac").unwrap(), "ac = 3 (0x0003)");
+//! assert_eq!(run("set mem:n 1").unwrap(), "mem:n = 1");
+//! assert!(run("c").unwrap().contains("breakpoint at pc 0x06"));
+//! assert_eq!(run("x n 2").unwrap(), "0x00 n: 0 (0x0000)\n0x01: 0 (0x0000)");
+//! assert!(run("b nowhere").unwrap_err().contains("no text label named `nowhere`"));
+//!
+//! assert_eq!("stpe".parse::<Command>().unwrap_err(), "unknown command `stpe`; did you mean `step`?");
+//! ```
+
+use crate::diagnostic::{Severity, SourceMap};
+use crate::emulator::{Event, Machine, StepResult};
+use crate::grade::{parse_number, Expectation};
+use crate::parser::closest_match;
+use crate::{AddressedProgram, Section};
+use std::str::FromStr;
+
+/// Every command with its short alias, usage, and description, for `help` and suggestions.
+const COMMANDS: &[(&str, &str, &str, &str)] = &[
+    ("step", "s", "step [N]", "run N instructions (default 1)"),
+    (
+        "continue",
+        "c",
+        "continue",
+        "run to a breakpoint or the end",
+    ),
+    (
+        "break",
+        "b",
+        "break LABEL|ADDR",
+        "pause before the instruction there",
+    ),
+    (
+        "print",
+        "p",
+        "print ac|pc|LABEL",
+        "show a register or data word",
+    ),
+    (
+        "x",
+        "x",
+        "x LABEL|ADDR [N]",
+        "show N data words (default 1)",
+    ),
+    (
+        "set",
+        "set",
+        "set LOCATION VALUE",
+        "set `ac`, `mem:LABEL`, or `mem:ADDR`",
+    ),
+    ("where", "w", "where", "show the current source line"),
+    ("help", "h", "help", "list these commands"),
+    ("quit", "q", "quit", "leave the debugger"),
+];
+
+/// One line typed at the debugger prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Step(usize),
+    Continue,
+    Break(String),
+    Print(String),
+    Examine(String, usize),
+    Set(Expectation),
+    Where,
+    Help,
+    Quit,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (&name, args) = words
+            .split_first()
+            .ok_or_else(|| "expected a command; try `help`".to_owned())?;
+        let full = COMMANDS
+            .iter()
+            .find(|(full, short, _, _)| name == *full || name == *short)
+            .map(|(full, _, _, _)| *full)
+            .ok_or_else(|| {
+                let hint = closest_match(name, COMMANDS.iter().map(|(full, _, _, _)| *full))
+                    .map(|full| format!("; did you mean `{}`?", full))
+                    .unwrap_or_else(|| "; try `help`".to_owned());
+                format!("unknown command `{}`{}", name, hint)
+            })?;
+        let usage = || {
+            let (_, _, usage, _) = COMMANDS.iter().find(|(f, _, _, _)| *f == full).unwrap();
+            format!("usage: {}", usage)
+        };
+        let count = |arg: Option<&&str>| match arg {
+            None => Ok(1),
+            Some(arg) => match arg.parse() {
+                Ok(n) if n > 0 => Ok(n),
+                _ => Err(format!("`{}` is not a positive count", arg)),
+            },
+        };
+
+        let command = match (full, args) {
+            ("step", [] | [_]) => Self::Step(count(args.first())?),
+            ("continue", []) => Self::Continue,
+            ("break", [at]) => Self::Break((*at).to_owned()),
+            ("print", [what]) => Self::Print((*what).to_owned()),
+            ("x", [at] | [at, _]) => Self::Examine((*at).to_owned(), count(args.get(1))?),
+            ("set", [location, value]) => Self::Set(format!("{}={}", location, value).parse()?),
+            ("where", []) => Self::Where,
+            ("help", []) => Self::Help,
+            ("quit", []) => Self::Quit,
+            _ => return Err(usage()),
+        };
+        Ok(command)
+    }
+}
+
+/// A program loaded into a [`Machine`] and paused between commands.
+pub struct Debugger<'p> {
+    pub machine: Machine,
+    program: &'p AddressedProgram,
+    sources: &'p SourceMap,
+    /// Most instructions a single `step` or `continue` may run.
+    max_steps: usize,
+}
+
+impl<'p> Debugger<'p> {
+    /// A debugger paused before the first instruction of `program`, whose source is
+    /// `sources`.
+    pub fn new(program: &'p AddressedProgram, sources: &'p SourceMap, max_steps: usize) -> Self {
+        Self {
+            machine: Machine::new(program),
+            program,
+            sources,
+            max_steps,
+        }
+    }
+
+    /// Runs `command`, returning what to show the user or why it could not run; a trap is
+    /// shown, not an error. `Quit` does nothing; leaving is up to the caller.
+    pub fn execute(&mut self, command: &Command) -> Result<String, String> {
+        match command {
+            Command::Step(n) => {
+                for _ in 0..*n {
+                    match self.machine.step() {
+                        Ok(StepResult::Executed(_)) => {}
+                        Ok(StepResult::Stopped(stop)) => {
+                            return Ok(self.location(Severity::Note, &stop))
+                        }
+                        Err(trap) => return Ok(self.location(Severity::Error, &trap)),
+                    }
+                }
+                Ok(self.location(Severity::Note, &format!("pc {:#04x}", self.machine.pc)))
+            }
+            Command::Continue => {
+                let limit = self.machine.steps.saturating_add(self.max_steps);
+                match self.machine.resume(limit) {
+                    Ok(Event::Stopped(stop)) => Ok(self.location(Severity::Note, &stop)),
+                    Ok(Event::Breakpoint(pc)) => {
+                        Ok(self.location(Severity::Note, &format!("breakpoint at pc {:#04x}", pc)))
+                    }
+                    Ok(Event::Watchpoint { .. }) => {
+                        Ok(self.location(Severity::Note, &"watchpoint"))
+                    }
+                    Err(trap) => Ok(self.location(Severity::Error, &trap)),
+                }
+            }
+            Command::Break(at) => {
+                let address = self.address(at, Section::Text)?;
+                self.machine.breakpoints.insert(address);
+                Ok(format!(
+                    "breakpoint at {:#04x}{}",
+                    address,
+                    self.name(Section::Text, address)
+                ))
+            }
+            Command::Print(what) => match what.as_str() {
+                "ac" => Ok(format!("ac = {}", word(self.machine.ac))),
+                "pc" => Ok(format!("pc = {:#04x}", self.machine.pc)),
+                label => {
+                    let address = self.address(label, Section::Data)?;
+                    Ok(format!("{} = {}", label, word(self.machine.data[address])))
+                }
+            },
+            Command::Examine(at, count) => {
+                let start = self.address(at, Section::Data)?;
+                let end = start.saturating_add(*count).min(self.machine.data.len());
+                let lines: Vec<String> = (start..end)
+                    .map(|address| {
+                        let name = match self.program.symbol_at(Section::Data, address) {
+                            Some(symbol) => format!(" {}", symbol.name),
+                            None => String::new(),
+                        };
+                        format!(
+                            "{:#04x}{}: {}",
+                            address,
+                            name,
+                            word(self.machine.data[address])
+                        )
+                    })
+                    .collect();
+                Ok(lines.join("\n"))
+            }
+            Command::Set(assignment) => {
+                assignment.apply(&mut self.machine, self.program)?;
+                Ok(assignment.to_string().replacen('=', " = ", 1))
+            }
+            Command::Where => {
+                Ok(self.location(Severity::Note, &format!("pc {:#04x}", self.machine.pc)))
+            }
+            Command::Help => {
+                let lines: Vec<String> = COMMANDS
+                    .iter()
+                    .map(|(_, _, usage, description)| format!("{:<20}{}", usage, description))
+                    .collect();
+                Ok(format!(
+                    "{}\nshort forms: s, c, b, p, w, h, q; an empty line repeats the last command",
+                    lines.join("\n")
+                ))
+            }
+            Command::Quit => Ok(String::new()),
+        }
+    }
+
+    /// `message` with the machine state, pointing at the source of the instruction at the PC.
+    fn location(&self, severity: Severity, message: &dyn std::fmt::Display) -> String {
+        let message = format!(
+            "{}, ac = {}, {} steps",
+            message,
+            word(self.machine.ac),
+            self.machine.steps
+        );
+        let span = self.program.text_spans.get(self.machine.pc).cloned();
+        self.sources.render(severity, message, span)
+    }
+
+    /// The address of a label in `section`, or of a decimal or `0x` hex address.
+    fn address(&self, at: &str, section: Section) -> Result<usize, String> {
+        if at.starts_with(|c: char| c.is_ascii_digit()) {
+            let limit = match section {
+                Section::Text => self.program.text.len(),
+                Section::Data => self.machine.data.len(),
+            };
+            return match parse_number(at) {
+                Some(address) if (0..limit as i64).contains(&address) => Ok(address as usize),
+                _ => Err(format!("`{}` is not a {} address", at, section)),
+            };
+        }
+        match self.program.symbol(at) {
+            Some(symbol) if symbol.section == section => Ok(symbol.address),
+            _ => {
+                let names = self
+                    .program
+                    .symbols
+                    .iter()
+                    .filter(|symbol| symbol.section == section)
+                    .map(|symbol| symbol.name.as_str());
+                let hint = closest_match(at, names)
+                    .map(|name| format!("; did you mean `{}`?", name))
+                    .unwrap_or_default();
+                Err(format!(
+                    "there is no {} label named `{}`{}",
+                    section, at, hint
+                ))
+            }
+        }
+    }
+
+    /// ` (`label`)` for the label at `address`, or nothing.
+    fn name(&self, section: Section, address: usize) -> String {
+        match self.program.symbol_at(section, address) {
+            Some(symbol) => format!(" (`{}`)", symbol.name),
+            None => String::new(),
+        }
+    }
+}
+
+/// A data word in decimal and hex.
+fn word(value: i16) -> String {
+    format!("{} ({:#06x})", value, value as u16)
+}
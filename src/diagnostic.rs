@@ -0,0 +1,370 @@
+use ansi_term::{Colour, Style};
+use logos::Span;
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+            Self::Note => write!(f, "note"),
+        }
+    }
+}
+
+impl Severity {
+    /// Style of the severity label in colored output.
+    fn style(self) -> Style {
+        match self {
+            Self::Error => Colour::Red.bold(),
+            Self::Warning => Colour::Yellow.bold(),
+            Self::Note => Colour::Green.bold(),
+        }
+    }
+
+    /// Style of the underline: the severity's color for the primary span, yellow for the
+    /// secondary spans that notes point at.
+    fn underline_style(self) -> Style {
+        match self {
+            Self::Note => Colour::Yellow.bold(),
+            _ => self.style(),
+        }
+    }
+}
+
+/// The concatenation of every input file, with enough bookkeeping to map an offset in the
+/// combined text back to the file it came from.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    pub text: String,
+    /// Render diagnostics with ANSI colors.
+    pub color: bool,
+    files: Vec<(String, usize)>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a file's contents, separated from the previous file by a newline so tokens
+    /// never run together across the boundary.
+    pub fn add_file(&mut self, name: String, contents: &str) {
+        if !self.files.is_empty() {
+            self.text.push('\n');
+        }
+        self.files.push((name, self.text.len()));
+        self.text.push_str(contents);
+    }
+
+    /// Byte offsets in `text` at which each file starts.
+    pub fn file_starts(&self) -> Vec<usize> {
+        self.files.iter().map(|(_, start)| *start).collect()
+    }
+
+    /// Directory of each file, taking its name as a path; stdin's is empty, which resolves
+    /// relative paths against the working directory.
+    pub fn file_dirs(&self) -> Vec<PathBuf> {
+        self.files
+            .iter()
+            .map(|(name, _)| Path::new(name).parent().unwrap_or(Path::new("")).to_owned())
+            .collect()
+    }
+
+    /// The name and starting offset of the file containing `offset`.
+    pub fn file_at(&self, offset: usize) -> (&str, usize) {
+        let index = match self
+            .files
+            .binary_search_by_key(&offset, |(_, start)| *start)
+        {
+            Ok(index) => index,
+            Err(index) => index.saturating_sub(1),
+        };
+        self.files
+            .get(index)
+            .map_or(("<input>", 0), |(name, start)| (name.as_str(), *start))
+    }
+
+    /// The file, 1-based line, and 1-based column of `offset` in `text`.
+    pub fn location(&self, offset: usize) -> (&str, usize, usize) {
+        let (name, start) = self.file_at(offset);
+        let (line, column) = line_col(&self.text[start..], offset - start);
+        (name, line, column)
+    }
+
+    /// The name of the first file, used for diagnostics without a location.
+    pub fn primary_name(&self) -> &str {
+        self.files
+            .first()
+            .map_or("<input>", |(name, _)| name.as_str())
+    }
+
+    /// Renders a diagnostic as `file:line:col: severity: message`, followed by the offending
+    /// source line with the span underlined. Lines and columns count from the start of the
+    /// file the span is in.
+    pub fn render<M: fmt::Display>(
+        &self,
+        severity: Severity,
+        message: M,
+        span: Option<Span>,
+    ) -> String {
+        let span = match span {
+            Some(span) => span,
+            None => {
+                return Diagnostic::new(severity, message, "", None)
+                    .render(self.primary_name(), self.color)
+            }
+        };
+
+        let (filename, file_start) = self.file_at(span.start);
+        let source = &self.text[file_start..];
+        let start = span.start.min(self.text.len()) - file_start;
+        let end = span.end.min(self.text.len()) - file_start;
+        Diagnostic::new(severity, message, source, Some(start..end)).render(filename, self.color)
+    }
+}
+
+/// Returns the 1-based line and column of byte `offset` in `source`.
+pub fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(source.len());
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+/// The source line a diagnostic points at, copied out so it can be shown after the source
+/// is gone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Excerpt {
+    /// 1-based line of the start of the span.
+    pub line: usize,
+    /// 1-based column, in characters, of the start of the span.
+    pub column: usize,
+    /// The whole line, without its line ending.
+    pub text: String,
+    /// The characters of `text` to underline, counted from 0; at least one wide, and cut
+    /// off at the end of the line for spans that run past it.
+    pub underline: Range<usize>,
+}
+
+impl Excerpt {
+    /// The line of `source` that `span` starts on, with the part of `span` on it underlined.
+    pub fn new(source: &str, span: Span) -> Self {
+        let (line, column) = line_col(source, span.start);
+        let start = span.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        let text = source[line_start..line_end].trim_end_matches('\r');
+
+        let underline_end = span.end.min(line_start + text.len()).max(start);
+        let width = source[start..underline_end].chars().count().max(1);
+        Self {
+            line,
+            column,
+            text: text.to_owned(),
+            underline: column - 1..column - 1 + width,
+        }
+    }
+}
+
+/// A message with the source it points at, owning everything it needs to be rendered.
+///
+/// ```
+/// use single_address_assembler::diagnostic::{Diagnostic, Severity};
+///
+/// let source = "add x\nstor  y";
+/// let diagnostic = Diagnostic::new(Severity::Warning, "odd spacing", source, Some(10..12));
+/// let excerpt = diagnostic.excerpt.as_ref().unwrap();
+/// assert_eq!((excerpt.line, excerpt.column, &*excerpt.text), (2, 5, "stor  y"));
+/// assert_eq!(
+///     diagnostic.render("prog.s", false),
+///     "prog.s:2:5: warning: odd spacing\n    stor  y\n        ^^"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// Where the message points, if anywhere.
+    pub excerpt: Option<Excerpt>,
+    /// A second location with a note about it, such as where a duplicate label was first
+    /// defined, rendered after the message.
+    pub note: Option<Box<Diagnostic>>,
+}
+
+impl Diagnostic {
+    /// `message` about `span` of `source`, or about no location in particular.
+    pub fn new<M: fmt::Display>(
+        severity: Severity,
+        message: M,
+        source: &str,
+        span: Option<Span>,
+    ) -> Self {
+        Self {
+            severity,
+            message: message.to_string(),
+            excerpt: span.map(|span| Excerpt::new(source, span)),
+            note: None,
+        }
+    }
+
+    /// This diagnostic with `note` about `span` of `source` attached.
+    pub fn with_note(mut self, note: &str, source: &str, span: Span) -> Self {
+        self.note = Some(Box::new(Self::new(
+            Severity::Note,
+            note,
+            source,
+            Some(span),
+        )));
+        self
+    }
+
+    /// Renders the diagnostic as `file:line:col: severity: message`, followed by the
+    /// excerpt with its span underlined, then the note the same way. With `color` set, the
+    /// location, severity, and underline are highlighted with ANSI escapes.
+    pub fn render(&self, filename: &str, color: bool) -> String {
+        let paint = |style: Style, text: &str| {
+            if color {
+                style.paint(text).to_string()
+            } else {
+                text.to_owned()
+            }
+        };
+        let header = paint(self.severity.style(), &self.severity.to_string());
+        let message = paint(Style::new().bold(), &self.message);
+
+        let mut out = match &self.excerpt {
+            None => {
+                let location = paint(Colour::Cyan.normal(), &format!("{}:", filename));
+                format!("{} {}: {}", location, header, message)
+            }
+            Some(excerpt) => {
+                let location = paint(
+                    Colour::Cyan.normal(),
+                    &format!("{}:{}:{}:", filename, excerpt.line, excerpt.column),
+                );
+                format!(
+                    "{} {}: {}\n    {}\n    {}{}",
+                    location,
+                    header,
+                    message,
+                    excerpt.text,
+                    " ".repeat(excerpt.underline.start),
+                    paint(
+                        self.severity.underline_style(),
+                        &"^".repeat(excerpt.underline.len())
+                    )
+                )
+            }
+        };
+        if let Some(note) = &self.note {
+            out.push('\n');
+            out.push_str(&note.render(filename, color));
+        }
+        out
+    }
+}
+
+/// Renders as from an unnamed `<input>` file, without color.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render("<input>", false))
+    }
+}
+
+/// Renders a diagnostic as `file:line:col: severity: message`, followed by the offending
+/// source line with the span underlined. With `color` set, the location, severity, and
+/// underline are highlighted with ANSI escapes; otherwise the output is plain text.
+pub fn render<M: fmt::Display>(
+    filename: &str,
+    source: &str,
+    severity: Severity,
+    message: M,
+    span: Option<Span>,
+    color: bool,
+) -> String {
+    Diagnostic::new(severity, message, source, span).render(filename, color)
+}
+
+/// Where a source file stops being valid UTF-8.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodingError {
+    /// Byte offset of the first invalid byte in the file.
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+    pub byte: u8,
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "file is not valid UTF-8: byte {:#04x} at offset {}; re-save it as UTF-8",
+            self.byte, self.offset
+        )
+    }
+}
+
+/// The text of a source file, without the byte order mark editors such as Notepad put at
+/// the start. Line endings are left alone; the lexer treats `\r` as whitespace.
+///
+/// ```
+/// use single_address_assembler::assemble;
+/// use single_address_assembler::diagnostic::{decode_source, decode_source_lossy};
+///
+/// let text = decode_source(b"\xef\xbb\xbf.text\r\nclac\r\nhalt\r\n").unwrap();
+/// assert_eq!(text, ".text\r\nclac\r\nhalt\r\n");
+/// assert_eq!(assemble(&text).unwrap().text.len(), 2);
+///
+/// let latin1 = b".text\nclac # caf\xe9\nadd\xa0x\n";
+/// let err = decode_source(latin1).unwrap_err();
+/// assert_eq!((err.offset, err.line, err.column, err.byte), (16, 2, 11, 0xe9));
+/// assert_eq!(
+///     err.to_string(),
+///     "file is not valid UTF-8: byte 0xe9 at offset 16; re-save it as UTF-8"
+/// );
+/// assert_eq!(decode_source_lossy(latin1), ".text\nclac # caf\u{fffd}\nadd\u{fffd}x\n");
+///
+/// // Text that still has its mark assembles too.
+/// assert!(assemble("\u{feff}.text clac halt").is_ok());
+/// ```
+pub fn decode_source(bytes: &[u8]) -> Result<String, EncodingError> {
+    let text = strip_bom(bytes);
+    match std::str::from_utf8(text) {
+        Ok(text) => Ok(text.to_owned()),
+        Err(err) => {
+            let valid = err.valid_up_to();
+            let (line, column) = line_col(std::str::from_utf8(&text[..valid]).unwrap(), valid);
+            Err(EncodingError {
+                offset: bytes.len() - text.len() + valid,
+                line,
+                column,
+                byte: text[valid],
+            })
+        }
+    }
+}
+
+/// The text of a source file without its byte order mark, with each invalid UTF-8
+/// sequence replaced by U+FFFD so the rest can still be parsed.
+pub fn decode_source_lossy(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(strip_bom(bytes)).into_owned()
+}
+
+fn strip_bom(bytes: &[u8]) -> &[u8] {
+    bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes)
+}
@@ -0,0 +1,114 @@
+use super::cpu::CpuSpec;
+use super::{Address, AddressedInstruction, DecodeError};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// Decodes every word of a text image with `cpu`'s encodings.
+pub fn decode_text(words: &[u16], cpu: &CpuSpec) -> Vec<Result<AddressedInstruction, DecodeError>> {
+    words.iter().map(|&word| cpu.decode(word)).collect()
+}
+
+/// Renders decoded text (and optionally data) one entry per line with its address and word.
+///
+/// With `labels`, referenced addresses get synthesized names (`L_xx` for branch targets,
+/// `D_xx` for data) that are used in place of raw operands. A branch after a `prefix` is
+/// labelled with its full target, and the data words at the offsets in `code_addresses`
+/// are shown as `@L_xx`.
+///
+/// ```
+/// use single_address_assembler::{assemble, disasm};
+///
+/// let program = assemble(".data .label t .jumptable b .text .label a halt .label b br a")
+///     .unwrap();
+/// let out = disasm::disassemble(
+///     &program.text_words(),
+///     Some(&program.data),
+///     &program.code_addresses,
+///     true,
+///     &program.cpu,
+/// );
+/// assert!(out.contains("L_01:\n  01  6000  br L_00\n"));
+/// assert!(out.ends_with(".data\n  00  0001  @L_01\n"));
+/// ```
+pub fn disassemble(
+    text: &[u16],
+    data: Option<&[i16]>,
+    code_addresses: &[usize],
+    labels: bool,
+    cpu: &CpuSpec,
+) -> String {
+    let decoded = decode_text(text, cpu);
+    let targets: Vec<Option<usize>> = decoded
+        .iter()
+        .enumerate()
+        .map(|(index, instr)| {
+            let low = usize::from(instr.as_ref().ok()?.branch_target()?);
+            match index.checked_sub(1).map(|prev| &decoded[prev]) {
+                Some(Ok(AddressedInstruction::Prefix(high))) => Some(usize::from(*high) << 8 | low),
+                _ => Some(low),
+            }
+        })
+        .collect();
+
+    let mut text_targets: BTreeSet<usize> = BTreeSet::new();
+    let mut data_targets = BTreeSet::new();
+    if labels {
+        text_targets.extend(targets.iter().flatten().copied());
+        for instr in decoded.iter().flatten() {
+            data_targets.extend(instr.data_address());
+        }
+        for &offset in code_addresses {
+            if let Some(&word) = data.and_then(|data| data.get(offset)) {
+                text_targets.insert(usize::from(word as u16));
+            }
+        }
+    }
+
+    let mut out = String::new();
+    writeln!(out, ".text").unwrap();
+    for (address, (word, instr)) in text.iter().zip(&decoded).enumerate() {
+        if text_targets.contains(&address) {
+            writeln!(out, "{:>12}L_{:02x}:", "", address).unwrap();
+        }
+        let rendered = match instr {
+            Ok(instr) if labels => labelled(instr, targets[address]),
+            Ok(instr) => instr.to_string(),
+            Err(err) => format!("??? # {}", err),
+        };
+        writeln!(out, "  {:02x}  {:04x}  {}", address, word, rendered).unwrap();
+    }
+
+    if let Some(data) = data {
+        writeln!(out, ".data").unwrap();
+        for (address, value) in data.iter().enumerate() {
+            if data_targets.contains(&(address as Address)) {
+                writeln!(out, "{:>12}D_{:02x}:", "", address).unwrap();
+            }
+            if labels && code_addresses.contains(&address) {
+                writeln!(
+                    out,
+                    "  {:02x}  {:04x}  @L_{:02x}",
+                    address, *value as u16, value
+                )
+                .unwrap();
+            } else {
+                writeln!(out, "  {:02x}  {:04x}  {}", address, *value as u16, value).unwrap();
+            }
+        }
+    }
+
+    out
+}
+
+fn labelled(instr: &AddressedInstruction, target: Option<usize>) -> String {
+    if let Some(target) = target {
+        format!("{} L_{:02x}", instr.mnemonic(), target)
+    } else if let Some(address) = instr.data_address() {
+        match instr.mnemonic().strip_suffix("_indirect") {
+            Some(direct) => format!("{} [D_{:02x}]", direct, address),
+            None => format!("{} D_{:02x}", instr.mnemonic(), address),
+        }
+    } else {
+        instr.to_string()
+    }
+}
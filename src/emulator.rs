@@ -0,0 +1,592 @@
+//! A model of the One-Address CPU for running assembled programs.
+//!
+//! Arithmetic wraps at 16 bits; `and` is bitwise; immediates are sign-extended to 16 bits.
+//! `shift n` shifts the accumulator left by `n` for positive `n` and arithmetically right
+//! by `-n` for negative `n`, with shifts of 16 or more leaving only zeros (left) or copies
+//! of the sign bit (right):
+//!
+//! ```
+//! use single_address_assembler::assemble;
+//! use single_address_assembler::emulator::{Machine, Stop};
+//!
+//! let program = assemble(
+//!     ".data .label x .number -8 .label y .number 0
+//!      .text clac add x shift -2 stor y shift 3 andi 15 halt",
+//! )
+//! .unwrap();
+//! let mut machine = Machine::new(&program);
+//! assert_eq!(machine.run(100), Ok(Stop::Halted));
+//! assert_eq!(machine.read(1), -2);
+//! assert_eq!(machine.ac, (-16i16) & 15);
+//! ```
+
+use crate::snapshot::{program_hash, MachineState};
+use crate::{Address, AddressedInstruction, AddressedProgram};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Why a program stopped on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// The PC moved past the last instruction.
+    Finished,
+    /// The next instruction branches to itself and can never leave.
+    Halted,
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Finished => write!(f, "ran past the last instruction"),
+            Self::Halted => write!(f, "halted"),
+        }
+    }
+}
+
+/// A store to data memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryWrite {
+    pub address: Address,
+    pub old: i16,
+    pub new: i16,
+}
+
+/// Everything one executed instruction did, for tracing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    /// The step count after this instruction, so the first instruction is step 1.
+    pub step: usize,
+    pub pc: usize,
+    pub instr: AddressedInstruction,
+    pub ac_before: i16,
+    pub ac_after: i16,
+    pub write: Option<MemoryWrite>,
+}
+
+/// The outcome of a single [`Machine::step`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepResult {
+    /// The instruction ran and the PC moved on.
+    Executed(Step),
+    /// Nothing ran because the program has stopped.
+    Stopped(Stop),
+}
+
+/// Why [`Machine::resume`] handed control back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// The program stopped on its own.
+    Stopped(Stop),
+    /// The PC reached a breakpoint; the instruction there has not run yet.
+    Breakpoint(usize),
+    /// The `stor` at `pc` changed a watched data word.
+    Watchpoint {
+        pc: usize,
+        address: Address,
+        old: i16,
+        new: i16,
+    },
+}
+
+/// A condition that ends a run abnormally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trap {
+    /// With [`DivZeroMode::Trap`], `instr` at `pc` divided or took a remainder by zero.
+    DivisionByZero {
+        pc: usize,
+        instr: AddressedInstruction,
+    },
+    /// The program was still running after the given number of steps.
+    StepLimit(usize),
+    /// With [`Machine::trap_uninit`] set, `instr` at `pc` read a data word that was neither
+    /// in the data image nor stored to.
+    UninitializedRead {
+        pc: usize,
+        instr: AddressedInstruction,
+        address: Address,
+    },
+    /// The instruction at `pc` read or wrote a data address past the end of data RAM.
+    DataOutOfRange { pc: usize, address: usize },
+    /// The branch at `pc` jumped past the end of text memory.
+    PcOutOfRange { pc: usize, target: usize },
+}
+
+impl fmt::Display for Trap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::DivisionByZero { pc, instr } => {
+                write!(f, "`{}` at pc {:#04x} divided by zero", instr, pc)
+            }
+            Self::StepLimit(steps) => write!(f, "still running after {} steps", steps),
+            Self::UninitializedRead { pc, instr, address } => write!(
+                f,
+                "`{}` at pc {:#04x} read uninitialized data word {:#04x}",
+                instr, pc, address
+            ),
+            Self::DataOutOfRange { pc, address } => write!(
+                f,
+                "data address {:#04x} used at pc {:#04x} is outside data memory",
+                address, pc
+            ),
+            Self::PcOutOfRange { pc, target } => write!(
+                f,
+                "branch at pc {:#04x} jumps to {:#04x}, outside text memory",
+                pc, target
+            ),
+        }
+    }
+}
+
+/// What a divide or remainder by zero does, since circuits differ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DivZeroMode {
+    /// Stop with [`Trap::DivisionByZero`].
+    Trap,
+    /// Leave zero in the accumulator.
+    Zero,
+    /// Divides give `i16::MAX`, or `i16::MIN` for a negative accumulator; remainders
+    /// leave the accumulator unchanged.
+    Saturate,
+}
+
+impl DivZeroMode {
+    /// The mode named by `--on-div-zero`.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "zero" => Self::Zero,
+            "saturate" => Self::Saturate,
+            _ => Self::Trap,
+        }
+    }
+}
+
+/// The architectural state of the One-Address CPU.
+#[derive(Debug, Clone)]
+pub struct Machine {
+    pub ac: i16,
+    pub pc: usize,
+    pub steps: usize,
+    pub data: Vec<i16>,
+    /// Text addresses at which [`resume`](Self::resume) pauses before executing.
+    pub breakpoints: BTreeSet<usize>,
+    /// Data addresses whose changes make [`resume`](Self::resume) pause.
+    pub watchpoints: BTreeSet<Address>,
+    /// Whether reading a data word that is not in the data image and has not been stored to
+    /// traps with [`Trap::UninitializedRead`], rather than reading zero.
+    ///
+    /// ```
+    /// use single_address_assembler::assemble;
+    /// use single_address_assembler::emulator::{Machine, Trap};
+    /// use single_address_assembler::AddressedInstruction;
+    ///
+    /// // Leave `y` out of the data image, as if it had never been given a value.
+    /// let mut program = assemble(".data .label x 5 .label y 0 .text clac add x add y halt").unwrap();
+    /// program.data.truncate(1);
+    ///
+    /// let mut machine = Machine::new(&program);
+    /// assert!(machine.run(100).is_ok());
+    ///
+    /// let mut machine = Machine::new(&program);
+    /// machine.trap_uninit = true;
+    /// let trap = Trap::UninitializedRead { pc: 2, instr: AddressedInstruction::Add(1), address: 1 };
+    /// assert_eq!(machine.run(100), Err(trap));
+    /// assert_eq!(machine.ac, 5);
+    ///
+    /// let mut machine = Machine::new(&program);
+    /// machine.trap_uninit = true;
+    /// machine.write(1, 2);
+    /// assert!(machine.run(100).is_ok());
+    /// ```
+    pub trap_uninit: bool,
+    /// What dividing by zero does; [`DivZeroMode::Trap`] unless set otherwise.
+    ///
+    /// ```
+    /// use single_address_assembler::emulator::{DivZeroMode, Machine, Trap};
+    /// use single_address_assembler::{AddressedInstruction, Parser};
+    ///
+    /// let run = |source, mode| {
+    ///     let mut parser = Parser::new(source);
+    ///     parser.options.allow_div_zero = true;
+    ///     parser.parse_program().unwrap();
+    ///     let mut machine = Machine::new(&parser.address_program().unwrap());
+    ///     machine.on_div_zero = mode;
+    ///     machine.run(10).map(|_| machine.ac)
+    /// };
+    /// let div = ".data .label zero 0 .text clac subi 9 div zero halt";
+    /// let remi = ".text clac subi 9 remi 0 halt";
+    ///
+    /// let instr = AddressedInstruction::Divide(0);
+    /// assert_eq!(run(div, DivZeroMode::Trap), Err(Trap::DivisionByZero { pc: 2, instr }));
+    /// assert_eq!(run(div, DivZeroMode::Zero), Ok(0));
+    /// assert_eq!(run(div, DivZeroMode::Saturate), Ok(i16::MIN));
+    ///
+    /// let instr = AddressedInstruction::RemainderImmediate(0);
+    /// assert_eq!(run(remi, DivZeroMode::Trap), Err(Trap::DivisionByZero { pc: 2, instr }));
+    /// assert_eq!(run(remi, DivZeroMode::Zero), Ok(0));
+    /// assert_eq!(run(remi, DivZeroMode::Saturate), Ok(-9));
+    /// ```
+    pub on_div_zero: DivZeroMode,
+    /// Which data words hold a value from the data image or a store.
+    initialized: Vec<bool>,
+    /// Set while paused at a breakpoint, so resuming runs the instruction there.
+    at_breakpoint: bool,
+    /// The high byte from a `prefix` that the next branch has not consumed yet.
+    prefix: Option<u8>,
+    text: Vec<AddressedInstruction>,
+    /// Words of text memory, past which branches trap.
+    text_words: usize,
+    /// [`program_hash`] of the loaded program, recorded in snapshots.
+    program_hash: u64,
+}
+
+impl Machine {
+    /// A machine with the program's text loaded and data RAM initialized from its data,
+    /// with memories the sizes given by the program's CPU spec.
+    pub fn new(program: &AddressedProgram) -> Self {
+        Self::with_memory(program, program.cpu.text_words(), program.cpu.data_words())
+    }
+
+    /// Like [`new`](Self::new), but with `text_words` of text memory and `data_words` of
+    /// data RAM. Memory operands and branch targets outside them trap.
+    ///
+    /// ```
+    /// use single_address_assembler::assemble;
+    /// use single_address_assembler::emulator::{Machine, Trap};
+    ///
+    /// let program = assemble(".data .label x 5 .label y 7 .text clac add y halt").unwrap();
+    /// assert_eq!(Machine::with_memory(&program, 256, 1024).data.len(), 1024);
+    ///
+    /// let mut machine = Machine::with_memory(&program, 256, 1);
+    /// assert_eq!(machine.run(10), Err(Trap::DataOutOfRange { pc: 1, address: 1 }));
+    ///
+    /// let program = assemble(".text clac br end noop .label end halt").unwrap();
+    /// let mut machine = Machine::with_memory(&program, 2, 256);
+    /// assert_eq!(machine.run(10), Err(Trap::PcOutOfRange { pc: 1, target: 3 }));
+    /// ```
+    pub fn with_memory(program: &AddressedProgram, text_words: usize, data_words: usize) -> Self {
+        let loaded = program.data.len().min(data_words);
+        let mut memory = vec![0; data_words];
+        memory[..loaded].copy_from_slice(&program.data[..loaded]);
+        let mut initialized = vec![false; data_words];
+        initialized[..loaded].fill(true);
+        Self {
+            ac: 0,
+            pc: 0,
+            steps: 0,
+            data: memory,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeSet::new(),
+            trap_uninit: false,
+            on_div_zero: DivZeroMode::Trap,
+            initialized,
+            at_breakpoint: false,
+            prefix: None,
+            text: program.text.clone(),
+            text_words,
+            program_hash: program_hash(program),
+        }
+    }
+
+    /// The state needed to continue this run later with [`restore`](Self::restore).
+    pub fn snapshot(&self) -> MachineState {
+        MachineState {
+            program: self.program_hash,
+            ac: self.ac,
+            pc: self.pc,
+            steps: self.steps,
+            prefix: self.prefix,
+            data: self.data.clone(),
+        }
+    }
+
+    /// Continues from a [`snapshot`](Self::snapshot) taken of a machine running the same
+    /// program with the same size of data RAM. Every data word counts as initialized.
+    pub fn restore(&mut self, state: &MachineState) -> Result<(), String> {
+        if state.program != self.program_hash {
+            return Err("the snapshot was taken from a different program".to_owned());
+        }
+        if state.data.len() != self.data.len() {
+            return Err(format!(
+                "the snapshot has {} words of data memory, but this machine has {}",
+                state.data.len(),
+                self.data.len()
+            ));
+        }
+        self.ac = state.ac;
+        self.pc = state.pc;
+        self.steps = state.steps;
+        self.prefix = state.prefix;
+        self.data.clone_from(&state.data);
+        self.initialized.fill(true);
+        self.at_breakpoint = false;
+        Ok(())
+    }
+
+    /// The loaded instructions.
+    pub fn text(&self) -> &[AddressedInstruction] {
+        &self.text
+    }
+
+    /// The word at `addr` in data memory.
+    pub fn read(&self, addr: Address) -> i16 {
+        self.data[usize::from(addr)]
+    }
+
+    /// Overwrites the word at `addr` in data memory, which then counts as initialized.
+    pub fn write(&mut self, addr: Address, value: i16) {
+        self.data[usize::from(addr)] = value;
+        self.initialized[usize::from(addr)] = true;
+    }
+
+    /// Executes one instruction, or reports why execution cannot continue.
+    pub fn step(&mut self) -> Result<StepResult, Trap> {
+        use AddressedInstruction::*;
+
+        let instr = match self.text.get(self.pc) {
+            Some(instr) => *instr,
+            None => return Ok(StepResult::Stopped(Stop::Finished)),
+        };
+        // A long branch starts at its prefix, so that is where a branch to itself points.
+        let (start, high) = match self.prefix {
+            Some(high) => (self.pc.saturating_sub(1), usize::from(high) << 8),
+            None => (self.pc, 0),
+        };
+        let target = |low: Address| high | usize::from(low);
+        match instr {
+            Branch(low) if target(low) == start => return Ok(StepResult::Stopped(Stop::Halted)),
+            BranchZero(low) if target(low) == start && self.ac == 0 => {
+                return Ok(StepResult::Stopped(Stop::Halted))
+            }
+            _ => {}
+        }
+
+        let ac_before = self.ac;
+        let mut write = None;
+        let mut next = self.pc + 1;
+        match instr {
+            Add(addr) => self.ac = self.ac.wrapping_add(self.load(addr)?),
+            AddImmediate(imm) => self.ac = self.ac.wrapping_add(imm.into()),
+            Subtract(addr) => self.ac = self.ac.wrapping_sub(self.load(addr)?),
+            SubtractImmediate(imm) => self.ac = self.ac.wrapping_sub(imm.into()),
+            Multiply(addr) => self.ac = self.ac.wrapping_mul(self.load(addr)?),
+            MultiplyImmediate(imm) => self.ac = self.ac.wrapping_mul(imm.into()),
+            Divide(addr) => self.ac = self.quotient(self.load(addr)?)?,
+            DivideImmediate(imm) => self.ac = self.quotient(imm.into())?,
+            Remainder(addr) => self.ac = self.remainder(self.load(addr)?)?,
+            RemainderImmediate(imm) => self.ac = self.remainder(imm.into())?,
+            Shift(amount) => self.ac = shift(self.ac, amount),
+            And(addr) => self.ac &= self.load(addr)?,
+            AndImmediate(imm) => self.ac &= i16::from(imm),
+            BranchZero(low) => {
+                if self.ac == 0 {
+                    next = target(low);
+                }
+            }
+            Branch(low) => next = target(low),
+            ClearAc => self.ac = 0,
+            Store(addr) => write = Some(self.store(addr)?),
+            AddIndirect(ptr) => self.ac = self.ac.wrapping_add(self.load(self.pointer(ptr)?)?),
+            SubtractIndirect(ptr) => self.ac = self.ac.wrapping_sub(self.load(self.pointer(ptr)?)?),
+            MultiplyIndirect(ptr) => self.ac = self.ac.wrapping_mul(self.load(self.pointer(ptr)?)?),
+            DivideIndirect(ptr) => self.ac = self.quotient(self.load(self.pointer(ptr)?)?)?,
+            RemainderIndirect(ptr) => self.ac = self.remainder(self.load(self.pointer(ptr)?)?)?,
+            AndIndirect(ptr) => self.ac &= self.load(self.pointer(ptr)?)?,
+            StoreIndirect(ptr) => write = Some(self.store(self.pointer(ptr)?)?),
+            NoOp | Prefix(_) => {}
+        }
+        if next != self.pc + 1 && next >= self.text_words {
+            return Err(Trap::PcOutOfRange {
+                pc: self.pc,
+                target: next,
+            });
+        }
+        self.prefix = match instr {
+            Prefix(high) => Some(high),
+            _ => None,
+        };
+        let step = Step {
+            step: self.steps + 1,
+            pc: self.pc,
+            instr,
+            ac_before,
+            ac_after: self.ac,
+            write,
+        };
+        self.pc = next;
+        self.steps += 1;
+        self.at_breakpoint = false;
+        Ok(StepResult::Executed(step))
+    }
+
+    /// Steps until the program stops or traps; a program that has not stopped once `steps`
+    /// reaches `max_steps` traps with [`Trap::StepLimit`].
+    ///
+    /// ```
+    /// use single_address_assembler::emulator::{Machine, Trap};
+    /// use single_address_assembler::{assemble, AddressedInstruction::DivideImmediate, Parser};
+    ///
+    /// let program = assemble(".text .label top addi 1 br top").unwrap();
+    /// assert_eq!(Machine::new(&program).run(10), Err(Trap::StepLimit(10)));
+    ///
+    /// let mut parser = Parser::new(".text clac addi 7 divi 0 halt");
+    /// parser.options.allow_div_zero = true;
+    /// parser.parse_program().unwrap();
+    /// let mut machine = Machine::new(&parser.address_program().unwrap());
+    /// assert_eq!(machine.run(10), Err(Trap::DivisionByZero { pc: 2, instr: DivideImmediate(0) }));
+    /// assert_eq!(machine.ac, 7);
+    /// ```
+    pub fn run(&mut self, max_steps: usize) -> Result<Stop, Trap> {
+        loop {
+            if let StepResult::Stopped(stop) = self.step_within(max_steps)? {
+                return Ok(stop);
+            }
+        }
+    }
+
+    /// Like [`step`](Self::step), but traps with [`Trap::StepLimit`] instead of running once
+    /// `steps` has reached `max_steps`.
+    pub fn step_within(&mut self, max_steps: usize) -> Result<StepResult, Trap> {
+        if self.steps >= max_steps {
+            return Err(Trap::StepLimit(self.steps));
+        }
+        self.step()
+    }
+
+    /// Runs like [`run`](Self::run), but also pauses at breakpoints and when a watched word
+    /// changes. Calling it again continues from where it paused, running the instruction at a
+    /// breakpoint rather than stopping there again.
+    ///
+    /// ```
+    /// use single_address_assembler::assemble;
+    /// use single_address_assembler::emulator::{Event, Machine, Stop};
+    ///
+    /// let program = assemble(
+    ///     ".data .label count .number 2
+    ///      .text .label loop clac add count subi 1 stor count beqz done br loop
+    ///      .label done halt",
+    /// )
+    /// .unwrap();
+    /// let mut machine = Machine::new(&program);
+    /// machine.breakpoints.insert(program.symbol("done").unwrap().address);
+    /// machine.watchpoints.insert(0);
+    ///
+    /// let changed = Event::Watchpoint { pc: 3, address: 0, old: 2, new: 1 };
+    /// assert_eq!(machine.resume(100), Ok(changed));
+    /// assert!(matches!(machine.resume(100), Ok(Event::Watchpoint { new: 0, .. })));
+    /// assert_eq!(machine.resume(100), Ok(Event::Breakpoint(6)));
+    /// assert_eq!(machine.resume(100), Ok(Event::Stopped(Stop::Halted)));
+    /// ```
+    pub fn resume(&mut self, max_steps: usize) -> Result<Event, Trap> {
+        self.resume_with(max_steps, |_| {})
+    }
+
+    /// Like [`resume`](Self::resume), calling `on_step` with each instruction as it executes.
+    pub fn resume_with<F>(&mut self, max_steps: usize, mut on_step: F) -> Result<Event, Trap>
+    where
+        F: FnMut(&Step),
+    {
+        loop {
+            if !self.at_breakpoint && self.breakpoints.contains(&self.pc) {
+                self.at_breakpoint = true;
+                return Ok(Event::Breakpoint(self.pc));
+            }
+
+            let step = match self.step_within(max_steps)? {
+                StepResult::Executed(step) => step,
+                StepResult::Stopped(stop) => return Ok(Event::Stopped(stop)),
+            };
+            on_step(&step);
+            if let Some(write) = step.write {
+                if write.old != write.new && self.watchpoints.contains(&write.address) {
+                    return Ok(Event::Watchpoint {
+                        pc: step.pc,
+                        address: write.address,
+                        old: write.old,
+                        new: write.new,
+                    });
+                }
+            }
+        }
+    }
+
+    /// The address held in the pointer word at `ptr`; only its low byte is used.
+    fn pointer(&self, ptr: Address) -> Result<Address, Trap> {
+        Ok(self.load(ptr)? as Address)
+    }
+
+    /// Reads the word at `addr` for the instruction at the PC, trapping if it is
+    /// uninitialized and [`trap_uninit`](Self::trap_uninit) is set.
+    fn load(&self, addr: Address) -> Result<i16, Trap> {
+        self.check_data(addr)?;
+        if self.trap_uninit && !self.initialized[usize::from(addr)] {
+            return Err(Trap::UninitializedRead {
+                pc: self.pc,
+                instr: self.text[self.pc],
+                address: addr,
+            });
+        }
+        Ok(self.read(addr))
+    }
+
+    /// Stores the accumulator at `addr` for the instruction at the PC.
+    fn store(&mut self, addr: Address) -> Result<MemoryWrite, Trap> {
+        self.check_data(addr)?;
+        let write = MemoryWrite {
+            address: addr,
+            old: self.read(addr),
+            new: self.ac,
+        };
+        self.write(addr, self.ac);
+        Ok(write)
+    }
+
+    /// Traps if `addr` is past the end of data RAM.
+    fn check_data(&self, addr: Address) -> Result<(), Trap> {
+        if usize::from(addr) < self.data.len() {
+            Ok(())
+        } else {
+            Err(Trap::DataOutOfRange {
+                pc: self.pc,
+                address: usize::from(addr),
+            })
+        }
+    }
+
+    /// `ac / divisor`, rounding toward zero, or per [`on_div_zero`](Self::on_div_zero).
+    fn quotient(&self, divisor: i16) -> Result<i16, Trap> {
+        match (divisor, self.on_div_zero) {
+            (0, DivZeroMode::Trap) => Err(self.division_by_zero()),
+            (0, DivZeroMode::Zero) => Ok(0),
+            (0, DivZeroMode::Saturate) if self.ac < 0 => Ok(i16::MIN),
+            (0, DivZeroMode::Saturate) => Ok(i16::MAX),
+            _ => Ok(self.ac.wrapping_div(divisor)),
+        }
+    }
+
+    /// `ac % divisor`, with the sign of `ac`, or per [`on_div_zero`](Self::on_div_zero).
+    fn remainder(&self, divisor: i16) -> Result<i16, Trap> {
+        match (divisor, self.on_div_zero) {
+            (0, DivZeroMode::Trap) => Err(self.division_by_zero()),
+            (0, DivZeroMode::Zero) => Ok(0),
+            (0, DivZeroMode::Saturate) => Ok(self.ac),
+            _ => Ok(self.ac.wrapping_rem(divisor)),
+        }
+    }
+
+    fn division_by_zero(&self) -> Trap {
+        Trap::DivisionByZero {
+            pc: self.pc,
+            instr: self.text[self.pc],
+        }
+    }
+}
+
+/// Shifts left by a positive amount or arithmetically right by a negative one.
+fn shift(ac: i16, amount: i8) -> i16 {
+    let distance = u32::from(amount.unsigned_abs());
+    if amount >= 0 {
+        ac.checked_shl(distance).unwrap_or(0)
+    } else {
+        ac.checked_shr(distance).unwrap_or(ac >> 15)
+    }
+}
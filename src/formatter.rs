@@ -0,0 +1,486 @@
+//! Rewrites assembly source in one canonical layout, for the `fmt` subcommand.
+//!
+//! Section directives, labels, and `.equ` lines start in the first column; each instruction,
+//! `.number`, and bare data value gets its own line, indented four spaces with operands
+//! aligned. Literals keep the radix they were written in, a comment stays with the code on
+//! its line, full-line comments take the indentation of the code after them, and runs of
+//! blank lines collapse to one, with one always separating sections.
+//!
+//! ```
+//! use single_address_assembler::formatter::format_source;
+//!
+//! let source = ".data .label x .number 0x10   # sixteen\n\n\n.text .label top add x br top";
+//! let formatted = format_source(source).unwrap();
+//! assert_eq!(
+//!     formatted,
+//!     "\
+//! .data
+//! .label x
+//!     .number 0x10        # sixteen
+//!
+//! .text
+//! .label top
+//!     add   x
+//!     br    top
+//! "
+//! );
+//! assert_eq!(format_source(&formatted).unwrap(), formatted);
+//! ```
+
+use crate::parser::MAX_DEPTH;
+use crate::{ParseError, Span, Token};
+use logos::Logos;
+use std::collections::HashMap;
+
+/// Spaces in front of instructions and `.number` entries.
+const INDENT: &str = "    ";
+
+/// Width of the mnemonic column, wide enough for the longest mnemonic.
+const MNEMONIC_WIDTH: usize = 5;
+
+/// Column at which comments after code start, unless the code is longer.
+const COMMENT_COLUMN: usize = 24;
+
+/// One line of formatted code and the source lines it was written across.
+struct Item {
+    code: String,
+    indented: bool,
+    section: bool,
+    first_line: usize,
+    last_line: usize,
+}
+
+/// What a source line holds besides code.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Trivia<'a> {
+    Blank,
+    Comment(&'a str),
+    Nothing,
+}
+
+/// Where the comment on `line` starts, skipping `#` inside string literals, which cannot
+/// span lines.
+fn comment_start(line: &str) -> Option<usize> {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Formats `source`, or reports the first token that does not fit the assembly grammar.
+/// Only the shape of each line is checked, so undefined labels and other problems that
+/// `assemble` would find are left alone.
+///
+/// A `#` inside a string literal does not start a comment:
+///
+/// ```
+/// use single_address_assembler::formatter::format_source;
+///
+/// let formatted = format_source(".data .label s .stringz \"a#b\" # note").unwrap();
+/// assert_eq!(formatted, ".data\n.label s\n    .stringz \"a#b\"      # note\n");
+/// assert_eq!(format_source(&formatted).unwrap(), formatted);
+///
+/// let formatted = format_source(".data .label s .stringz \"a#b\"").unwrap();
+/// assert_eq!(formatted, ".data\n.label s\n    .stringz \"a#b\"\n");
+/// assert_eq!(format_source(&formatted).unwrap(), formatted);
+/// ```
+pub fn format_source(source: &str) -> Result<String, ParseError> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let line_of = |offset: usize| match line_starts.binary_search(&offset) {
+        Ok(line) => line,
+        Err(next) => next - 1,
+    };
+    let trivia: Vec<Trivia> = source
+        .split('\n')
+        .map(|line| match comment_start(line) {
+            Some(hash) => Trivia::Comment(line[hash..].trim_end()),
+            None if line.trim().is_empty() => Trivia::Blank,
+            None => Trivia::Nothing,
+        })
+        .collect();
+
+    let items = ItemReader::new(source).read_all()?;
+    let items: Vec<Item> = items
+        .into_iter()
+        .map(|(code, indented, span)| Item {
+            section: code == ".text" || code == ".data",
+            code,
+            indented,
+            first_line: line_of(span.start),
+            last_line: line_of(span.end.saturating_sub(1).max(span.start)),
+        })
+        .collect();
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut next_line = 0;
+    for (index, item) in items.iter().enumerate() {
+        let indent = if item.indented { INDENT } else { "" };
+        let mut between: Vec<Trivia> = trivia[next_line.min(item.first_line)..item.first_line]
+            .iter()
+            .copied()
+            .filter(|t| *t != Trivia::Nothing)
+            .collect();
+        if item.section && !between.starts_with(&[Trivia::Blank]) {
+            between.insert(0, Trivia::Blank);
+        }
+        for t in between {
+            match t {
+                Trivia::Blank => push_blank(&mut lines),
+                Trivia::Comment(comment) => lines.push(format!("{}{}", indent, comment)),
+                Trivia::Nothing => {}
+            }
+        }
+
+        // A comment belongs to the last item on its line.
+        let start = item.first_line.max(next_line);
+        let end = match items.get(index + 1) {
+            Some(next) if next.first_line <= item.last_line => next.first_line,
+            _ => item.last_line + 1,
+        };
+        let comments: Vec<&str> = trivia[start..end.max(start)]
+            .iter()
+            .filter_map(|t| match t {
+                Trivia::Comment(comment) => Some(*comment),
+                _ => None,
+            })
+            .collect();
+        let code = format!("{}{}", indent, item.code);
+        if comments.is_empty() {
+            lines.push(code);
+        } else if code.len() < COMMENT_COLUMN {
+            lines.push(format!(
+                "{:<width$}{}",
+                code,
+                comments.join(" "),
+                width = COMMENT_COLUMN
+            ));
+        } else {
+            lines.push(format!("{} {}", code, comments.join(" ")));
+        }
+        next_line = next_line.max(end);
+    }
+    for t in trivia.iter().skip(next_line) {
+        match t {
+            Trivia::Blank => push_blank(&mut lines),
+            Trivia::Comment(comment) => lines.push((*comment).to_owned()),
+            Trivia::Nothing => {}
+        }
+    }
+    while lines.last().is_some_and(String::is_empty) {
+        lines.pop();
+    }
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    Ok(out)
+}
+
+/// Adds a blank line unless the output is empty or already ends in one.
+fn push_blank(lines: &mut Vec<String>) {
+    if lines.last().is_some_and(|line| !line.is_empty()) {
+        lines.push(String::new());
+    }
+}
+
+/// Groups tokens into the directives and instructions that each get a line.
+struct ItemReader<'a> {
+    source: &'a str,
+    tokens: Vec<(Token<'a>, Span)>,
+    next: usize,
+    /// The mnemonic each `.alias` so far stands for, so uses format like it.
+    aliases: HashMap<&'a str, Token<'a>>,
+    /// Parentheses and unary operators open in the value being read.
+    depth: usize,
+}
+
+impl<'a> ItemReader<'a> {
+    fn new(source: &'a str) -> Self {
+        let mut lexer = Token::lexer(source);
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.next() {
+            tokens.push((token.classify(), lexer.span()));
+        }
+        Self {
+            source,
+            tokens,
+            next: 0,
+            aliases: HashMap::new(),
+            depth: 0,
+        }
+    }
+
+    /// Every item with whether it is indented and the span from its first to last token.
+    fn read_all(mut self) -> Result<Vec<(String, bool, Span)>, ParseError> {
+        let mut items = Vec::new();
+        while let Some((token, span)) = self.tokens.get(self.next).cloned() {
+            self.next += 1;
+            // An alias formats like its mnemonic but keeps its own spelling.
+            let kind = match token {
+                Token::LabelIdent(name) => self.aliases.get(name).copied().unwrap_or(token),
+                _ => token,
+            };
+            let (code, indented) = match kind {
+                Token::Text | Token::Data => (token.to_string(), false),
+                Token::Label => (format!(".label {}", self.label()?), false),
+                Token::Equ => {
+                    let name = self.label()?;
+                    (format!(".equ {} {}", name, self.value()?), false)
+                }
+                Token::Alias => {
+                    let name = self.label()?;
+                    let target = self.expect("expected a mnemonic", |t| {
+                        t.is_mnemonic() || matches!(t, Token::LabelIdent(_))
+                    })?;
+                    let (target_token, target_span) = self.tokens[self.next - 1].clone();
+                    let mnemonic = match target_token {
+                        Token::LabelIdent(alias) => self.aliases.get(alias).copied(),
+                        mnemonic => Some(mnemonic),
+                    };
+                    let mnemonic = mnemonic.ok_or_else(|| {
+                        ParseError::InvalidToken(
+                            target.to_owned(),
+                            "expected a mnemonic".to_owned(),
+                            target_span,
+                        )
+                    })?;
+                    self.aliases.insert(name, mnemonic);
+                    (format!(".alias {} {}", name, target), false)
+                }
+                Token::Number => {
+                    let value = self.data_value()?;
+                    (format!(".number {}{}", value, self.repeat()?), true)
+                }
+                Token::NumLiteral(_) | Token::Minus | Token::Tilde | Token::LParen => {
+                    self.next -= 1;
+                    let value = self.value()?;
+                    (format!("{}{}", value, self.repeat()?), true)
+                }
+                Token::Ampersand => {
+                    let label = self.label()?;
+                    (format!("&{}{}", label, self.repeat()?), true)
+                }
+                Token::TextAddress(label) => (format!("@{}{}", label, self.repeat()?), true),
+                Token::JumpTable => {
+                    let mut targets = vec![self.label()?];
+                    while let Some((Token::LabelIdent(label), _)) = self.tokens.get(self.next) {
+                        if *label == "rep" {
+                            break;
+                        }
+                        targets.push(label);
+                        self.next += 1;
+                    }
+                    let code = format!(".jumptable {}{}", targets.join(" "), self.repeat()?);
+                    (code, true)
+                }
+                Token::IncBin => {
+                    let path = self.expect("expected a quoted file name", |t| {
+                        matches!(t, Token::StringLiteral(_))
+                    })?;
+                    let mut code = format!(".incbin {}", path);
+                    while let Some((Token::LabelIdent(keyword @ "offset"), _))
+                    | Some((Token::LabelIdent(keyword @ "length"), _)) =
+                        self.tokens.get(self.next).cloned()
+                    {
+                        self.next += 1;
+                        code = format!("{} {} {}", code, keyword, self.value()?);
+                    }
+                    (code, true)
+                }
+                Token::Csv => {
+                    let path = self.expect("expected a quoted file name", |t| {
+                        matches!(t, Token::StringLiteral(_))
+                    })?;
+                    (format!(".csv {}", path), true)
+                }
+                Token::String | Token::StringZ => {
+                    let text = self.expect("expected a quoted string", |t| {
+                        matches!(t, Token::StringLiteral(_))
+                    })?;
+                    (format!("{} {}", token, text), true)
+                }
+                Token::ClearAc | Token::NoOp | Token::Halt => (token.to_string(), true),
+                Token::AddImmediate
+                | Token::SubtractImmediate
+                | Token::MultiplyImmediate
+                | Token::DivideImmediate
+                | Token::RemainderImmediate
+                | Token::AndImmediate
+                | Token::Shift
+                | Token::ShiftLeft
+                | Token::ShiftRight => (instruction(&token, self.value()?), true),
+                Token::Add
+                | Token::Subtract
+                | Token::Multiply
+                | Token::Divide
+                | Token::Remainder
+                | Token::And
+                | Token::Store
+                | Token::BranchZero
+                | Token::Branch => (instruction(&token, &self.operand()?), true),
+                _ => {
+                    return Err(ParseError::InvalidToken(
+                        self.source[span.clone()].to_owned(),
+                        "expected a directive or mnemonic".to_owned(),
+                        span,
+                    ))
+                }
+            };
+            let end = self.tokens[self.next - 1].1.end;
+            items.push((code, indented, span.start..end));
+        }
+        Ok(items)
+    }
+
+    /// The source text of the next token, if `accept` allows it.
+    fn expect(
+        &mut self,
+        expected: &str,
+        accept: fn(&Token) -> bool,
+    ) -> Result<&'a str, ParseError> {
+        match self.tokens.get(self.next) {
+            Some((token, span)) if accept(token) => {
+                self.next += 1;
+                Ok(&self.source[span.clone()])
+            }
+            Some((_, span)) => Err(ParseError::InvalidToken(
+                self.source[span.clone()].to_owned(),
+                expected.to_owned(),
+                span.clone(),
+            )),
+            None => Err(ParseError::UnexpectedEof(expected.to_owned())),
+        }
+    }
+
+    fn label(&mut self) -> Result<&'a str, ParseError> {
+        self.expect("expected a label", |t| matches!(t, Token::LabelIdent(_)))
+    }
+
+    /// A `.number` operand: `&label`, `@label`, or a constant expression.
+    fn data_value(&mut self) -> Result<String, ParseError> {
+        match self.tokens.get(self.next) {
+            Some((Token::Ampersand, _)) => {
+                self.next += 1;
+                Ok(format!("&{}", self.label()?))
+            }
+            Some((Token::TextAddress(label), _)) => {
+                self.next += 1;
+                Ok(format!("@{}", label))
+            }
+            _ => self.value().map(str::to_owned),
+        }
+    }
+
+    /// A constant expression, as written.
+    fn value(&mut self) -> Result<&'a str, ParseError> {
+        let start = self.next;
+        self.operand_value()?;
+        loop {
+            let end = self.tokens[self.next - 1].1.end;
+            match self.tokens.get(self.next) {
+                // The same rule as the parser: `-` against the digits after it only
+                // subtracts when it also touches the operand before it.
+                Some((Token::NumLiteral(n), span)) if *n < 0 && span.start == end => self.next += 1,
+                Some((token, _)) if is_binary_operator(token) => {
+                    self.next += 1;
+                    self.operand_value()?;
+                }
+                _ => break,
+            }
+        }
+        let span = self.tokens[start].1.start..self.tokens[self.next - 1].1.end;
+        Ok(&self.source[span])
+    }
+
+    /// One operand of a constant expression, with any unary operators before it.
+    fn operand_value(&mut self) -> Result<(), ParseError> {
+        let token = self.tokens.get(self.next).map(|(token, _)| *token);
+        self.expect("expected an integer or `.equ` constant", |t| {
+            matches!(
+                t,
+                Token::NumLiteral(_)
+                    | Token::LabelIdent(_)
+                    | Token::Minus
+                    | Token::Tilde
+                    | Token::LParen
+            )
+        })?;
+        if !matches!(token, Some(Token::Minus | Token::Tilde | Token::LParen)) {
+            return Ok(());
+        }
+        // The parser's limit, so that formatting cannot overflow the stack either.
+        if self.depth == MAX_DEPTH {
+            let span = self.tokens[self.next - 1].1.clone();
+            return Err(ParseError::InvalidConstant(
+                self.source[span.clone()].to_owned(),
+                format!("is nested more than {} deep", MAX_DEPTH),
+                span,
+            ));
+        }
+        self.depth += 1;
+        let nested = if token == Some(Token::LParen) {
+            self.value()
+                .and_then(|_| self.expect("expected `)`", |t| *t == Token::RParen))
+                .map(|_| ())
+        } else {
+            self.operand_value()
+        };
+        self.depth -= 1;
+        nested
+    }
+
+    /// A ` rep N` suffix after a data value, or nothing if there is none.
+    fn repeat(&mut self) -> Result<String, ParseError> {
+        match self.tokens.get(self.next) {
+            Some((Token::LabelIdent("rep"), _)) => {
+                self.next += 1;
+                Ok(format!(" rep {}", self.value()?))
+            }
+            _ => Ok(String::new()),
+        }
+    }
+
+    /// A label or address operand, possibly in brackets.
+    fn operand(&mut self) -> Result<String, ParseError> {
+        let expected = "expected a label or address";
+        let direct = |t: &Token| matches!(t, Token::LabelIdent(_) | Token::AddressLiteral(_));
+        if self.expect(expected, |t| *t == Token::LBracket).is_err() {
+            return self.expect(expected, direct).map(str::to_owned);
+        }
+        let operand = self.expect(expected, direct)?;
+        self.expect("expected `]`", |t| *t == Token::RBracket)?;
+        Ok(format!("[{}]", operand))
+    }
+}
+
+fn is_binary_operator(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Pipe
+            | Token::Caret
+            | Token::Ampersand
+            | Token::ShiftLeftOp
+            | Token::ShiftRightOp
+            | Token::Plus
+            | Token::Minus
+            | Token::Star
+            | Token::Slash
+            | Token::Percent
+    )
+}
+
+/// An instruction with its operand in the aligned column.
+fn instruction(mnemonic: &Token, operand: &str) -> String {
+    format!(
+        "{:<width$} {}",
+        mnemonic.to_string(),
+        operand,
+        width = MNEMONIC_WIDTH
+    )
+}
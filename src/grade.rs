@@ -0,0 +1,232 @@
+//! Expectations about a program's final state, for the `grade` subcommand.
+//!
+//! ```
+//! use single_address_assembler::emulator::Machine;
+//! use single_address_assembler::grade::Expectation;
+//! use single_address_assembler::assemble;
+//!
+//! let program = assemble(
+//!     ".data .label result .number 0
+//!      .text clac addi 6 muli 7 stor result halt",
+//! )
+//! .unwrap();
+//! let mut machine = Machine::new(&program);
+//! machine.run(100).unwrap();
+//!
+//! let expectations: Vec<Expectation> = ["ac=42", "mem:result=42", "mem:0x0=0x2a", "mem:1=7"]
+//!     .iter()
+//!     .map(|spec| spec.parse().unwrap())
+//!     .collect();
+//! let actual: Vec<i16> = expectations
+//!     .iter()
+//!     .map(|expectation| expectation.actual(&machine, &program).unwrap())
+//!     .collect();
+//! assert_eq!(actual, [42, 42, 42, 0]);
+//! assert_eq!(expectations[3].to_string(), "mem:1=7");
+//!
+//! assert!("mem:nowhere=1".parse::<Expectation>().unwrap().actual(&machine, &program).is_err());
+//! assert!("pc=3".parse::<Expectation>().is_err());
+//! ```
+//!
+//! An [`ExpectFile`] gathers the setup and expectations for one program, for the `test`
+//! subcommand. Each line is `key = value`, and `#` starts a comment:
+//!
+//! ```text
+//! max_steps = 5000     # fail if the program has not halted by then
+//! init mem:n = 6       # store 6 at `n` before the program starts
+//! ac = 720             # then expect these once it stops
+//! mem:result = 720
+//! ```
+
+use crate::emulator::Machine;
+use crate::{Address, AddressedProgram, Section};
+use std::fmt;
+use std::str::FromStr;
+
+/// Where an expectation reads its value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    Ac,
+    /// The data word at a data label.
+    Label(String),
+    /// The data word at a raw address.
+    Address(usize),
+}
+
+/// A value some location must hold once the program stops, written `ac=VALUE`,
+/// `mem:LABEL=VALUE`, or `mem:ADDRESS=VALUE`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expectation {
+    pub location: Location,
+    pub expected: i16,
+}
+
+impl Expectation {
+    /// The value at this expectation's location in `machine`, or why there is none.
+    pub fn actual(&self, machine: &Machine, program: &AddressedProgram) -> Result<i16, String> {
+        match self.address(machine, program)? {
+            Some(address) => Ok(machine.data[address]),
+            None => Ok(machine.ac),
+        }
+    }
+
+    /// Stores the expected value at this expectation's location, to set up a run.
+    pub fn apply(&self, machine: &mut Machine, program: &AddressedProgram) -> Result<(), String> {
+        match self.address(machine, program)? {
+            Some(address) => machine.write(address as Address, self.expected),
+            None => machine.ac = self.expected,
+        }
+        Ok(())
+    }
+
+    /// The data address this expectation is about, or `None` for the accumulator.
+    fn address(
+        &self,
+        machine: &Machine,
+        program: &AddressedProgram,
+    ) -> Result<Option<usize>, String> {
+        let address = match &self.location {
+            Location::Ac => return Ok(None),
+            Location::Label(name) => match program.symbol(name) {
+                Some(symbol) if symbol.section == Section::Data => symbol.address,
+                _ => return Err(format!("there is no data label named `{}`", name)),
+            },
+            Location::Address(address) => *address,
+        };
+        if address < machine.data.len() {
+            Ok(Some(address))
+        } else {
+            Err(format!("address {:#x} is outside data memory", address))
+        }
+    }
+
+    /// Whether `machine` meets this expectation.
+    pub fn holds(&self, machine: &Machine, program: &AddressedProgram) -> bool {
+        self.actual(machine, program) == Ok(self.expected)
+    }
+}
+
+impl FromStr for Expectation {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, String> {
+        let (location, value) = spec
+            .split_once('=')
+            .ok_or_else(|| format!("`{}` is not of the form LOCATION=VALUE", spec))?;
+        let (location, value) = (location.trim(), value.trim());
+        let location = match location.strip_prefix("mem:") {
+            _ if location == "ac" => Location::Ac,
+            Some(address) if address.starts_with(|c: char| c.is_ascii_digit()) => {
+                Location::Address(
+                    parse_number(address)
+                        .ok_or_else(|| format!("`{}` is not a valid address", address))?
+                        as usize,
+                )
+            }
+            Some(label) if !label.is_empty() => Location::Label(label.to_owned()),
+            _ => {
+                return Err(format!(
+                    "`{}` is not `ac`, `mem:LABEL`, or `mem:ADDRESS`",
+                    location
+                ))
+            }
+        };
+        let expected = parse_number(value)
+            .filter(|value| (i64::from(i16::MIN)..=i64::from(u16::MAX)).contains(value))
+            .ok_or_else(|| format!("`{}` is not a 16-bit value", value))?;
+        Ok(Self {
+            location,
+            expected: expected as i16,
+        })
+    }
+}
+
+impl fmt::Display for Expectation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.location {
+            Location::Ac => write!(f, "ac")?,
+            Location::Label(name) => write!(f, "mem:{}", name)?,
+            Location::Address(address) => write!(f, "mem:{}", address)?,
+        }
+        write!(f, "={}", self.expected)
+    }
+}
+
+/// A decimal or `0x` hexadecimal integer, optionally negative.
+pub(crate) fn parse_number(text: &str) -> Option<i64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    let magnitude = match digits.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None => digits.parse().ok()?,
+    };
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+/// The setup and expectations for one program under the `test` subcommand.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpectFile {
+    /// How many instructions the program may execute, if not the runner's default.
+    pub max_steps: Option<usize>,
+    /// Values stored before the program starts, from `init` lines.
+    pub initial: Vec<Expectation>,
+    pub expectations: Vec<Expectation>,
+}
+
+/// A problem in an expectation file, with the 1-based line it was found on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectFileError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ExpectFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl ExpectFile {
+    /// Reads a file in the format described in the [module docs](self).
+    ///
+    /// ```
+    /// use single_address_assembler::grade::ExpectFile;
+    ///
+    /// let file = ExpectFile::parse("# factorial\nmax_steps = 500\ninit mem:n = 5\nac = 120\n").unwrap();
+    /// assert_eq!(file.max_steps, Some(500));
+    /// assert_eq!(file.initial[0].to_string(), "mem:n=5");
+    /// assert_eq!(file.expectations[0].to_string(), "ac=120");
+    ///
+    /// let err = ExpectFile::parse("ac = 1\nmax_steps = lots").unwrap_err();
+    /// assert_eq!(err.to_string(), "line 2: `lots` is not a valid step count");
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, ExpectFileError> {
+        let mut file = Self::default();
+        for (index, line) in text.lines().enumerate() {
+            let error = |message: String| ExpectFileError {
+                line: index + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            match line.split_once('=') {
+                Some((key, value)) if key.trim() == "max_steps" => {
+                    let value = value.trim();
+                    let steps = value
+                        .parse()
+                        .map_err(|_| error(format!("`{}` is not a valid step count", value)))?;
+                    file.max_steps = Some(steps);
+                }
+                _ => match line.strip_prefix("init ") {
+                    Some(init) => file.initial.push(init.parse().map_err(error)?),
+                    None => file.expectations.push(line.parse().map_err(error)?),
+                },
+            }
+        }
+        Ok(file)
+    }
+}
@@ -0,0 +1,495 @@
+//! Writing Logisim memory image files, and reading them back into values.
+
+use crate::snapshot::fnv1a;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+
+/// The file format of a written memory image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Logisim's original `v2.0 raw` format: one value per line.
+    V2Raw,
+    /// Logisim-evolution's `v3.0 hex words addressed` format.
+    V3Hex,
+    /// The encoded bytes with no header or hex encoding.
+    Binary,
+}
+
+impl OutputFormat {
+    /// The format for a `--format` name; unknown names fall back to `v2-raw`.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "v3-hex" => Self::V3Hex,
+            "bin" => Self::Binary,
+            _ => Self::V2Raw,
+        }
+    }
+
+    /// Default extensions for the text and data outputs.
+    pub fn extensions(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Binary => ("bin", "dbin"),
+            Self::V2Raw | Self::V3Hex => ("mc", "dat"),
+        }
+    }
+}
+
+/// How each 16-bit word is stored in memory cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellWidth {
+    /// Two cells per word, high byte first, for 8-bit memories.
+    Bytes,
+    /// One cell per word, for 16-bit memories.
+    Words,
+}
+
+impl CellWidth {
+    /// The width selected by `--data-width` or `--text-width`, given in bits.
+    pub fn from_bits(bits: &str) -> Option<Self> {
+        match bits {
+            "8" => Some(Self::Bytes),
+            "16" => Some(Self::Words),
+            _ => None,
+        }
+    }
+
+    /// Number of memory cells each data word occupies.
+    pub fn cells_per_word(self) -> usize {
+        match self {
+            Self::Bytes => 2,
+            Self::Words => 1,
+        }
+    }
+
+    /// Splits `words` into cell values.
+    pub fn cells(self, words: &[u16]) -> Vec<u16> {
+        match self {
+            Self::Bytes => words
+                .iter()
+                .flat_map(|word| word.to_be_bytes())
+                .map(u16::from)
+                .collect(),
+            Self::Words => words.to_vec(),
+        }
+    }
+
+    /// Splits data `words` into cell values, with the two bytes of each in `endian` order
+    /// when they take a cell each.
+    pub fn data_cells(self, words: &[u16], endian: Endian) -> Vec<u16> {
+        match self {
+            Self::Bytes => words
+                .iter()
+                .flat_map(|&word| endian.bytes(word))
+                .map(u16::from)
+                .collect(),
+            Self::Words => words.to_vec(),
+        }
+    }
+
+    /// Hex digits needed for one cell.
+    pub fn digits(self) -> usize {
+        match self {
+            Self::Bytes => 2,
+            Self::Words => 4,
+        }
+    }
+}
+
+/// Byte order of data words wherever they are stored as separate bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    /// High byte first, the historical order.
+    Big,
+    /// Low byte first, for RAM wired to expect it.
+    Little,
+}
+
+impl Endian {
+    /// The order for a `--data-endian` name; unknown names fall back to big-endian.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "little" => Self::Little,
+            _ => Self::Big,
+        }
+    }
+
+    /// The two bytes of `word`, in this order.
+    pub fn bytes(self, word: u16) -> [u8; 2] {
+        match self {
+            Self::Big => word.to_be_bytes(),
+            Self::Little => word.to_le_bytes(),
+        }
+    }
+}
+
+/// How the Logisim memory images are laid out.
+#[derive(Debug, Clone)]
+pub struct ImageOptions {
+    pub format: OutputFormat,
+    /// How data words are split into cells; `None` keeps each format's historical layout.
+    pub data_width: Option<CellWidth>,
+    /// How instruction words are split into cells in text images.
+    pub text_width: CellWidth,
+    /// Collapse runs of equal values into `count*value` entries (`v2.0 raw` only).
+    pub rle: bool,
+    /// Values written on each line of `v2.0 raw` output.
+    pub per_line: usize,
+    /// Fill value used to extend each image to the full memory size.
+    pub pad: Option<u16>,
+    /// Byte order of data words in 8-bit cells and binary data images.
+    pub data_endian: Endian,
+    /// Interleave `#` comments naming labels and instructions, one value per line, in the
+    /// text and data images; binary images have nowhere to put them.
+    pub annotate: bool,
+    /// Lines written as `#` comments after the format line, such as the
+    /// [`provenance`] of the image; binary images have no header to put them in.
+    pub header: Vec<String>,
+}
+
+impl ImageOptions {
+    /// Options for `format` with one value per line, no run-length encoding, and no padding.
+    pub fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            data_width: None,
+            text_width: CellWidth::Words,
+            rle: false,
+            per_line: 1,
+            pad: None,
+            data_endian: Endian::Big,
+            annotate: false,
+            header: vec![],
+        }
+    }
+
+    /// The data width for separate data images: as chosen, or bytes for `v2.0 raw` and
+    /// binary images and words for `v3.0 hex` ones.
+    pub fn data_width(&self) -> CellWidth {
+        self.data_width.unwrap_or(match self.format {
+            OutputFormat::V3Hex => CellWidth::Words,
+            OutputFormat::V2Raw | OutputFormat::Binary => CellWidth::Bytes,
+        })
+    }
+}
+
+/// Extends `words` to `len` entries with the padding fill value, if padding was requested.
+pub fn pad_words(mut words: Vec<u16>, len: usize, options: &ImageOptions) -> Vec<u16> {
+    if let Some(fill) = options.pad {
+        if words.len() < len {
+            words.resize(len, fill);
+        }
+    }
+    words
+}
+
+/// Renders a memory image of `values`, each printed as `digits` hex digits (or written as
+/// `digits / 2` bytes in the binary format). The result is exactly what is written to disk.
+pub fn render(values: &[u16], digits: usize, options: &ImageOptions) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_image(&mut out, options, values, digits).expect("writing to a Vec cannot fail");
+    out
+}
+
+/// Comments to interleave with the values of an annotated image, by value index.
+#[derive(Debug, Clone, Default)]
+pub struct Annotations {
+    /// Lines written above a value, such as the labels at its address.
+    pub above: HashMap<usize, Vec<String>>,
+    /// A comment after a value, such as the instruction it encodes.
+    pub after: HashMap<usize, String>,
+}
+
+/// Like [`render`], but with `notes` written as `#` comments, which Logisim ignores, and
+/// each value on its own line so they have somewhere to go. Binary images are unchanged.
+pub fn render_annotated(
+    values: &[u16],
+    digits: usize,
+    options: &ImageOptions,
+    notes: &Annotations,
+) -> Vec<u8> {
+    let mut out = Vec::new();
+    let written = match options.format {
+        OutputFormat::Binary => write_image(&mut out, options, values, digits),
+        _ => write_annotated(&mut out, options, values, digits, notes),
+    };
+    written.expect("writing to a Vec cannot fail");
+    out
+}
+
+/// The header-identified layout of an image file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageKind {
+    V2Raw,
+    V3Addressed,
+    V3Plain,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+fn error<T>(line: usize, message: String) -> Result<T, ImageError> {
+    Err(ImageError { line, message })
+}
+
+fn parse_hex(line: usize, value: &str) -> Result<u16, ImageError> {
+    u16::from_str_radix(value, 16)
+        .or_else(|_| error(line, format!("`{}` is not a 16-bit hex value", value)))
+}
+
+/// Parses a `v2.0 raw` or `v3.0 hex words` image into its values, in address order.
+pub fn parse_image(contents: &str) -> Result<(ImageKind, Vec<u16>), ImageError> {
+    let contents = contents.trim_start_matches('\u{feff}');
+    let mut lines = contents.lines().enumerate().map(|(i, line)| {
+        let line = line.split('#').next().unwrap_or("").trim();
+        (i + 1, line)
+    });
+
+    let kind = match lines.next() {
+        Some((_, "v2.0 raw")) => ImageKind::V2Raw,
+        Some((_, "v3.0 hex words addressed")) => ImageKind::V3Addressed,
+        Some((_, "v3.0 hex words plain")) => ImageKind::V3Plain,
+        Some((line, header)) => {
+            return error(line, format!("unsupported image header `{}`", header));
+        }
+        None => return error(1, "missing image header".to_owned()),
+    };
+
+    let mut values = Vec::new();
+    for (line, text) in lines {
+        let mut entries = text.split_whitespace();
+
+        if kind == ImageKind::V3Addressed {
+            let address = match entries.next() {
+                Some(address) => address,
+                None => continue,
+            };
+            let address = match address.strip_suffix(':') {
+                Some(address) => usize::from(parse_hex(line, address)?),
+                None => return error(line, format!("expected `address:`, found `{}`", address)),
+            };
+            if address < values.len() {
+                return error(line, format!("address {:#x} is out of order", address));
+            }
+            values.resize(address, 0);
+        }
+
+        for entry in entries {
+            match entry.split_once('*') {
+                Some((count, value)) => {
+                    let count: usize = count.parse().or_else(|_| {
+                        error(line, format!("`{}` is not a valid repeat count", count))
+                    })?;
+                    let value = parse_hex(line, value)?;
+                    values.extend(std::iter::repeat_n(value, count));
+                }
+                None => values.push(parse_hex(line, entry)?),
+            }
+        }
+    }
+
+    Ok((kind, values))
+}
+
+/// Longest line written when run-length encoding `v2.0 raw` output.
+const RLE_LINE_WIDTH: usize = 72;
+
+/// Collapses consecutive equal values into `(value, count)` runs.
+fn runs(values: &[u16]) -> Vec<(u16, usize)> {
+    let mut runs: Vec<(u16, usize)> = Vec::new();
+    for &value in values {
+        match runs.last_mut() {
+            Some((last, count)) if *last == value => *count += 1,
+            _ => runs.push((value, 1)),
+        }
+    }
+    runs
+}
+
+/// Writes the line naming an image's format, then its header comments.
+fn write_format(out: &mut dyn Write, options: &ImageOptions, format: &str) -> io::Result<()> {
+    writeln!(out, "{}", format)?;
+    for line in &options.header {
+        writeln!(out, "# {}", line)?;
+    }
+    Ok(())
+}
+
+/// Where an image came from, as lines for [`ImageOptions::header`]: the source files, a
+/// hash of their contents, the assembler version, the command line, and, unless `generated`
+/// is `None` for reproducible output, when it was written in seconds since the Unix epoch.
+///
+/// ```
+/// use single_address_assembler::image::{self, ImageOptions, OutputFormat};
+///
+/// let header = image::provenance(&["prog.s"], ".text halt", "assembler prog.s --header", Some(0));
+/// assert_eq!(header[0], "source: prog.s");
+/// assert_eq!(header[1], "fnv1a-64: 12116b0973ba5f89");
+/// assert_eq!(header[4], "generated: 1970-01-01T00:00:00Z");
+///
+/// let mut options = ImageOptions::new(OutputFormat::V2Raw);
+/// options.header = image::provenance(&["prog.s"], ".text halt", "assembler prog.s", None);
+/// let text = String::from_utf8(image::render(&[0x6000], 4, &options)).unwrap();
+/// assert!(text.starts_with("v2.0 raw\n# source: prog.s\n# fnv1a-64: "));
+/// assert!(text.ends_with("# command: assembler prog.s\n6000\n"));
+/// assert_eq!(image::parse_image(&text).unwrap().1, [0x6000]);
+/// ```
+pub fn provenance(
+    files: &[&str],
+    input: &str,
+    command: &str,
+    generated: Option<u64>,
+) -> Vec<String> {
+    let mut lines = vec![
+        format!("source: {}", files.join(", ")),
+        format!("fnv1a-64: {:016x}", fnv1a(input.bytes())),
+        format!(
+            "assembler: {} {}",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        ),
+        format!("command: {}", command),
+    ];
+    if let Some(seconds) = generated {
+        lines.push(format!("generated: {}", utc_timestamp(seconds)));
+    }
+    lines
+}
+
+/// `seconds` since the Unix epoch as an ISO 8601 UTC date and time.
+fn utc_timestamp(seconds: u64) -> String {
+    // Howard Hinnant's days-to-civil conversion, counting from 0000-03-01.
+    let days = seconds / 86_400 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    let time = seconds % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+/// Writes a text image of `values` one per line, with the comments in `notes` around them.
+fn write_annotated(
+    out: &mut dyn Write,
+    options: &ImageOptions,
+    values: &[u16],
+    digits: usize,
+    notes: &Annotations,
+) -> io::Result<()> {
+    let address_digits = format!("{:x}", values.len().saturating_sub(1)).len().max(2);
+    match options.format {
+        OutputFormat::V3Hex => write_format(out, options, "v3.0 hex words addressed")?,
+        _ => write_format(out, options, "v2.0 raw")?,
+    }
+    for (index, value) in values.iter().enumerate() {
+        for line in notes.above.get(&index).into_iter().flatten() {
+            writeln!(out, "# {}", line)?;
+        }
+        if options.format == OutputFormat::V3Hex {
+            write!(out, "{:0width$x}: ", index, width = address_digits)?;
+        }
+        write!(out, "{:0width$x}", value, width = digits)?;
+        match notes.after.get(&index) {
+            Some(note) => writeln!(out, " # {}", note)?,
+            None => writeln!(out)?,
+        }
+    }
+    Ok(())
+}
+
+/// Number of values per line in the `v3.0 hex words addressed` format.
+const V3_WORDS_PER_LINE: usize = 8;
+
+/// Writes a Logisim memory image of `values`, each printed as `digits` hex digits.
+fn write_image(
+    out: &mut dyn Write,
+    options: &ImageOptions,
+    values: &[u16],
+    digits: usize,
+) -> io::Result<()> {
+    match options.format {
+        OutputFormat::Binary => {
+            for value in values {
+                let bytes = value.to_be_bytes();
+                out.write_all(&bytes[2 - digits / 2..])?;
+            }
+        }
+        OutputFormat::V2Raw if options.rle => {
+            write_format(out, options, "v2.0 raw")?;
+            let mut line = String::new();
+            for (value, count) in runs(values) {
+                let entry = if count == 1 {
+                    format!("{:0width$x}", value, width = digits)
+                } else {
+                    format!("{}*{:0width$x}", count, value, width = digits)
+                };
+                if !line.is_empty() && line.len() + 1 + entry.len() > RLE_LINE_WIDTH {
+                    writeln!(out, "{}", line)?;
+                    line.clear();
+                }
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(&entry);
+            }
+            if !line.is_empty() {
+                writeln!(out, "{}", line)?;
+            }
+        }
+        OutputFormat::V2Raw => {
+            write_format(out, options, "v2.0 raw")?;
+            for chunk in values.chunks(options.per_line) {
+                let line: Vec<String> = chunk
+                    .iter()
+                    .map(|value| format!("{:0width$x}", value, width = digits))
+                    .collect();
+                writeln!(out, "{}", line.join(" "))?;
+            }
+        }
+        OutputFormat::V3Hex => {
+            write_format(out, options, "v3.0 hex words addressed")?;
+            let address_digits = format!("{:x}", values.len().saturating_sub(1)).len().max(2);
+            for (line, chunk) in values.chunks(V3_WORDS_PER_LINE).enumerate() {
+                let words: Vec<String> = chunk
+                    .iter()
+                    .map(|value| format!("{:0width$x}", value, width = digits))
+                    .collect();
+                writeln!(
+                    out,
+                    "{:0width$x}: {}",
+                    line * V3_WORDS_PER_LINE,
+                    words.join(" "),
+                    width = address_digits
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,31 +1,160 @@
+use logos::Span;
+use std::convert::TryFrom;
 use std::fmt;
 
 pub type Immediate = i8;
-pub type Address = u8;
+/// A text or data address. The value field of an instruction is 8 bits, so operands past
+/// `0xff` are rejected when encoding; data beyond that is reached through pointers, and
+/// text through `prefix`.
+///
+/// ```
+/// use single_address_assembler::{AddressedInstruction::*, ParseError, Parser};
+///
+/// let assemble = |source: &str| {
+///     let mut parser = Parser::new(source);
+///     parser.cpu.set_data_words(1024);
+///     parser.parse_program()?;
+///     parser.address_program()
+/// };
+/// // `x` lands on data address `n`, and `p` points at it.
+/// let padded = |n: usize, use_x: &str| {
+///     format!(".data .label p &x .label pad 0 rep {} .label x 1 .text {} halt", n - 1, use_x)
+/// };
+///
+/// let program = assemble(&padded(255, "add x")).unwrap();
+/// assert_eq!(program.text[0], Add(0xff));
+/// for &n in [256, 257].iter() {
+///     let err = assemble(&padded(n, "add x")).unwrap_err();
+///     assert!(matches!(err, ParseError::AddressOutOfRange(_, _, address, _, _) if address == n));
+///     // A pointer word holds the whole address.
+///     let program = assemble(&padded(n, "add p")).unwrap();
+///     assert_eq!(program.data[0], n as i16);
+/// }
+///
+/// let text = |n: usize| Parser::parse(&format!(".text {}", "noop ".repeat(n))).map(|_| ());
+/// assert!(text(255).is_ok() && text(256).is_ok());
+/// assert!(matches!(text(257).unwrap_err(), ParseError::InstructionOverflow(_, 256, _)));
+/// ```
+pub type Address = u16;
 
 #[derive(Debug)]
 pub enum Instruction<'a> {
-    Add(&'a str),
+    Add(&'a str, Span),
     AddImmediate(Immediate),
-    Subtract(&'a str),
+    Subtract(&'a str, Span),
     SubtractImmediate(Immediate),
-    Multiply(&'a str),
+    Multiply(&'a str, Span),
     MultiplyImmediate(Immediate),
-    Divide(&'a str),
+    Divide(&'a str, Span),
     DivideImmediate(Immediate),
-    Remainder(&'a str),
+    Remainder(&'a str, Span),
     RemainderImmediate(Immediate),
     Shift(Immediate),
-    And(&'a str),
+    And(&'a str, Span),
     AndImmediate(Immediate),
 
-    BranchZero(&'a str),
-    Branch(&'a str),
+    BranchZero(&'a str, Span),
+    Branch(&'a str, Span),
     ClearAc,
-    Store(&'a str),
+    Store(&'a str, Span),
     NoOp,
+    /// Pseudo-instruction assembled as a branch to itself.
+    Halt,
+
+    // Indirect forms, whose operand labels a word holding the address to use.
+    AddIndirect(&'a str, Span),
+    SubtractIndirect(&'a str, Span),
+    MultiplyIndirect(&'a str, Span),
+    DivideIndirect(&'a str, Span),
+    RemainderIndirect(&'a str, Span),
+    AndIndirect(&'a str, Span),
+    StoreIndirect(&'a str, Span),
+}
+
+impl<'a> Instruction<'a> {
+    /// The assembly mnemonic for this instruction.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Self::Add(..) => "add",
+            Self::AddImmediate(_) => "addi",
+            Self::Subtract(..) => "sub",
+            Self::SubtractImmediate(_) => "subi",
+            Self::Multiply(..) => "mul",
+            Self::MultiplyImmediate(_) => "muli",
+            Self::Divide(..) => "div",
+            Self::DivideImmediate(_) => "divi",
+            Self::Remainder(..) => "rem",
+            Self::RemainderImmediate(_) => "remi",
+            Self::Shift(_) => "shift",
+            Self::And(..) => "and",
+            Self::AndImmediate(_) => "andi",
+            Self::BranchZero(..) => "beqz",
+            Self::Branch(..) => "br",
+            Self::ClearAc => "clac",
+            Self::Store(..) => "stor",
+            Self::NoOp => "noop",
+            Self::Halt => "halt",
+            Self::AddIndirect(..) => "add_indirect",
+            Self::SubtractIndirect(..) => "sub_indirect",
+            Self::MultiplyIndirect(..) => "mul_indirect",
+            Self::DivideIndirect(..) => "div_indirect",
+            Self::RemainderIndirect(..) => "rem_indirect",
+            Self::AndIndirect(..) => "and_indirect",
+            Self::StoreIndirect(..) => "stor_indirect",
+        }
+    }
+
+    /// The label operand and its span, for instructions that take one.
+    pub fn label(&self) -> Option<(&'a str, &Span)> {
+        match self {
+            Self::Add(label, span)
+            | Self::Subtract(label, span)
+            | Self::Multiply(label, span)
+            | Self::Divide(label, span)
+            | Self::Remainder(label, span)
+            | Self::And(label, span)
+            | Self::BranchZero(label, span)
+            | Self::Branch(label, span)
+            | Self::Store(label, span)
+            | Self::AddIndirect(label, span)
+            | Self::SubtractIndirect(label, span)
+            | Self::MultiplyIndirect(label, span)
+            | Self::DivideIndirect(label, span)
+            | Self::RemainderIndirect(label, span)
+            | Self::AndIndirect(label, span)
+            | Self::StoreIndirect(label, span) => Some((label, span)),
+            _ => None,
+        }
+    }
 }
 
+/// An instruction with its operand resolved to an address or immediate.
+///
+/// The `Display` form uses `@address` operands, which the parser accepts in place of a
+/// label, and prints shifts of 1 to 15 places as `shl`/`shr`, so printed instructions
+/// re-assemble to the same words. The exception is `prefix`, which the assembler only
+/// inserts itself in front of long branches:
+///
+/// ```
+/// use single_address_assembler::assemble;
+///
+/// let program = assemble(
+///     ".data .label x .number 1 .label y .number -2
+///      .text
+///      .label top
+///      add x addi -3 sub y subi 4 mul x muli 5 div y divi 6 rem x remi 7
+///      shift -1 shl 3 shr 15 shift 0 and y andi 15 beqz top br end clac stor x noop
+///      .label end
+///      halt",
+/// )
+/// .unwrap();
+///
+/// let printed: Vec<String> = program.text.iter().map(|instr| instr.to_string()).collect();
+/// assert_eq!(printed[10..14], ["shr 1", "shl 3", "shr 15", "shift 0"]);
+/// let reassembled = assemble(&format!(".text\n{}", printed.join("\n"))).unwrap();
+/// assert_eq!(reassembled.text, program.text);
+/// assert!(assemble(".text shl 16").is_err());
+/// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AddressedInstruction {
     Add(Address),
@@ -46,9 +175,273 @@ pub enum AddressedInstruction {
     ClearAc,
     Store(Address),
     NoOp,
+    /// The high byte of the next branch's target, on CPUs with more than 256 text words.
+    Prefix(u8),
+    AddIndirect(Address),
+    SubtractIndirect(Address),
+    MultiplyIndirect(Address),
+    DivideIndirect(Address),
+    RemainderIndirect(Address),
+    AndIndirect(Address),
+    StoreIndirect(Address),
+}
+
+/// Why an instruction could not be encoded into a machine word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodeError {
+    /// The operand of the named instruction does not fit in the 8-bit value field.
+    OperandOutOfRange(&'static str, Address),
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::OperandOutOfRange(mnemonic, operand) => write!(
+                f,
+                "the `{}` operand {:#x} does not fit in the 8-bit value field",
+                mnemonic, operand
+            ),
+        }
+    }
+}
+
+/// Why a machine word could not be decoded into an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    InvalidOpcode(u8),
+    InvalidAluOp(u8, u8),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidOpcode(opcode) => write!(f, "invalid opcode {:#x}", opcode),
+            Self::InvalidAluOp(opcode, alu_op) => {
+                write!(f, "invalid alu_op {:#x} for opcode {:#x}", alu_op, opcode)
+            }
+        }
+    }
+}
+
+impl TryFrom<[u8; 2]> for AddressedInstruction {
+    type Error = DecodeError;
+
+    /// Inverts `bytes()`: the high nibble of the first byte is the opcode, the low nibble the
+    /// alu_op, and the second byte the operand.
+    ///
+    /// Fields the hardware does not read are ignored rather than rejected: the alu_op of
+    /// every opcode other than 1 and 2, and the operand of `noop` and `clac`. Such words
+    /// decode successfully but do not re-encode to the same bits.
+    fn try_from(bytes: [u8; 2]) -> Result<Self, Self::Error> {
+        let opcode = bytes[0] >> 4;
+        let alu_op = bytes[0] & 0xf;
+        let immediate = bytes[1] as Immediate;
+        let value = Address::from(bytes[1]);
+
+        let instr = match (opcode, alu_op) {
+            (0, _) => Self::NoOp,
+            (1, 0) => Self::AddImmediate(immediate),
+            (1, 1) => Self::SubtractImmediate(immediate),
+            (1, 2) => Self::MultiplyImmediate(immediate),
+            (1, 3) => Self::DivideImmediate(immediate),
+            (1, 4) => Self::RemainderImmediate(immediate),
+            (1, 5) => Self::AndImmediate(immediate),
+            (1, 6) => Self::Shift(immediate),
+            (2, 0) => Self::Add(value),
+            (2, 1) => Self::Subtract(value),
+            (2, 2) => Self::Multiply(value),
+            (2, 3) => Self::Divide(value),
+            (2, 4) => Self::Remainder(value),
+            (2, 5) => Self::And(value),
+            (1, _) | (2, _) => return Err(DecodeError::InvalidAluOp(opcode, alu_op)),
+            (3, _) => Self::ClearAc,
+            (4, _) => Self::Store(value),
+            (5, _) => Self::BranchZero(value),
+            (6, _) => Self::Branch(value),
+            _ => return Err(DecodeError::InvalidOpcode(opcode)),
+        };
+
+        Ok(instr)
+    }
 }
 
 impl AddressedInstruction {
+    /// Decodes a 16-bit machine word; see the `TryFrom<[u8; 2]>` impl for the rules.
+    ///
+    /// Every instruction survives an encode/decode round trip:
+    ///
+    /// ```
+    /// use single_address_assembler::AddressedInstruction::{self, *};
+    ///
+    /// for operand in 0..=255u16 {
+    ///     let imm = operand as i8;
+    ///     let all = [
+    ///         Add(operand), AddImmediate(imm), Subtract(operand), SubtractImmediate(imm),
+    ///         Multiply(operand), MultiplyImmediate(imm), Divide(operand), DivideImmediate(imm),
+    ///         Remainder(operand), RemainderImmediate(imm), Shift(imm), And(operand),
+    ///         AndImmediate(imm), BranchZero(operand), Branch(operand), ClearAc,
+    ///         Store(operand), NoOp,
+    ///     ];
+    ///     for instr in all.iter() {
+    ///         let word = u16::from_be_bytes(instr.bytes());
+    ///         assert_eq!(AddressedInstruction::try_from_word(word), Ok(*instr));
+    ///     }
+    /// }
+    ///
+    /// assert!(AddressedInstruction::try_from_word(0x7000).is_err());
+    /// assert!(AddressedInstruction::try_from_word(0x1700).is_err());
+    /// ```
+    pub fn try_from_word(word: u16) -> Result<Self, DecodeError> {
+        Self::try_from(word.to_be_bytes())
+    }
+
+    /// The instruction named `mnemonic` with `value` as its operand field, or `None` for
+    /// `halt` and unknown mnemonics.
+    pub fn from_mnemonic(mnemonic: &str, value: u8) -> Option<Self> {
+        let immediate = value as Immediate;
+        let address = Address::from(value);
+        Some(match mnemonic {
+            "add" => Self::Add(address),
+            "addi" => Self::AddImmediate(immediate),
+            "sub" => Self::Subtract(address),
+            "subi" => Self::SubtractImmediate(immediate),
+            "mul" => Self::Multiply(address),
+            "muli" => Self::MultiplyImmediate(immediate),
+            "div" => Self::Divide(address),
+            "divi" => Self::DivideImmediate(immediate),
+            "rem" => Self::Remainder(address),
+            "remi" => Self::RemainderImmediate(immediate),
+            "shift" => Self::Shift(immediate),
+            "and" => Self::And(address),
+            "andi" => Self::AndImmediate(immediate),
+            "beqz" => Self::BranchZero(address),
+            "br" => Self::Branch(address),
+            "clac" => Self::ClearAc,
+            "stor" => Self::Store(address),
+            "noop" => Self::NoOp,
+            "prefix" => Self::Prefix(value),
+            "add_indirect" => Self::AddIndirect(address),
+            "sub_indirect" => Self::SubtractIndirect(address),
+            "mul_indirect" => Self::MultiplyIndirect(address),
+            "div_indirect" => Self::DivideIndirect(address),
+            "rem_indirect" => Self::RemainderIndirect(address),
+            "and_indirect" => Self::AndIndirect(address),
+            "stor_indirect" => Self::StoreIndirect(address),
+            _ => return None,
+        })
+    }
+
+    /// The assembly mnemonic for this instruction.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Self::Add(_) => "add",
+            Self::AddImmediate(_) => "addi",
+            Self::Subtract(_) => "sub",
+            Self::SubtractImmediate(_) => "subi",
+            Self::Multiply(_) => "mul",
+            Self::MultiplyImmediate(_) => "muli",
+            Self::Divide(_) => "div",
+            Self::DivideImmediate(_) => "divi",
+            Self::Remainder(_) => "rem",
+            Self::RemainderImmediate(_) => "remi",
+            Self::Shift(_) => "shift",
+            Self::And(_) => "and",
+            Self::AndImmediate(_) => "andi",
+            Self::BranchZero(_) => "beqz",
+            Self::Branch(_) => "br",
+            Self::ClearAc => "clac",
+            Self::Store(_) => "stor",
+            Self::NoOp => "noop",
+            Self::Prefix(_) => "prefix",
+            Self::AddIndirect(_) => "add_indirect",
+            Self::SubtractIndirect(_) => "sub_indirect",
+            Self::MultiplyIndirect(_) => "mul_indirect",
+            Self::DivideIndirect(_) => "div_indirect",
+            Self::RemainderIndirect(_) => "rem_indirect",
+            Self::AndIndirect(_) => "and_indirect",
+            Self::StoreIndirect(_) => "stor_indirect",
+        }
+    }
+
+    /// The data address this instruction reads or writes, if any. For an indirect
+    /// instruction this is the address of the pointer word.
+    pub fn data_address(&self) -> Option<Address> {
+        match self {
+            Self::Add(a)
+            | Self::Subtract(a)
+            | Self::Multiply(a)
+            | Self::Divide(a)
+            | Self::Remainder(a)
+            | Self::And(a)
+            | Self::Store(a)
+            | Self::AddIndirect(a)
+            | Self::SubtractIndirect(a)
+            | Self::MultiplyIndirect(a)
+            | Self::DivideIndirect(a)
+            | Self::RemainderIndirect(a)
+            | Self::AndIndirect(a)
+            | Self::StoreIndirect(a) => Some(*a),
+            _ => None,
+        }
+    }
+
+    /// Whether the operand is the address of a pointer to the data rather than of the data.
+    ///
+    /// ```
+    /// use single_address_assembler::cpu::{CpuSpec, DEFAULT_SPEC};
+    /// use single_address_assembler::emulator::Machine;
+    /// use single_address_assembler::{AddressedInstruction::*, ParseError, Parser};
+    ///
+    /// let source = ".data .label ptr .number 2 .label out .number 0 .label x .number 9
+    ///               .text clac add [ptr] stor [ptr] halt";
+    /// let mut parser = Parser::new(source);
+    /// parser.cpu = CpuSpec::parse(&format!(
+    ///     "{}add_indirect = {{ opcode = 8, alu_op = 0 }}\nstor_indirect = {{ opcode = 9 }}",
+    ///     DEFAULT_SPEC
+    /// ))
+    /// .unwrap();
+    /// parser.parse_program().unwrap();
+    /// let program = parser.address_program().unwrap();
+    /// assert_eq!(program.text[1..3], [AddIndirect(0), StoreIndirect(0)]);
+    /// assert!(program.text[1].is_indirect());
+    /// assert_eq!(program.text[1].to_string(), "add [@0x0]");
+    ///
+    /// let mut machine = Machine::new(&program);
+    /// machine.run(10).unwrap();
+    /// assert_eq!(machine.ac, 9);
+    /// assert_eq!(machine.read(2), 9);
+    ///
+    /// let err = Parser::parse(".data .label p .number 0 .text add [p]").unwrap_err();
+    /// assert!(matches!(err, ParseError::UnsupportedIndirect(..)));
+    /// let err = Parser::parse(".text .label top br [top]").unwrap_err();
+    /// assert!(matches!(err, ParseError::IndirectBranch(..)));
+    /// ```
+    pub fn is_indirect(&self) -> bool {
+        self.mnemonic().ends_with("_indirect")
+    }
+
+    /// The text address this instruction may branch to, if any.
+    pub fn branch_target(&self) -> Option<Address> {
+        match self {
+            Self::BranchZero(a) | Self::Branch(a) => Some(*a),
+            _ => None,
+        }
+    }
+
+    /// The immediate operand of this instruction, if it has one.
+    pub fn immediate(&self) -> Option<Immediate> {
+        match self {
+            Self::AddImmediate(i)
+            | Self::SubtractImmediate(i)
+            | Self::MultiplyImmediate(i)
+            | Self::DivideImmediate(i)
+            | Self::RemainderImmediate(i)
+            | Self::Shift(i)
+            | Self::AndImmediate(i) => Some(*i),
+            _ => None,
+        }
+    }
+
     pub fn opcode(&self) -> u8 {
         match self {
             Self::NoOp => 0,
@@ -69,26 +462,40 @@ impl AddressedInstruction {
             Self::Store(_) => 4,
             Self::BranchZero(_) => 5,
             Self::Branch(_) => 6,
+            Self::Prefix(_) => 7,
+            Self::AddIndirect(_)
+            | Self::SubtractIndirect(_)
+            | Self::MultiplyIndirect(_)
+            | Self::DivideIndirect(_)
+            | Self::RemainderIndirect(_)
+            | Self::AndIndirect(_) => 8,
+            Self::StoreIndirect(_) => 9,
         }
     }
 
     pub fn alu_op(&self) -> u8 {
         match self {
-            Self::NoOp | Self::ClearAc | Self::Store(_) | Self::BranchZero(_) | Self::Branch(_) => {
-                0
-            }
+            Self::NoOp
+            | Self::ClearAc
+            | Self::Store(_)
+            | Self::BranchZero(_)
+            | Self::Branch(_)
+            | Self::Prefix(_)
+            | Self::StoreIndirect(_) => 0,
 
-            Self::AddImmediate(_) | Self::Add(_) => 0,
-            Self::SubtractImmediate(_) | Self::Subtract(_) => 1,
-            Self::MultiplyImmediate(_) | Self::Multiply(_) => 2,
-            Self::DivideImmediate(_) | Self::Divide(_) => 3,
-            Self::RemainderImmediate(_) | Self::Remainder(_) => 4,
-            Self::AndImmediate(_) | Self::And(_) => 5,
+            Self::AddImmediate(_) | Self::Add(_) | Self::AddIndirect(_) => 0,
+            Self::SubtractImmediate(_) | Self::Subtract(_) | Self::SubtractIndirect(_) => 1,
+            Self::MultiplyImmediate(_) | Self::Multiply(_) | Self::MultiplyIndirect(_) => 2,
+            Self::DivideImmediate(_) | Self::Divide(_) | Self::DivideIndirect(_) => 3,
+            Self::RemainderImmediate(_) | Self::Remainder(_) | Self::RemainderIndirect(_) => 4,
+            Self::AndImmediate(_) | Self::And(_) | Self::AndIndirect(_) => 5,
             Self::Shift(_) => 6,
         }
     }
 
-    pub fn value(&self) -> u8 {
+    /// The operand, as it goes in the value field: an address, the high byte of a `prefix`,
+    /// or an immediate's two's-complement byte.
+    pub fn value(&self) -> Address {
         match self {
             Self::NoOp | Self::ClearAc => 0,
             Self::AddImmediate(i)
@@ -97,7 +504,8 @@ impl AddressedInstruction {
             | Self::DivideImmediate(i)
             | Self::AndImmediate(i)
             | Self::RemainderImmediate(i)
-            | Self::Shift(i) => *i as u8,
+            | Self::Shift(i) => Address::from(*i as u8),
+            Self::Prefix(high) => Address::from(*high),
             Self::Add(i)
             | Self::Subtract(i)
             | Self::Multiply(i)
@@ -106,16 +514,52 @@ impl AddressedInstruction {
             | Self::Store(i)
             | Self::Remainder(i)
             | Self::Branch(i)
-            | Self::BranchZero(i) => *i,
+            | Self::BranchZero(i)
+            | Self::AddIndirect(i)
+            | Self::SubtractIndirect(i)
+            | Self::MultiplyIndirect(i)
+            | Self::DivideIndirect(i)
+            | Self::RemainderIndirect(i)
+            | Self::AndIndirect(i)
+            | Self::StoreIndirect(i) => *i,
         }
     }
 
-    pub fn bytes(&self) -> [u8; 2] {
+    /// The value field, or why the operand does not fit in it.
+    pub fn value_byte(&self) -> Result<u8, EncodeError> {
+        u8::try_from(self.value())
+            .map_err(|_| EncodeError::OperandOutOfRange(self.mnemonic(), self.value()))
+    }
+
+    /// Encodes the instruction with the default opcodes, or says which operand is too wide
+    /// for the 8-bit value field.
+    ///
+    /// ```
+    /// use single_address_assembler::{AddressedInstruction::*, EncodeError};
+    ///
+    /// assert_eq!(Add(0xff).try_bytes(), Ok([0x20, 0xff]));
+    /// assert_eq!(Add(0x100).try_bytes(), Err(EncodeError::OperandOutOfRange("add", 0x100)));
+    /// assert_eq!(
+    ///     Store(0x101).try_bytes().unwrap_err().to_string(),
+    ///     "the `stor` operand 0x101 does not fit in the 8-bit value field"
+    /// );
+    /// ```
+    pub fn try_bytes(&self) -> Result<[u8; 2], EncodeError> {
         let opcode = self.opcode();
         let alu_op = self.alu_op();
-        let value = self.value();
+        let value = self.value_byte()?;
+
+        Ok([(opcode << 4) | alu_op, value])
+    }
 
-        [(opcode << 4) | alu_op, value]
+    /// Encodes the instruction with the default opcodes.
+    ///
+    /// # Panics
+    ///
+    /// If the operand does not fit in 8 bits, which an assembled program's never does; see
+    /// [`try_bytes`](Self::try_bytes).
+    pub fn bytes(&self) -> [u8; 2] {
+        self.try_bytes().unwrap_or_else(|err| panic!("{}", err))
     }
 
     #[allow(dead_code)]
@@ -128,24 +572,34 @@ impl AddressedInstruction {
 impl fmt::Display for AddressedInstruction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::Add(addr) => write!(f, "add {:#x}", addr),
-            Self::Subtract(addr) => write!(f, "sub {:#x}", addr),
-            Self::Multiply(addr) => write!(f, "mul {:#x}", addr),
-            Self::Divide(addr) => write!(f, "div {:#x}", addr),
-            Self::Remainder(addr) => write!(f, "rem {:#x}", addr),
-            Self::And(addr) => write!(f, "and {:#x}", addr),
-            Self::Store(addr) => write!(f, "store {:#x}", addr),
+            Self::Add(addr) => write!(f, "add @{:#x}", addr),
+            Self::Subtract(addr) => write!(f, "sub @{:#x}", addr),
+            Self::Multiply(addr) => write!(f, "mul @{:#x}", addr),
+            Self::Divide(addr) => write!(f, "div @{:#x}", addr),
+            Self::Remainder(addr) => write!(f, "rem @{:#x}", addr),
+            Self::And(addr) => write!(f, "and @{:#x}", addr),
+            Self::Store(addr) => write!(f, "stor @{:#x}", addr),
             Self::AddImmediate(i) => write!(f, "addi {}", i),
             Self::SubtractImmediate(i) => write!(f, "subi {}", i),
             Self::MultiplyImmediate(i) => write!(f, "muli {}", i),
             Self::DivideImmediate(i) => write!(f, "divi {}", i),
             Self::RemainderImmediate(i) => write!(f, "remi {}", i),
+            Self::Shift(i @ 1..=15) => write!(f, "shl {}", i),
+            Self::Shift(i @ -15..=-1) => write!(f, "shr {}", -i),
             Self::Shift(i) => write!(f, "shift {}", i),
             Self::AndImmediate(i) => write!(f, "andi {}", i),
-            Self::BranchZero(i) => write!(f, "beqz {:#x}", i),
-            Self::Branch(i) => write!(f, "br {:#x}", i),
+            Self::BranchZero(i) => write!(f, "beqz @{:#x}", i),
+            Self::Branch(i) => write!(f, "br @{:#x}", i),
             Self::ClearAc => write!(f, "clac"),
             Self::NoOp => write!(f, "noop"),
+            Self::Prefix(high) => write!(f, "prefix {:#x}", high),
+            Self::AddIndirect(addr) => write!(f, "add [@{:#x}]", addr),
+            Self::SubtractIndirect(addr) => write!(f, "sub [@{:#x}]", addr),
+            Self::MultiplyIndirect(addr) => write!(f, "mul [@{:#x}]", addr),
+            Self::DivideIndirect(addr) => write!(f, "div [@{:#x}]", addr),
+            Self::RemainderIndirect(addr) => write!(f, "rem [@{:#x}]", addr),
+            Self::AndIndirect(addr) => write!(f, "and [@{:#x}]", addr),
+            Self::StoreIndirect(addr) => write!(f, "stor [@{:#x}]", addr),
         }
     }
 }
@@ -0,0 +1,723 @@
+use super::cpu::CpuSpec;
+use super::diagnostic::SourceMap;
+use super::{Address, AddressedInstruction, AddressedProgram, Section, Symbol};
+use std::convert::TryFrom;
+use std::fmt::{self, Write};
+
+/// Version of the JSON schema written by `program_json`; bump on incompatible changes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Version of the debug info written by `debug_info_json`; bump on incompatible changes.
+pub const DEBUG_INFO_VERSION: u32 = 1;
+
+/// Quotes and escapes `s` as a JSON string.
+pub fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Serializes an assembled program as JSON:
+///
+/// ```text
+/// {
+///   "version": 1,
+///   "text_base": 0,
+///   "data_base": 0,
+///   "text": [{ "address", "mnemonic", "opcode", "alu_op", "value", "word", "instr" }, ...],
+///   "data": [{ "address", "value", "word", "code_address" }, ...],
+///   "symbols": [{ "name", "section": "text" | "data", "address" }, ...]
+/// }
+/// ```
+///
+/// `word` is the encoded 16-bit word as four hex digits, and `instr` is the instruction in
+/// the tagged form written by [`instruction_json`]. `code_address` is `true` for data words
+/// holding a text address, from `@label` or `.jumptable`. Symbol addresses and code
+/// addresses include the bases.
+pub fn program_json(program: &AddressedProgram) -> String {
+    let mut out = String::new();
+    writeln!(out, "{{").unwrap();
+    writeln!(out, "  \"version\": {},", SCHEMA_VERSION).unwrap();
+    writeln!(out, "  \"text_base\": {},", program.text_base).unwrap();
+    writeln!(out, "  \"data_base\": {},", program.data_base).unwrap();
+
+    writeln!(out, "  \"text\": [").unwrap();
+    for (address, instr) in program.text.iter().enumerate() {
+        let bytes = program.cpu.encode(instr);
+        let comma = if address + 1 < program.text.len() {
+            ","
+        } else {
+            ""
+        };
+        writeln!(
+            out,
+            "    {{ \"address\": {}, \"mnemonic\": {}, \"opcode\": {}, \"alu_op\": {}, \"value\": {}, \"word\": \"{:04x}\", \"instr\": {} }}{}",
+            address,
+            string(&instr.to_string()),
+            bytes[0] >> 4,
+            bytes[0] & 0xf,
+            instr.value(),
+            u16::from_be_bytes(bytes),
+            instruction_json(instr),
+            comma
+        )
+        .unwrap();
+    }
+    writeln!(out, "  ],").unwrap();
+
+    writeln!(out, "  \"data\": [").unwrap();
+    for (address, value) in program.data.iter().enumerate() {
+        let comma = if address + 1 < program.data.len() {
+            ","
+        } else {
+            ""
+        };
+        writeln!(
+            out,
+            "    {{ \"address\": {}, \"value\": {}, \"word\": \"{:04x}\", \"code_address\": {} }}{}",
+            address,
+            value,
+            *value as u16,
+            program.code_addresses.contains(&address),
+            comma
+        )
+        .unwrap();
+    }
+    writeln!(out, "  ],").unwrap();
+
+    writeln!(out, "  \"symbols\": [").unwrap();
+    for (index, symbol) in program.symbols.iter().enumerate() {
+        let comma = if index + 1 < program.symbols.len() {
+            ","
+        } else {
+            ""
+        };
+        let section = match symbol.section {
+            Section::Text => "text",
+            Section::Data => "data",
+        };
+        writeln!(
+            out,
+            "    {{ \"name\": {}, \"section\": \"{}\", \"address\": {} }}{}",
+            string(&symbol.name),
+            section,
+            symbol.address,
+            comma
+        )
+        .unwrap();
+    }
+    writeln!(out, "  ]").unwrap();
+    writeln!(out, "}}").unwrap();
+
+    out
+}
+
+/// Serializes where each word of a program came from, for source-level debuggers:
+///
+/// ```text
+/// {
+///   "version": 1,
+///   "text": [{ "address", "file", "line", "column", "synthesized" }, ...],
+///   "data": [{ "address", "file", "line", "column" }, ...]
+/// }
+/// ```
+///
+/// Addresses include the text and data bases. A word is `synthesized` when the assembler
+/// wrote it for something else: the `prefix` of a long branch, or the branch a `halt`
+/// becomes, even under an alias. Both point at the instruction they were written for.
+///
+/// ```
+/// use single_address_assembler::diagnostic::SourceMap;
+/// use single_address_assembler::{json, Parser};
+///
+/// let mut sources = SourceMap::new();
+/// sources.add_file("prog.s".to_owned(), ".data .label x 7\n.text\n  add x\n  halt\n");
+/// let mut parser = Parser::new(&sources.text);
+/// parser.parse_program().unwrap();
+/// let program = parser.address_program().unwrap();
+///
+/// let info = json::debug_info_json(&program, &sources);
+/// assert!(info.contains(r#"{ "address": 0, "file": "prog.s", "line": 3, "column": 3, "synthesized": false }"#));
+/// assert!(info.contains(r#"{ "address": 1, "file": "prog.s", "line": 4, "column": 3, "synthesized": true }"#));
+/// assert!(info.contains(r#"{ "address": 0, "file": "prog.s", "line": 1, "column": 16 }"#));
+///
+/// let mut sources = SourceMap::new();
+/// sources.add_file("alias.s".to_owned(), ".alias stop halt\n.text\n  stop\n");
+/// let mut parser = Parser::new(&sources.text);
+/// parser.parse_program().unwrap();
+/// let program = parser.address_program().unwrap();
+///
+/// let info = json::debug_info_json(&program, &sources);
+/// assert!(info.contains(r#"{ "address": 0, "file": "alias.s", "line": 3, "column": 3, "synthesized": true }"#));
+/// ```
+pub fn debug_info_json(program: &AddressedProgram, sources: &SourceMap) -> String {
+    let mut out = String::new();
+    writeln!(out, "{{").unwrap();
+    writeln!(out, "  \"version\": {},", DEBUG_INFO_VERSION).unwrap();
+
+    writeln!(out, "  \"text\": [").unwrap();
+    for (index, span) in program.text_spans.iter().enumerate() {
+        let comma = if index + 1 < program.text_spans.len() {
+            ","
+        } else {
+            ""
+        };
+        let (file, line, column) = sources.location(span.start);
+        let synthesized = program.synthesized.contains(&index);
+        writeln!(
+            out,
+            "    {{ \"address\": {}, \"file\": {}, \"line\": {}, \"column\": {}, \"synthesized\": {} }}{}",
+            program.text_base + index,
+            string(file),
+            line,
+            column,
+            synthesized,
+            comma
+        )
+        .unwrap();
+    }
+    writeln!(out, "  ],").unwrap();
+
+    writeln!(out, "  \"data\": [").unwrap();
+    for (index, span) in program.data_spans.iter().enumerate() {
+        let comma = if index + 1 < program.data_spans.len() {
+            ","
+        } else {
+            ""
+        };
+        let (file, line, column) = sources.location(span.start);
+        writeln!(
+            out,
+            "    {{ \"address\": {}, \"file\": {}, \"line\": {}, \"column\": {} }}{}",
+            program.data_base + index,
+            string(file),
+            line,
+            column,
+            comma
+        )
+        .unwrap();
+    }
+    writeln!(out, "  ]").unwrap();
+    writeln!(out, "}}").unwrap();
+
+    out
+}
+
+/// Serializes an instruction in a tagged form: `{ "op": "add", "addr": 3 }` for
+/// instructions with an address, `{ "op": "addi", "imm": -2 }` for immediates, and
+/// `{ "op": "clac" }` for those without an operand. A `prefix` carries the high byte of the
+/// next branch target as its `addr`.
+pub fn instruction_json(instr: &AddressedInstruction) -> String {
+    let op = string(instr.mnemonic());
+    let high = match instr {
+        AddressedInstruction::Prefix(high) => Some(Address::from(*high)),
+        _ => None,
+    };
+    if let Some(addr) = instr
+        .data_address()
+        .or_else(|| instr.branch_target())
+        .or(high)
+    {
+        format!("{{ \"op\": {}, \"addr\": {} }}", op, addr)
+    } else if let Some(imm) = instr.immediate() {
+        format!("{{ \"op\": {}, \"imm\": {} }}", op, imm)
+    } else {
+        format!("{{ \"op\": {} }}", op)
+    }
+}
+
+/// Why a JSON document could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonError {
+    pub message: String,
+}
+
+impl JsonError {
+    fn new<S: Into<String>>(message: S) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// A parsed JSON value. Numbers must be integers, which is all these documents contain.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// The member named `key`, if this is an object that has one.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Self::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+/// How deeply arrays and objects may nest in a document that [`parse`] reads.
+pub const MAX_DEPTH: usize = 128;
+
+/// Parses a JSON document, with arrays and objects nested at most [`MAX_DEPTH`] deep.
+///
+/// ```
+/// use single_address_assembler::json;
+///
+/// assert!(json::parse(&format!("{}{}", "[".repeat(128), "]".repeat(128))).is_ok());
+///
+/// let err = json::parse(&"[".repeat(200_000)).unwrap_err();
+/// assert_eq!(err.message, "arrays and objects nest more than 128 deep at byte 128");
+/// ```
+pub fn parse(input: &str) -> Result<Value, JsonError> {
+    let mut reader = Reader {
+        input,
+        pos: 0,
+        depth: 0,
+    };
+    let value = reader.value()?;
+    reader.skip_whitespace();
+    if reader.pos < input.len() {
+        return Err(reader.error("trailing characters after the document"));
+    }
+    Ok(value)
+}
+
+struct Reader<'a> {
+    input: &'a str,
+    pos: usize,
+    /// Arrays and objects open at `pos`.
+    depth: usize,
+}
+
+impl Reader<'_> {
+    fn error(&self, message: &str) -> JsonError {
+        JsonError::new(format!("{} at byte {}", message, self.pos))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start_matches(&[' ', '\t', '\n', '\r'][..]).len();
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), JsonError> {
+        self.skip_whitespace();
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", byte as char)))
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, JsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') | Some(b'[') => {
+                if self.depth == MAX_DEPTH {
+                    let message = format!("arrays and objects nest more than {} deep", MAX_DEPTH);
+                    return Err(self.error(&message));
+                }
+                self.depth += 1;
+                let nested = if self.peek() == Some(b'{') {
+                    self.object()
+                } else {
+                    self.array()
+                };
+                self.depth -= 1;
+                nested
+            }
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'-') | Some(b'0'..=b'9') => self.integer(),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, JsonError> {
+        if self.input[self.pos..].starts_with(word) {
+            self.pos += word.len();
+            Ok(value)
+        } else {
+            Err(self.error("expected a value"))
+        }
+    }
+
+    fn integer(&mut self) -> Result<Value, JsonError> {
+        let start = self.pos;
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+        if let Some(b'.') | Some(b'e') | Some(b'E') = self.peek() {
+            return Err(self.error("expected an integer"));
+        }
+        self.input[start..self.pos]
+            .parse()
+            .map(Value::Integer)
+            .map_err(|_| self.error("invalid integer"))
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        let mut chars = self.input[self.pos..].char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += offset + 1;
+                    return Ok(out);
+                }
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                            u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("invalid unicode escape"))?
+                        }
+                        _ => return Err(self.error("invalid escape in string")),
+                    };
+                    out.push(escaped);
+                }
+                c => out.push(c),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn array(&mut self) -> Result<Value, JsonError> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(items));
+                }
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, JsonError> {
+        self.expect(b'{')?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Value::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(b':')?;
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(members));
+                }
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+}
+
+/// The member `key` of `value` as an integer that fits in `T`.
+fn integer_field<T: TryFrom<i64>>(value: &Value, key: &str, context: &str) -> Result<T, JsonError> {
+    match value.get(key) {
+        Some(Value::Integer(i)) => T::try_from(*i).map_err(|_| {
+            JsonError::new(format!(
+                "{}: `{}` value {} is out of range",
+                context, key, i
+            ))
+        }),
+        Some(_) => Err(JsonError::new(format!(
+            "{}: `{}` must be an integer",
+            context, key
+        ))),
+        None => Err(JsonError::new(format!("{}: missing `{}`", context, key))),
+    }
+}
+
+/// The member `key` of `value` as a string.
+fn string_field<'v>(value: &'v Value, key: &str, context: &str) -> Result<&'v str, JsonError> {
+    match value.get(key) {
+        Some(Value::String(s)) => Ok(s),
+        Some(_) => Err(JsonError::new(format!(
+            "{}: `{}` must be a string",
+            context, key
+        ))),
+        None => Err(JsonError::new(format!("{}: missing `{}`", context, key))),
+    }
+}
+
+/// The member `key` of `value` as an array.
+fn array_field<'v>(value: &'v Value, key: &str, context: &str) -> Result<&'v [Value], JsonError> {
+    match value.get(key) {
+        Some(Value::Array(items)) => Ok(items),
+        Some(_) => Err(JsonError::new(format!(
+            "{}: `{}` must be an array",
+            context, key
+        ))),
+        None => Err(JsonError::new(format!("{}: missing `{}`", context, key))),
+    }
+}
+
+/// Reads an instruction in the tagged form written by [`instruction_json`], rejecting
+/// addresses and immediates that do not fit their fields.
+pub fn instruction_from_json(value: &Value) -> Result<AddressedInstruction, JsonError> {
+    use AddressedInstruction::*;
+
+    let context = "instruction";
+    let op = string_field(value, "op", context)?;
+    // Every address field is 8 bits wide; wider targets take a `prefix`.
+    let byte = || integer_field::<u8>(value, "addr", op);
+    let addr = || byte().map(Address::from);
+    let imm = || integer_field(value, "imm", op);
+    Ok(match op {
+        "add" => Add(addr()?),
+        "addi" => AddImmediate(imm()?),
+        "sub" => Subtract(addr()?),
+        "subi" => SubtractImmediate(imm()?),
+        "mul" => Multiply(addr()?),
+        "muli" => MultiplyImmediate(imm()?),
+        "div" => Divide(addr()?),
+        "divi" => DivideImmediate(imm()?),
+        "rem" => Remainder(addr()?),
+        "remi" => RemainderImmediate(imm()?),
+        "shift" => Shift(imm()?),
+        "and" => And(addr()?),
+        "andi" => AndImmediate(imm()?),
+        "beqz" => BranchZero(addr()?),
+        "br" => Branch(addr()?),
+        "clac" => ClearAc,
+        "stor" => Store(addr()?),
+        "noop" => NoOp,
+        "prefix" => Prefix(byte()?),
+        "add_indirect" => AddIndirect(addr()?),
+        "sub_indirect" => SubtractIndirect(addr()?),
+        "mul_indirect" => MultiplyIndirect(addr()?),
+        "div_indirect" => DivideIndirect(addr()?),
+        "rem_indirect" => RemainderIndirect(addr()?),
+        "and_indirect" => AndIndirect(addr()?),
+        "stor_indirect" => StoreIndirect(addr()?),
+        other => {
+            return Err(JsonError::new(format!(
+                "{}: unknown op `{}`",
+                context, other
+            )))
+        }
+    })
+}
+
+/// Reads a program written by [`program_json`]. Only the `instr` of each text entry and the
+/// `value` and optional `code_address` of each data entry are used; the other fields are
+/// derived from them. The bases are 0 when missing, as in documents from before they were
+/// written. Symbols and code addresses must point into the text and data read, or just
+/// past their end, where a label after the last word points. The document carries no
+/// source, so the program's spans are empty.
+///
+/// Nor does it record the CPU spec the program was assembled for, so the caller supplies
+/// `cpu`. With a different spec than the program was written under, its
+/// [`text_words`](AddressedProgram::text_words) differ from the document's `word` fields.
+///
+/// ```
+/// use single_address_assembler::cpu::CpuSpec;
+/// use single_address_assembler::{assemble, json};
+///
+/// let program = assemble(".data .label x .number 7 .text add x halt").unwrap();
+/// let read = json::program_from_json(&json::program_json(&program), program.cpu.clone()).unwrap();
+/// assert_eq!(read.text, program.text);
+/// assert_eq!(read.data, program.data);
+/// assert_eq!(read.symbols, program.symbols);
+///
+/// let program = assemble(".data .label t .jumptable a b .text .label a halt .label b halt");
+/// let program = program.unwrap();
+/// let read = json::program_from_json(&json::program_json(&program), program.cpu).unwrap();
+/// assert_eq!((read.data, read.code_addresses), (vec![0, 1], vec![0, 1]));
+///
+/// let bad = r#"{ "version": 1, "text": [{ "instr": { "op": "add", "addr": 300 } }],
+///               "data": [], "symbols": [] }"#;
+/// assert!(json::program_from_json(bad, CpuSpec::default()).is_err());
+///
+/// let bad = r#"{ "version": 1, "text": [], "data": [],
+///               "symbols": [{ "name": "x", "section": "data", "address": 5 }] }"#;
+/// let err = json::program_from_json(bad, CpuSpec::default()).unwrap_err();
+/// assert_eq!(err.message, "symbols[0]: address 5 is outside the data, 0..=0");
+///
+/// let bad = r#"{ "version": 1, "text": [], "data": [{ "value": 9, "code_address": true }],
+///               "symbols": [] }"#;
+/// let err = json::program_from_json(bad, CpuSpec::default()).unwrap_err();
+/// assert_eq!(err.message, "data[0]: code address 9 is outside the text, 0..=0");
+/// ```
+pub fn program_from_json(input: &str, cpu: CpuSpec) -> Result<AddressedProgram, JsonError> {
+    let document = parse(input)?;
+    let version: u32 = integer_field(&document, "version", "document")?;
+    if version != SCHEMA_VERSION {
+        return Err(JsonError::new(format!(
+            "unsupported schema version {}; expected {}",
+            version, SCHEMA_VERSION
+        )));
+    }
+
+    let base = |key| match document.get(key) {
+        None => Ok(0),
+        Some(_) => integer_field(&document, key, "document"),
+    };
+    let text_base: usize = base("text_base")?;
+    let data_base: usize = base("data_base")?;
+
+    let mut text = Vec::new();
+    for (index, entry) in array_field(&document, "text", "document")?
+        .iter()
+        .enumerate()
+    {
+        let context = format!("text[{}]", index);
+        let instr = entry
+            .get("instr")
+            .ok_or_else(|| JsonError::new(format!("{}: missing `instr`", context)))?;
+        let instr = instruction_from_json(instr)
+            .map_err(|err| JsonError::new(format!("{}: {}", context, err)))?;
+        text.push(instr);
+    }
+
+    let mut data = Vec::new();
+    let mut code_addresses = Vec::new();
+    for (index, entry) in array_field(&document, "data", "document")?
+        .iter()
+        .enumerate()
+    {
+        let context = format!("data[{}]", index);
+        data.push(integer_field(entry, "value", &context)?);
+        match entry.get("code_address") {
+            None | Some(Value::Bool(false)) => {}
+            Some(Value::Bool(true)) => code_addresses.push(index),
+            Some(_) => {
+                return Err(JsonError::new(format!(
+                    "{}: `code_address` must be a boolean",
+                    context
+                )))
+            }
+        }
+    }
+
+    // A label after the last word points just past the end.
+    let text_range = text_base..=text_base + text.len();
+    let data_range = data_base..=data_base + data.len();
+    for &index in &code_addresses {
+        let address = data[index] as u16 as usize;
+        if !text_range.contains(&address) {
+            return Err(JsonError::new(format!(
+                "data[{}]: code address {} is outside the text, {}..={}",
+                index,
+                address,
+                text_range.start(),
+                text_range.end()
+            )));
+        }
+    }
+
+    let mut symbols = Vec::new();
+    for (index, entry) in array_field(&document, "symbols", "document")?
+        .iter()
+        .enumerate()
+    {
+        let context = format!("symbols[{}]", index);
+        let section = match string_field(entry, "section", &context)? {
+            "text" => Section::Text,
+            "data" => Section::Data,
+            other => {
+                return Err(JsonError::new(format!(
+                    "{}: unknown section `{}`",
+                    context, other
+                )))
+            }
+        };
+        let address = integer_field(entry, "address", &context)?;
+        let (range, name) = match section {
+            Section::Text => (&text_range, "text"),
+            Section::Data => (&data_range, "data"),
+        };
+        if !range.contains(&address) {
+            return Err(JsonError::new(format!(
+                "{}: address {} is outside the {}, {}..={}",
+                context,
+                address,
+                name,
+                range.start(),
+                range.end()
+            )));
+        }
+        symbols.push(Symbol {
+            name: string_field(entry, "name", &context)?.to_owned(),
+            section,
+            address,
+        });
+    }
+
+    Ok(AddressedProgram {
+        text,
+        data,
+        symbols,
+        text_spans: Vec::new(),
+        data_spans: Vec::new(),
+        text_base,
+        data_base,
+        code_addresses,
+        synthesized: Vec::new(),
+        cpu,
+    })
+}
@@ -0,0 +1,138 @@
+//! An assembler for the One-Address CPU from Dr. Kann's
+//! [Implementing a One Address CPU in Logisim](https://cupola.gettysburg.edu/oer/3/).
+//!
+//! [`assemble`] turns source text into an [`AddressedProgram`], whose instructions and data
+//! can be encoded into the words Logisim loads into text and data memory:
+//!
+//! ```
+//! use single_address_assembler::{assemble, AddressedInstruction};
+//!
+//! let program = assemble(
+//!     ".data
+//!      .label count .number 3
+//!      .text
+//!      .label loop
+//!      clac
+//!      add count
+//!      halt",
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(program.text[1], AddressedInstruction::Add(0));
+//! assert_eq!(program.data, vec![3]);
+//! ```
+//!
+//! In a data section, the values after a label may be written bare, without `.number`, in
+//! any mix with `.number` entries:
+//!
+//! ```
+//! use single_address_assembler::{assemble, AddressedInstruction};
+//!
+//! let program = assemble(".data .label xs 5 -7 0x1f .number 9 .label y -0 .text halt").unwrap();
+//! assert_eq!(program.data, vec![5, -7, 0x1f, 9, 0]);
+//!
+//! let program = assemble(".data .label xs -0x10 .number -0x7fff 0x10-0x1 .text addi -0x10 halt");
+//! let program = program.unwrap();
+//! assert_eq!(program.data, vec![-16, -0x7fff, 15]);
+//! assert_eq!(program.text[0], AddressedInstruction::AddImmediate(-16));
+//!
+//! let err = assemble(".data 5 .label x 6 .text halt").unwrap_err();
+//! assert!(err.to_string().contains("must follow a `.label`"));
+//! ```
+//!
+//! Any value may be followed by `rep N` to repeat it `N` times, where `N` is a positive
+//! literal or `.equ` constant:
+//!
+//! ```
+//! use single_address_assembler::assemble;
+//!
+//! let program = assemble(".equ N 3 .data .label t 1 2 rep N .number -4 rep 2 .text halt");
+//! assert_eq!(program.unwrap().data, vec![1, 2, 2, 2, -4, -4]);
+//!
+//! let err = assemble(".data .label t 0 rep 300 .text halt").unwrap_err();
+//! assert!(err.to_string().contains("would bring the data to 300 words"));
+//! ```
+//!
+//! Values may be constant expressions, using C's operators and precedence. A `-` against
+//! the digits after it subtracts only when it also touches the operand before it, so
+//! `N-1` and `N - 1` are one value while `N -1` is two. Bare data values cannot start with
+//! a constant's name; use `.number` for those.
+//!
+//! ```
+//! use single_address_assembler::{assemble, AddressedInstruction};
+//!
+//! let source = ".equ BUFLEN 8
+//!               .data .label t .number BUFLEN*2-1 (1<<10)|3 0x7fff+0x7fff 2 -1 .number -(2 - 5) rep 2
+//!               .text addi BUFLEN/2 halt";
+//! let program = assemble(source).unwrap();
+//! assert_eq!(program.data, vec![15, 1027, -2, 2, -1, 3, 3]);
+//! assert_eq!(program.text[0], AddressedInstruction::AddImmediate(4));
+//!
+//! let err = assemble(".data .label t 1/(2-2) .text halt").unwrap_err();
+//! assert_eq!(err.to_string(), "`1/(2-2)` divides by zero");
+//! let err = assemble(".data .label t 300*300 .text halt").unwrap_err();
+//! assert_eq!(err.to_string(), "`300*300` evaluates to 90000, which does not fit in 16 bits");
+//! ```
+//!
+//! For control over parsing options or access to warnings, drive a [`Parser`] directly,
+//! configured with [`ParseOptions`].
+
+mod token;
+use token::{Token, MNEMONICS};
+
+mod parser;
+pub use parser::{
+    AddressedProgram, DataWord, LabelStyle, Lint, LintLevel, OwnedProgram, ParseError,
+    ParseOptions, Parser, Section, Symbol, Warning, LINTS, MAX_DATA, MAX_TEXT,
+};
+
+mod instructions;
+pub use instructions::{
+    Address, AddressedInstruction, DecodeError, EncodeError, Immediate, Instruction,
+};
+
+pub mod builder;
+pub mod circ;
+pub mod compare;
+pub mod cpu;
+pub mod debugger;
+pub mod diagnostic;
+pub mod disasm;
+pub mod emulator;
+pub mod formatter;
+pub mod grade;
+pub mod image;
+pub mod json;
+pub mod listing;
+pub mod log;
+pub mod optimize;
+pub mod profile;
+pub mod snapshot;
+pub mod stats;
+pub mod trace;
+pub mod validate;
+pub mod vectors;
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod web;
+pub mod xref;
+
+/// A byte range in the source text.
+pub use logos::Span;
+
+/// Assembles `source` with the default options, discarding any warnings.
+///
+/// ```
+/// use single_address_assembler::{assemble, ParseError};
+///
+/// let program = assemble(".text clac halt").unwrap();
+/// assert_eq!(program.text.len(), 2);
+///
+/// match assemble(".text br nowhere") {
+///     Err(ParseError::UnknownLabel(label, _, _)) => assert_eq!(label, "nowhere"),
+///     other => panic!("unexpected result: {:?}", other),
+/// }
+/// ```
+pub fn assemble(source: &str) -> Result<AddressedProgram, ParseError> {
+    Parser::parse(source)?.into_program()
+}
@@ -0,0 +1,225 @@
+use super::optimize::EliminatedRange;
+use super::{AddressedProgram, Parser, Section};
+use std::fmt::Write;
+
+/// The base encoded words are shown in; addresses are always hex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Radix {
+    Hex,
+    Bin,
+    Dec,
+}
+
+impl Radix {
+    /// The radix for a `--radix` name; unknown names fall back to hex.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "bin" => Self::Bin,
+            "dec" => Self::Dec,
+            _ => Self::Hex,
+        }
+    }
+
+    /// `word` in this radix, padded to the same width for every word.
+    fn format(self, word: u16) -> String {
+        match self {
+            Self::Hex => format!("{:04x}", word),
+            Self::Bin => format!("{:016b}", word),
+            Self::Dec => format!("{:5}", word),
+        }
+    }
+}
+
+/// How the words in a listing are written. Only the listing changes; memory images are
+/// unaffected.
+#[derive(Debug, Clone, Copy)]
+pub struct ListingOptions {
+    pub radix: Radix,
+    /// Follow each instruction word with its opcode, alu_op, and value fields in binary.
+    pub annotate_bits: bool,
+}
+
+impl Default for ListingOptions {
+    fn default() -> Self {
+        Self {
+            radix: Radix::Hex,
+            annotate_bits: false,
+        }
+    }
+}
+
+/// Width of an instruction's fields split out as `oooo aaaa | vvvvvvvv`.
+const BITS_WIDTH: usize = 20;
+
+/// The opcode, alu_op, and value fields of `word` in binary.
+fn bit_fields(word: u16) -> String {
+    format!(
+        "{:04b} {:04b} | {:08b}",
+        word >> 12,
+        (word >> 8) & 0xf,
+        word & 0xff
+    )
+}
+
+/// Renders a human-readable listing: each instruction with its address, encoded word, and
+/// source text, then the data words, then a symbol table. Code removed as unreachable by
+/// the optimizer is noted where it used to be. A packed `.string` shows its source and
+/// then the two characters in each word.
+///
+/// ```
+/// use single_address_assembler::listing::{listing, ListingOptions, Radix};
+/// use single_address_assembler::Parser;
+///
+/// let mut parser = Parser::new(".data .label s .stringz \"abc\" .text add s halt");
+/// parser.parse_program().unwrap();
+/// let program = parser.address_program().unwrap();
+/// let text = listing(&parser, &program, &ListingOptions::default());
+/// assert!(text.contains("  00  6162      \"ab\"\n  01  6300      \"c\\0\"\n  02  0000"));
+///
+/// let options = ListingOptions {
+///     radix: Radix::Bin,
+///     annotate_bits: true,
+/// };
+/// let text = listing(&parser, &program, &options);
+/// assert!(text.contains("  00  0010000000000000  0010 0000 | 00000000      add s\n"));
+/// assert!(text.contains(&format!("  00  0110000101100010{}\"ab\"\n", " ".repeat(28))));
+/// ```
+pub fn listing(parser: &Parser, program: &AddressedProgram, options: &ListingOptions) -> String {
+    let mut out = String::new();
+
+    let labels_in = |section| {
+        program
+            .symbols
+            .iter()
+            .filter(move |symbol| symbol.section == section)
+            .peekable()
+    };
+
+    // Every word column is padded to one width, and labels and notes line up with the
+    // source after it.
+    let word_width = options.radix.format(0).len()
+        + if options.annotate_bits {
+            2 + BITS_WIDTH
+        } else {
+            0
+        };
+    let indent = 2 + 2 + 2 + word_width + 4;
+    let line = |out: &mut String, address: usize, word: String, source: &dyn std::fmt::Display| {
+        writeln!(
+            out,
+            "  {:02x}  {:<width$}      {}",
+            address,
+            word,
+            source,
+            width = word_width
+        )
+        .unwrap();
+    };
+
+    writeln!(out, "Text:").unwrap();
+    let mut labels = labels_in(Section::Text);
+    let mut eliminated = parser.eliminated.iter().peekable();
+    let note = |out: &mut String, range: &EliminatedRange| {
+        let plural = if range.count == 1 { "" } else { "s" };
+        writeln!(
+            out,
+            "{:>indent$}({} unreachable instruction{} eliminated)",
+            "",
+            range.count,
+            plural,
+            indent = indent
+        )
+        .unwrap();
+    };
+    for (index, instr) in program.text.iter().enumerate() {
+        let address = index + parser.text_base;
+        let start = program.text_spans[index].start;
+        while let Some(range) = eliminated.next_if(|range| range.span.start < start) {
+            note(&mut out, range);
+        }
+        while let Some(symbol) = labels.next_if(|symbol| symbol.address == address) {
+            writeln!(out, "{:>indent$}{}:", "", symbol.name, indent = indent).unwrap();
+        }
+        let source = &parser.input[program.text_spans[index].clone()];
+        let encoded = program.cpu.word(instr);
+        let mut word = options.radix.format(encoded);
+        if options.annotate_bits {
+            word = format!("{}  {}", word, bit_fields(encoded));
+        }
+        line(&mut out, address, word, &source);
+    }
+    for range in eliminated {
+        note(&mut out, range);
+    }
+    for symbol in labels {
+        writeln!(out, "{:>indent$}{}:", "", symbol.name, indent = indent).unwrap();
+    }
+
+    writeln!(out, "\nData:").unwrap();
+    let mut labels = labels_in(Section::Data);
+    let mut offset = 0;
+    while offset < program.data.len() {
+        let address = offset + parser.data_base;
+        while let Some(symbol) = labels.next_if(|symbol| symbol.address == address) {
+            writeln!(out, "{:>indent$}{}:", "", symbol.name, indent = indent).unwrap();
+        }
+        let value = program.data[offset];
+        let span = &program.data_spans[offset];
+        let source = parser.input.get(span.clone()).unwrap_or("");
+        // A packed string shows its source once, then the characters in each word.
+        let packed = source.starts_with(".string");
+        if packed && (offset == 0 || program.data_spans[offset - 1] != *span) {
+            writeln!(out, "{:>indent$}{}", "", source, indent = indent).unwrap();
+        }
+        // Show a stored text address as the label there, if there is one.
+        let target = program
+            .symbols
+            .iter()
+            .filter(|symbol| symbol.section == Section::Text)
+            .find(|symbol| symbol.address == value as u16 as usize)
+            .filter(|_| program.code_addresses.contains(&offset));
+        let word = options.radix.format(value as u16);
+        match target {
+            _ if packed => line(
+                &mut out,
+                address,
+                word,
+                &format!("{:?}", String::from_utf8_lossy(&value.to_be_bytes())),
+            ),
+            Some(symbol) => line(&mut out, address, word, &format!("@{}", symbol.name)),
+            None => line(&mut out, address, word, &value),
+        }
+
+        // The words of a `rep` share its span and value; show them as one line and a count.
+        let copies = program.data_spans[offset..]
+            .iter()
+            .zip(&program.data[offset..])
+            .take_while(|(other, &word)| {
+                !packed && !span.is_empty() && *other == span && word == value
+            })
+            .count()
+            .max(1);
+        if copies > 1 {
+            writeln!(
+                out,
+                "{:>indent$}({} copies, through {:02x})",
+                "",
+                copies,
+                address + copies - 1,
+                indent = indent
+            )
+            .unwrap();
+        }
+        offset += copies;
+    }
+    for symbol in labels {
+        writeln!(out, "{:>indent$}{}:", "", symbol.name, indent = indent).unwrap();
+    }
+
+    writeln!(out, "\nSymbols:").unwrap();
+    for symbol in &program.symbols {
+        writeln!(out, "  {}", symbol).unwrap();
+    }
+
+    out
+}
@@ -0,0 +1,85 @@
+//! Leveled messages about what the assembler is doing, for `--log-level`.
+//!
+//! Logging is off until a level is set, so a successful run prints nothing extra. Messages
+//! go to stderr, leaving stdout to outputs written to `-`. The passes log what they did at
+//! `debug`: the tokens each section took, the labels defined, files included and written,
+//! and what the optimizer changed.
+//!
+//! ```
+//! use single_address_assembler::log::{self, Level};
+//!
+//! assert!(!log::enabled(Level::Error));
+//! log::set_max_level(Level::from_name("info"));
+//! assert!(log::enabled(Level::Warn) && log::enabled(Level::Info));
+//! assert!(!log::enabled(Level::Debug));
+//! single_address_assembler::log!(Info, "printed to stderr as `[info] {}`", 42);
+//! log::set_max_level(None);
+//! ```
+
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// How much detail a message carries, from least to most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    /// The level for a `--log-level` name, in any case, or `None` for `off` or an unknown
+    /// name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "error" => Some(Self::Error),
+            "warn" => Some(Self::Warn),
+            "info" => Some(Self::Info),
+            "debug" => Some(Self::Debug),
+            "trace" => Some(Self::Trace),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warn => "warn",
+            Self::Info => "info",
+            Self::Debug => "debug",
+            Self::Trace => "trace",
+        }
+    }
+}
+
+/// The most detailed level written, as its discriminant; 0 is off.
+static MAX_LEVEL: AtomicUsize = AtomicUsize::new(0);
+
+/// Writes messages up to `level`, or none for `None`.
+pub fn set_max_level(level: Option<Level>) {
+    MAX_LEVEL.store(level.map_or(0, |level| level as usize), Ordering::Relaxed);
+}
+
+/// Whether messages at `level` are written.
+pub fn enabled(level: Level) -> bool {
+    level as usize <= MAX_LEVEL.load(Ordering::Relaxed)
+}
+
+/// Writes one message; use [`log!`](crate::log!), which skips formatting when the level is
+/// off.
+#[doc(hidden)]
+pub fn write(level: Level, message: fmt::Arguments) {
+    eprintln!("[{}] {}", level.name(), message);
+}
+
+/// Logs a message at a [`Level`] named by its variant: `log!(Debug, "{} labels", n)`.
+#[macro_export]
+macro_rules! log {
+    ($level:ident, $($arg:tt)+) => {
+        if $crate::log::enabled($crate::log::Level::$level) {
+            $crate::log::write($crate::log::Level::$level, format_args!($($arg)+));
+        }
+    };
+}
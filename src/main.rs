@@ -1,53 +1,1504 @@
-use clap::{App, Arg};
+use clap::ArgMatches;
 
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::env;
+use std::ffi::OsStr;
+use std::fmt;
 use std::fs::{self, OpenOptions};
-use std::io::Write;
+use std::io::{self, Read, Write};
+use std::panic;
 use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, SystemTime};
 
-mod token;
-use token::Token;
-
-mod parser;
-use parser::*;
-
-mod instructions;
-use instructions::*;
-
-fn main() -> Result<(), std::io::Error> {
-    let matches = App::new("One-Address CPU Assembler")
-        .version("1.0")
-        .about("Assembles input for use with the One-Address CPU")
-        .arg(
-            Arg::with_name("input")
-                .help("input file to assemble")
-                .required(true)
-                .takes_value(true)
-                .value_name("INPUT")
-                .index(1),
+mod cli;
+
+/// Where a line of output goes.
+#[derive(Clone, Copy)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+thread_local! {
+    /// Lines printed on this thread while an `--each` run holds them back, so that each
+    /// input's output appears together, in input order.
+    static HELD: RefCell<Option<Vec<(Stream, String)>>> = const { RefCell::new(None) };
+}
+
+/// Prints a line of diagnostics to stderr, or holds it for an `--each` run.
+macro_rules! diag {
+    ($($arg:tt)*) => {
+        print_line(Stream::Stderr, format_args!($($arg)*))
+    };
+}
+
+/// Prints a line of results to stdout, or holds it for an `--each` run.
+macro_rules! out {
+    ($($arg:tt)*) => {
+        print_line(Stream::Stdout, format_args!($($arg)*))
+    };
+}
+
+fn print_line(stream: Stream, line: fmt::Arguments) {
+    HELD.with(|held| match (held.borrow_mut().as_mut(), stream) {
+        (Some(held), _) => held.push((stream, line.to_string())),
+        (None, Stream::Stdout) => println!("{}", line),
+        (None, Stream::Stderr) => eprintln!("{}", line),
+    })
+}
+
+/// Runs `f`, returning the lines it printed with `diag!` and `out!` instead of printing
+/// them.
+fn holding_output<T>(f: impl FnOnce() -> T) -> (T, Vec<(Stream, String)>) {
+    HELD.with(|held| *held.borrow_mut() = Some(Vec::new()));
+    let result = f();
+    let held = HELD.with(|held| held.borrow_mut().take());
+    (result, held.unwrap_or_default())
+}
+
+/// Prints lines returned by [`holding_output`].
+fn release_output(lines: Vec<(Stream, String)>) {
+    for (stream, line) in lines {
+        match stream {
+            Stream::Stdout => println!("{}", line),
+            Stream::Stderr => eprintln!("{}", line),
+        }
+    }
+}
+
+use single_address_assembler::cpu::CpuSpec;
+use single_address_assembler::debugger::{Command as DebugCommand, Debugger};
+use single_address_assembler::diagnostic::{
+    decode_source, decode_source_lossy, Severity, SourceMap,
+};
+use single_address_assembler::emulator::{DivZeroMode, Event, Machine, Step, StepResult, Trap};
+use single_address_assembler::grade::{ExpectFile, Expectation};
+use single_address_assembler::image::{self, CellWidth, Endian, ImageOptions, OutputFormat};
+use single_address_assembler::log::{self, Level};
+use single_address_assembler::profile::Profile;
+use single_address_assembler::stats::Stats;
+use single_address_assembler::trace::{self, TraceFormat};
+use single_address_assembler::vectors::{self, PinFile, RecordError};
+use single_address_assembler::{circ, compare, verify};
+use single_address_assembler::{
+    disasm, formatter, json, listing, xref, Address, AddressedInstruction, AddressedProgram,
+    LabelStyle, LintLevel, ParseError, ParseOptions, Parser, Section, Span,
+};
+
+fn main() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        eprintln!("error: internal assembler error; please report it");
+        process::exit(Failure::Internal.exit_code());
+    }));
+    let matches = cli::app().get_matches();
+    let log_level = match matches.subcommand() {
+        (_, Some(sub)) => sub.value_of("log-level"),
+        _ => matches.value_of("log-level"),
+    };
+    let log_level = log_level
+        .map(str::to_owned)
+        .or_else(|| env::var("ASSEMBLER_LOG").ok());
+    log::set_max_level(log_level.as_deref().and_then(Level::from_name));
+
+    let result = match matches.subcommand() {
+        ("assemble", Some(sub)) => assemble_or_watch(sub),
+        ("check", Some(sub)) => check(sub),
+        ("debug", Some(sub)) => debug(sub),
+        ("disasm", Some(sub)) => disassemble(sub),
+        ("fmt", Some(sub)) => format(sub),
+        ("grade", Some(sub)) => grade(sub),
+        ("inject", Some(sub)) => inject(sub),
+        ("run", Some(sub)) => run(sub),
+        ("symbols", Some(sub)) => symbols(sub),
+        ("test", Some(sub)) => test(sub),
+        ("vectors", Some(sub)) => vectors(sub),
+        ("verify", Some(sub)) => verify(sub),
+        _ => assemble_or_watch(&matches),
+    };
+    if let Err(failure) = result {
+        process::exit(failure.exit_code());
+    }
+}
+
+fn image_options(matches: &ArgMatches) -> ImageOptions {
+    ImageOptions {
+        format: OutputFormat::from_name(matches.value_of("format").unwrap()),
+        data_width: matches
+            .value_of("data-width")
+            .and_then(CellWidth::from_bits),
+        text_width: CellWidth::from_bits(matches.value_of("text-width").unwrap()).unwrap(),
+        rle: matches.is_present("rle"),
+        per_line: matches.value_of("per-line").unwrap().parse().unwrap(),
+        pad: if matches.is_present("pad") {
+            Some(parse_word(matches.value_of("pad-value").unwrap_or("0")).unwrap())
+        } else {
+            None
+        },
+        data_endian: Endian::from_name(matches.value_of("data-endian").unwrap()),
+        annotate: matches.is_present("annotate"),
+        header: vec![],
+    }
+}
+
+/// Why a command failed; diagnostics have already been printed by the time one is returned.
+/// Each kind exits with its own code, and scripts may rely on the numbers staying put:
+/// 0 is success, 1 a syntax error, 2 a label that does not resolve, 3 a program too big
+/// for memory, 4 an I/O error, 5 a bug in the assembler, 6 a trap, 7 the step limit, and
+/// 8 a failed check. Clap also exits with 1 for a malformed command line.
+enum Failure {
+    /// The source, or another input such as an image or CPU spec, does not parse, or a
+    /// denied warning or validation issue was found.
+    Syntax,
+    /// A label or constant is undefined, defined twice, the wrong kind, or out of reach.
+    Resolution,
+    /// The program does not fit in text or data memory.
+    Overflow,
+    Io,
+    /// The assembler panicked.
+    Internal,
+    /// The simulated program faulted under `run`.
+    Trap,
+    /// The program was still running at the step limit under `run`, `grade`, or `vectors`.
+    Timeout,
+    /// The program ran, but not every `grade` expectation held, or `verify` found a
+    /// word that does not round-trip, or `inject` found a memory that does not fit, or
+    /// `fmt --check` found a file that is not formatted.
+    Mismatch,
+}
+
+impl Failure {
+    fn exit_code(&self) -> i32 {
+        match self {
+            Self::Syntax => 1,
+            Self::Resolution => 2,
+            Self::Overflow => 3,
+            Self::Io => 4,
+            Self::Internal => 5,
+            Self::Trap => 6,
+            Self::Timeout => 7,
+            Self::Mismatch => 8,
+        }
+    }
+
+    /// The kind of failure `err` is.
+    fn from_parse_error(err: &ParseError) -> Self {
+        use ParseError::*;
+
+        match err {
+            DuplicateLabel(..)
+            | UnknownLabel(..)
+            | WrongLabelKind(..)
+            | AddressOutOfRange(..)
+            | BranchOutOfReach(..)
+            | DuplicateConstant(..)
+            | DuplicateAlias(..) => Self::Resolution,
+            InstructionOverflow(..) | DataOverflow(..) | RepeatOverflow(..) => Self::Overflow,
+            IncludeFailed(..) => Self::Io,
+            _ => Self::Syntax,
+        }
+    }
+}
+
+/// Where data starts in a `--combined` image when `--data-base` is not given: halfway
+/// through the 256 words an 8-bit data operand can reach, leaving the rest for the text.
+const DEFAULT_COMBINED_DATA_BASE: usize = 0x80;
+
+/// Parses a decimal or `0x`-prefixed hexadecimal address.
+fn parse_address(value: &str) -> Result<usize, String> {
+    let parsed = if let Some(hex) = value.strip_prefix("0x") {
+        usize::from_str_radix(hex, 16)
+    } else {
+        value.parse()
+    };
+    parsed.map_err(|_| format!("`{}` is not a valid address", value))
+}
+
+/// Parses a decimal or `0x`-prefixed hexadecimal 16-bit word.
+fn parse_word(value: &str) -> Result<u16, String> {
+    let parsed = if let Some(hex) = value.strip_prefix("0x") {
+        u16::from_str_radix(hex, 16)
+    } else {
+        value.parse()
+    };
+    parsed.map_err(|_| format!("`{}` is not a valid 16-bit word", value))
+}
+
+/// Parses a decimal or `0x`-prefixed hexadecimal byte.
+fn parse_byte(value: &str) -> Result<u8, String> {
+    let parsed = if let Some(hex) = value.strip_prefix("0x") {
+        u8::from_str_radix(hex, 16)
+    } else {
+        value.parse()
+    };
+    parsed.map_err(|_| format!("`{}` is not a valid byte", value))
+}
+
+/// Parses an `--alias NAME=MNEMONIC` argument, checking that the alias is allowed.
+fn parse_alias(alias: &str) -> Result<(&str, &str), String> {
+    let (name, mnemonic) = match alias.find('=') {
+        Some(eq) => (&alias[..eq], &alias[eq + 1..]),
+        None => return Err(format!("`{}` is not of the form NAME=MNEMONIC", alias)),
+    };
+    Parser::new("").define_alias(name, mnemonic)?;
+    Ok((name, mnemonic))
+}
+
+/// Parses a `--define NAME=VALUE` argument; the value is a decimal or `0x` hex `i16`.
+fn parse_define(define: &str) -> Result<(String, i16), String> {
+    let (name, value) = match define.find('=') {
+        Some(eq) => (&define[..eq], &define[eq + 1..]),
+        None => return Err(format!("`{}` is not of the form NAME=VALUE", define)),
+    };
+    let valid_name =
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_name {
+        return Err(format!("`{}` is not a valid constant name", name));
+    }
+
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value),
+    };
+    let magnitude = match digits.strip_prefix("0x") {
+        Some(hex) => i32::from_str_radix(hex, 16),
+        None => digits.parse(),
+    };
+    let parsed = magnitude
+        .ok()
+        .map(|m| if negative { -m } else { m })
+        .and_then(|v| i16::try_from(v).ok());
+    match parsed {
+        Some(value) => Ok((name.to_owned(), value)),
+        None => Err(format!(
+            "`{}` is not a valid value for `{}`; expected an integer in {}..={}",
+            value,
+            name,
+            i16::MIN,
+            i16::MAX
+        )),
+    }
+}
+
+/// Name used for an input in diagnostics; `-` is reported as `<stdin>`.
+fn source_name(path: &Path) -> String {
+    if path == Path::new("-") {
+        "<stdin>".to_owned()
+    } else {
+        path.display().to_string()
+    }
+}
+
+/// Reads an input file, or all of stdin when the path is `-`, dropping a byte order mark.
+/// Bytes that are not UTF-8 are an error, or with `lossy` a warning and replaced.
+fn read_source(path: &Path, lossy: bool) -> Result<String, Failure> {
+    let bytes = if path == Path::new("-") {
+        let mut bytes = Vec::new();
+        io::stdin().read_to_end(&mut bytes).map(|_| bytes)
+    } else {
+        fs::read(path)
+    }
+    .map_err(|e| io_failure("read", path, e))?;
+
+    match decode_source(&bytes) {
+        Ok(text) => Ok(text),
+        Err(err) if lossy => {
+            diag!(
+                "{}:{}:{}: warning: {}; continuing with replacement characters",
+                source_name(path),
+                err.line,
+                err.column,
+                err
+            );
+            Ok(decode_source_lossy(&bytes))
+        }
+        Err(err) => {
+            diag!(
+                "{}:{}:{}: error: {}, or pass --lossy",
+                source_name(path),
+                err.line,
+                err.column,
+                err
+            );
+            Err(Failure::Io)
+        }
+    }
+}
+
+fn io_failure(action: &str, path: &Path, err: io::Error) -> Failure {
+    diag!("error: could not {} `{}`: {}", action, path.display(), err);
+    Failure::Io
+}
+
+/// Reads and parses a Logisim image file, reporting problems against `path`.
+fn read_image(path: &Path) -> Result<(image::ImageKind, Vec<u16>), Failure> {
+    let contents = read_source(path, false)?;
+    image::parse_image(&contents).map_err(|err| {
+        diag!("{}:{}: error: {}", source_name(path), err.line, err.message);
+        Failure::Syntax
+    })
+}
+
+fn disassemble(matches: &ArgMatches) -> Result<(), Failure> {
+    let cpu = cpu_spec(matches)?;
+    let input = Path::new(matches.value_of("input").unwrap());
+    if input.extension().is_some_and(|ext| ext == "json") {
+        let contents = read_source(input, false)?;
+        let program = json::program_from_json(&contents, cpu.clone()).map_err(|err| {
+            diag!("{}: error: {}", source_name(input), err);
+            Failure::Syntax
+        })?;
+        print!(
+            "{}",
+            disasm::disassemble(
+                &program.text_words(),
+                Some(&program.data),
+                &program.code_addresses,
+                matches.is_present("labels"),
+                &cpu
+            )
+        );
+        return Ok(());
+    }
+    let (_, text) = read_image(input)?;
+
+    let data = match matches.value_of("data") {
+        Some(path) => {
+            let (kind, values) = read_image(Path::new(path))?;
+            // Raw data images hold each word as two big-endian bytes unless told otherwise.
+            let width = matches
+                .value_of("data-width")
+                .and_then(CellWidth::from_bits)
+                .unwrap_or(if kind == image::ImageKind::V2Raw {
+                    CellWidth::Bytes
+                } else {
+                    CellWidth::Words
+                });
+            let words: Vec<i16> = if width == CellWidth::Bytes {
+                values
+                    .chunks(2)
+                    .map(|pair| {
+                        i16::from_be_bytes([pair[0] as u8, *pair.get(1).unwrap_or(&0) as u8])
+                    })
+                    .collect()
+            } else {
+                values.into_iter().map(|word| word as i16).collect()
+            };
+            Some(words)
+        }
+        None => None,
+    };
+
+    print!(
+        "{}",
+        disasm::disassemble(
+            &text,
+            data.as_deref(),
+            &[],
+            matches.is_present("labels"),
+            &cpu
         )
-        .arg(
-            Arg::with_name("data")
-                .help("data output file")
-                .short("d")
-                .takes_value(true)
-                .value_name("DATA"),
+    );
+    Ok(())
+}
+
+/// Formats each input in place, or with `--check` reports the ones that would change.
+fn format(matches: &ArgMatches) -> Result<(), Failure> {
+    let color = use_color(matches);
+    let mut result = Ok(());
+    for path in input_files(matches) {
+        let contents = read_source(path, false)?;
+        let formatted = match formatter::format_source(&contents) {
+            Ok(formatted) => formatted,
+            Err(err) => {
+                let mut sources = SourceMap::new();
+                sources.color = color;
+                sources.add_file(source_name(path), &contents);
+                diag!("{}", sources.render(Severity::Error, &err, err.span()));
+                result = Err(Failure::Syntax);
+                continue;
+            }
+        };
+
+        if matches.is_present("check") {
+            if formatted != contents {
+                diag!("{}: not formatted", source_name(path));
+                result = Err(Failure::Mismatch);
+            }
+        } else if path == Path::new("-") {
+            print!("{}", formatted);
+        } else if formatted != contents {
+            fs::write(path, formatted).map_err(|e| io_failure("write", path, e))?;
+        }
+    }
+    result
+}
+
+/// The `--cpu-spec` file, or the default spec, with memories resized by `--max-text` and
+/// `--max-data` or `--mem-size`.
+fn cpu_spec(matches: &ArgMatches) -> Result<CpuSpec, Failure> {
+    let mut spec = match matches.value_of("cpu-spec") {
+        Some(path) => {
+            let path = Path::new(path);
+            let contents = read_source(path, false)?;
+            CpuSpec::parse(&contents).map_err(|err| {
+                diag!("{}:{}: error: {}", source_name(path), err.line, err.message);
+                Failure::Syntax
+            })?
+        }
+        None => CpuSpec::default(),
+    };
+    if let Some(words) = matches.value_of("max-text") {
+        spec.set_text_words(words.parse().unwrap());
+    }
+    if let Some(words) = matches
+        .value_of("max-data")
+        .or(matches.value_of("mem-size"))
+    {
+        spec.set_data_words(words.parse().unwrap());
+    }
+    Ok(spec)
+}
+
+/// The input paths, in the order given.
+fn input_files<'a>(matches: &'a ArgMatches) -> Vec<&'a Path> {
+    matches.values_of("input").unwrap().map(Path::new).collect()
+}
+
+/// Whether diagnostics should be colored, per `--color`.
+fn use_color(matches: &ArgMatches) -> bool {
+    match matches.value_of("color") {
+        Some("always") => enable_ansi(),
+        Some("never") => false,
+        _ => atty::is(atty::Stream::Stderr) && enable_ansi(),
+    }
+}
+
+/// Turns on ANSI escape handling in the console; `false` if the console cannot show them.
+#[cfg(windows)]
+fn enable_ansi() -> bool {
+    ansi_term::enable_ansi_support().is_ok()
+}
+
+#[cfg(not(windows))]
+fn enable_ansi() -> bool {
+    true
+}
+
+/// Reads every input, in order, into one source map.
+fn load_sources(input_files: &[&Path], matches: &ArgMatches) -> Result<SourceMap, Failure> {
+    let mut sources = SourceMap::new();
+    sources.color = use_color(matches);
+    for path in input_files {
+        let contents = read_source(path, matches.is_present("lossy"))?;
+        single_address_assembler::log!(
+            Debug,
+            "read {} bytes from {}",
+            contents.len(),
+            source_name(path)
+        );
+        sources.add_file(source_name(path), &contents);
+    }
+    Ok(sources)
+}
+
+/// The language extensions and warning strictness the command line asks for.
+fn parse_options(matches: &ArgMatches) -> ParseOptions {
+    let options = ParseOptions::new()
+        .allow_div_zero(matches.is_present("allow-div-zero"))
+        .pedantic(matches.is_present("strict"))
+        .strict(matches.is_present("deny-warnings"))
+        .require_halt(matches.is_present("require-halt"));
+    let options = match matches.value_of("label-style") {
+        Some(pattern) => options
+            .label_style(LabelStyle::new(pattern).unwrap())
+            .level("W007", LintLevel::Warn),
+        None => options,
+    };
+    // When a code is both allowed and denied, the flag given last wins.
+    let mut levels: Vec<(usize, &str, LintLevel)> = Vec::new();
+    for (flag, level) in &[("allow", LintLevel::Allow), ("deny", LintLevel::Deny)] {
+        if let (Some(indices), Some(codes)) = (matches.indices_of(flag), matches.values_of(flag)) {
+            levels.extend(
+                indices
+                    .zip(codes)
+                    .map(|(index, code)| (index, code, *level)),
+            );
+        }
+    }
+    levels.sort_by_key(|&(index, _, _)| index);
+    levels
+        .into_iter()
+        .fold(options, |options, (_, code, level)| {
+            options.level(code, level)
+        })
+}
+
+/// Parses and addresses `sources`, reporting errors and warnings as they are found.
+fn assemble_program<'a>(
+    matches: &ArgMatches,
+    sources: &'a SourceMap,
+) -> Result<(Parser<'a>, AddressedProgram), Failure> {
+    let report = |severity, message: &dyn fmt::Display, span, note: Option<(&str, Span)>| {
+        diag!("{}", sources.render(severity, message, span));
+        if let Some((note, span)) = note {
+            diag!("{}", sources.render(Severity::Note, note, Some(span)));
+        }
+    };
+    let report_error = |err: ParseError| {
+        report(Severity::Error, &err, err.span(), err.note());
+        Failure::from_parse_error(&err)
+    };
+
+    let mut parser = Parser::with_options(&sources.text, parse_options(matches));
+    parser.cpu = cpu_spec(matches)?;
+    parser.file_starts = sources.file_starts();
+    parser.file_dirs = sources.file_dirs();
+    if let Some(pad) = matches.value_of("pad-byte") {
+        parser.pad_byte = Some(parse_byte(pad).unwrap());
+    }
+    parser.optimizing = matches.is_present("optimize");
+    for define in matches.values_of("define").into_iter().flatten() {
+        let (name, value) = parse_define(define).unwrap();
+        parser.constants.insert(name, (value, None));
+    }
+    for alias in matches.values_of("alias").into_iter().flatten() {
+        let (name, mnemonic) = parse_alias(alias).unwrap();
+        if let Err(err) = parser.define_alias(name, mnemonic) {
+            diag!("error: --alias {}: {}", alias, err);
+            return Err(Failure::Syntax);
+        }
+    }
+    parser.data_base = match matches.value_of("data-base") {
+        Some(base) => parse_address(base).unwrap(),
+        None if matches.is_present("combined") => DEFAULT_COMBINED_DATA_BASE,
+        None => 0,
+    };
+    if let Some(base) = matches.value_of("text-base") {
+        parser.text_base = parse_address(base).unwrap();
+    }
+    if let Err(err) = parser.parse_program() {
+        // Only errors in the checks after parsing are missing from `errors`.
+        if parser.errors.is_empty() {
+            parser.errors.push(err);
+        }
+        // The first error decides the exit code; later ones are often knock-on effects.
+        let failure = Failure::from_parse_error(&parser.errors[0]);
+        for err in parser.errors.drain(..) {
+            report_error(err);
+        }
+        return Err(failure);
+    }
+    if parser.optimizing {
+        parser.optimize();
+    }
+    let addressed = parser.address_program().map_err(report_error)?;
+
+    let mut denied = 0;
+    for warning in &parser.warnings {
+        let message = format!("{} [{}]", warning, warning.code());
+        report(Severity::Warning, &message, warning.span(), warning.note());
+    }
+    let strict = parser.options.strict || matches.is_present("strict");
+    for issue in addressed.validate() {
+        let severity = if strict {
+            Severity::Error
+        } else {
+            issue.severity()
+        };
+        if severity == Severity::Error {
+            denied += 1;
+        }
+        report(severity, &issue, issue.span(&addressed), None);
+    }
+    if denied > 0 {
+        diag!("error: aborting due to {} previous error(s)", denied);
+        return Err(Failure::Syntax);
+    }
+
+    Ok((parser, addressed))
+}
+
+/// Resolves each value of a `--break` or `--watch-data` flag to the address of a label in
+/// `section`, exiting with a usage error for names that are not such labels.
+fn label_addresses(
+    matches: &ArgMatches,
+    flag: &str,
+    section: Section,
+    addressed: &AddressedProgram,
+) -> Vec<(String, usize)> {
+    matches
+        .values_of(flag)
+        .into_iter()
+        .flatten()
+        .map(|name| match addressed.symbol(name) {
+            Some(symbol) if symbol.section == section => (name.to_owned(), symbol.address),
+            _ => clap::Error::with_description(
+                &format!("--{}: there is no {} label named `{}`", flag, section, name),
+                clap::ErrorKind::InvalidValue,
+            )
+            .exit(),
+        })
+        .collect()
+}
+
+/// What the user asked for at a `run` pause.
+enum Command {
+    Continue,
+    Step,
+    Quit,
+}
+
+/// Asks on stderr what to do next; end of input quits.
+fn prompt() -> Command {
+    loop {
+        eprint!("(c)ontinue, (s)tep, (q)uit? ");
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => return Command::Quit,
+            Ok(_) => {}
+        }
+        match line.trim() {
+            "" | "c" | "continue" => return Command::Continue,
+            "s" | "step" => return Command::Step,
+            "q" | "quit" => return Command::Quit,
+            _ => {}
+        }
+    }
+}
+
+/// Streams `--trace` output to stdout, remembering the first write error instead of
+/// interrupting the run.
+struct Tracer {
+    out: io::BufWriter<io::Stdout>,
+    format: TraceFormat,
+    error: Option<io::Error>,
+}
+
+impl Tracer {
+    fn new(format: TraceFormat) -> Self {
+        let mut tracer = Self {
+            out: io::BufWriter::new(io::stdout()),
+            format,
+            error: None,
+        };
+        let result = trace::write_header(&mut tracer.out, format);
+        tracer.check(result);
+        tracer
+    }
+
+    fn record(&mut self, step: &Step) {
+        if self.error.is_none() {
+            let result = trace::write_step(&mut self.out, step, self.format);
+            self.check(result);
+        }
+    }
+
+    /// Flushes buffered lines so they appear before anything else printed to stdout.
+    fn flush(&mut self) {
+        let result = self.out.flush();
+        self.check(result);
+    }
+
+    fn check(&mut self, result: io::Result<()>) {
+        if let Err(err) = result {
+            self.error.get_or_insert(err);
+        }
+    }
+}
+
+/// Prints a pause in a run with the machine state and the source line at `pc`.
+fn report_pause(
+    message: String,
+    pc: usize,
+    machine: &Machine,
+    addressed: &AddressedProgram,
+    sources: &SourceMap,
+) {
+    let message = format!(
+        "{} after {} steps, ac = {} ({:#06x})",
+        message, machine.steps, machine.ac, machine.ac as u16
+    );
+    let span = addressed.text_spans.get(pc).cloned();
+    println!("{}", sources.render(Severity::Note, message, span));
+}
+
+/// Runs `machine` in lockstep with the Logisim probe log at `log` for `run --compare`,
+/// reporting the first place they disagree.
+fn compare_run(
+    matches: &ArgMatches,
+    log: &Path,
+    machine: &mut Machine,
+    addressed: &AddressedProgram,
+    sources: &SourceMap,
+) -> Result<(), Failure> {
+    let contents = read_source(log, false)?;
+    let columns =
+        compare::Columns::parse(matches.value_of("compare-columns").unwrap_or("")).unwrap();
+    let radix = listing::Radix::from_name(matches.value_of("compare-radix").unwrap());
+    let samples = compare::parse_log(&contents, &columns, radix).map_err(|err| {
+        diag!("{}:{}", log.display(), err);
+        Failure::Io
+    })?;
+
+    match compare::compare(machine, addressed, &samples) {
+        Ok(matched) => {
+            println!(
+                "{}: all {} instruction boundaries match the emulator",
+                log.display(),
+                matched
+            );
+            Ok(())
+        }
+        Err(divergence) => {
+            let message = match divergence.after {
+                Some(index) => format!("{}, after `{}`", divergence, addressed.text[index]),
+                None => format!("{}, before the first instruction", divergence),
+            };
+            let span = divergence
+                .after
+                .and_then(|index| addressed.text_spans.get(index).cloned());
+            diag!("{}", sources.render(Severity::Error, message, span));
+            Err(Failure::Mismatch)
+        }
+    }
+}
+
+fn run(matches: &ArgMatches) -> Result<(), Failure> {
+    let sources = load_sources(&input_files(matches), matches)?;
+    let (_, addressed) = assemble_program(matches, &sources)?;
+
+    let max_steps = matches.value_of("max-steps").unwrap().parse().unwrap();
+    let mut machine = Machine::new(&addressed);
+    machine.trap_uninit = matches.is_present("trap-uninit");
+    machine.on_div_zero = DivZeroMode::from_name(matches.value_of("on-div-zero").unwrap());
+    if let Some(path) = matches.value_of("resume").map(Path::new) {
+        let contents = read_source(path, false)?;
+        contents
+            .parse()
+            .and_then(|state| machine.restore(&state))
+            .map_err(|err| {
+                diag!("error: could not resume from `{}`: {}", path.display(), err);
+                Failure::Io
+            })?;
+    }
+    if let Some(log) = matches.value_of("compare").map(Path::new) {
+        return compare_run(matches, log, &mut machine, &addressed, &sources);
+    }
+    let mut snapshot_at = matches.values_of("snapshot-at").map(|mut values| {
+        let step = values.next().unwrap();
+        let step: usize = step.parse().unwrap_or_else(|_| {
+            clap::Error::with_description(
+                &format!("--snapshot-at: `{}` is not a valid step count", step),
+                clap::ErrorKind::InvalidValue,
+            )
+            .exit()
+        });
+        (step, Path::new(values.next().unwrap()))
+    });
+    let watched = label_addresses(matches, "watch-data", Section::Data, &addressed);
+    machine.breakpoints = label_addresses(matches, "break", Section::Text, &addressed)
+        .into_iter()
+        .map(|(_, address)| address)
+        .collect();
+    machine.watchpoints = watched
+        .iter()
+        .map(|(_, address)| Address::try_from(*address).unwrap())
+        .collect();
+    let interactive = !matches.is_present("break-dump") && atty::is(atty::Stream::Stdin);
+    let mut tracer = if matches.is_present("trace") {
+        let format = matches.value_of("trace-format").unwrap_or("text");
+        Some(Tracer::new(TraceFormat::from_name(format)))
+    } else {
+        None
+    };
+
+    let mut profile = if matches.is_present("profile") || matches.is_present("profile-json") {
+        Some(Profile::new(&addressed))
+    } else {
+        None
+    };
+
+    // `None` when the user quits at a pause.
+    let result = 'run: loop {
+        // Stop early at the snapshot step, as though it were the step limit.
+        let limit = match snapshot_at {
+            Some((step, _)) => step.min(max_steps),
+            None => max_steps,
+        };
+        let event = machine.resume_with(limit, |step| {
+            if let Some(tracer) = &mut tracer {
+                tracer.record(step);
+            }
+            if let Some(profile) = &mut profile {
+                profile.record(step);
+            }
+        });
+        if let Some(tracer) = &mut tracer {
+            tracer.flush();
+        }
+        match event {
+            Ok(Event::Stopped(stop)) => break Ok(Some(stop)),
+            Err(Trap::StepLimit(_)) if limit < max_steps => {
+                let (_, path) = snapshot_at.take().unwrap();
+                create_output(path)
+                    .and_then(|mut out| out.write_all(machine.snapshot().to_string().as_bytes()))
+                    .map_err(|e| io_failure("write", path, e))?;
+                continue;
+            }
+            Err(trap) => break Err(trap),
+            Ok(Event::Breakpoint(pc)) => {
+                let message = format!("breakpoint at pc {:#04x}", pc);
+                report_pause(message, pc, &machine, &addressed, &sources);
+            }
+            Ok(Event::Watchpoint {
+                pc,
+                address,
+                old,
+                new,
+            }) => {
+                let name = &watched
+                    .iter()
+                    .find(|(_, watched)| *watched == usize::from(address))
+                    .unwrap()
+                    .0;
+                let message = format!(
+                    "`{}` changed from {} to {} at pc {:#04x}",
+                    name, old, new, pc
+                );
+                report_pause(message, pc, &machine, &addressed, &sources);
+            }
+        }
+        if !interactive {
+            continue;
+        }
+        loop {
+            match prompt() {
+                Command::Continue => continue 'run,
+                Command::Quit => break 'run Ok(None),
+                Command::Step => match machine.step_within(max_steps) {
+                    Ok(StepResult::Executed(step)) => {
+                        if let Some(tracer) = &mut tracer {
+                            tracer.record(&step);
+                            tracer.flush();
+                        }
+                        if let Some(profile) = &mut profile {
+                            profile.record(&step);
+                        }
+                        let message = format!("stepped to pc {:#04x}", machine.pc);
+                        report_pause(message, machine.pc, &machine, &addressed, &sources);
+                    }
+                    Ok(StepResult::Stopped(stop)) => break 'run Ok(Some(stop)),
+                    Err(trap) => break 'run Err(trap),
+                },
+            }
+        }
+    };
+
+    if let Some(err) = tracer.and_then(|tracer| tracer.error) {
+        diag!("error: could not write the trace: {}", err);
+        return Err(Failure::Io);
+    }
+    if let Some((step, _)) = snapshot_at {
+        diag!(
+            "warning: the run ended before step {}, so no snapshot was saved",
+            step
+        );
+    }
+    match result {
+        Ok(Some(stop)) => println!(
+            "{} after {} steps at pc {:#04x}",
+            stop, machine.steps, machine.pc
+        ),
+        Ok(None) => println!(
+            "quit after {} steps at pc {:#04x}",
+            machine.steps, machine.pc
+        ),
+        Err(Trap::StepLimit(_)) => println!(
+            "stopped at the step limit after {} steps at pc {:#04x}",
+            machine.steps, machine.pc
+        ),
+        Err(trap) => {
+            let message = trap_message(&trap, &machine, &addressed);
+            let span = addressed.text_spans.get(machine.pc).cloned();
+            diag!("{}", sources.render(Severity::Error, message, span));
+        }
+    }
+    println!("ac = {} ({:#06x})", machine.ac, machine.ac as u16);
+    if matches.is_present("dump") {
+        for (row, words) in machine.data.chunks(8).enumerate() {
+            let words: Vec<String> = words.iter().map(|w| format!("{:04x}", *w as u16)).collect();
+            println!("{:02x}: {}", row * 8, words.join(" "));
+        }
+    }
+    if let Some(profile) = &profile {
+        if matches.is_present("profile") {
+            print!("\n{}", profile.report(&addressed, &sources));
+        }
+        if let Some(path) = matches.value_of("profile-json").map(Path::new) {
+            create_output(path)
+                .and_then(|mut out| out.write_all(profile.to_json(&addressed, &sources).as_bytes()))
+                .map_err(|e| io_failure("write", path, e))?;
+        }
+    }
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(Trap::StepLimit(_)) => {
+            diag!("note: use --max-steps to allow longer runs");
+            Err(Failure::Timeout)
+        }
+        Err(_) => Err(Failure::Trap),
+    }
+}
+
+/// Reads debugger commands from stdin until `quit` or the end of input. An empty line
+/// repeats the last command, and commands piped in are echoed after the prompt.
+fn debug(matches: &ArgMatches) -> Result<(), Failure> {
+    let sources = load_sources(&input_files(matches), matches)?;
+    let (_, addressed) = assemble_program(matches, &sources)?;
+    let max_steps = matches.value_of("max-steps").unwrap().parse().unwrap();
+    let mut debugger = Debugger::new(&addressed, &sources, max_steps);
+    let interactive = atty::is(atty::Stream::Stdin);
+
+    println!("{}", debugger.execute(&DebugCommand::Where).unwrap());
+    let mut last = None;
+    loop {
+        print!("(debug) ");
+        io::stdout().flush().ok();
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => {
+                println!();
+                return Ok(());
+            }
+            Ok(_) => {}
+        }
+        if !interactive {
+            print!("{}", line);
+        }
+        let command = if line.trim().is_empty() {
+            match &last {
+                Some(command) => Ok(command),
+                None => continue,
+            }
+        } else {
+            line.parse().map(|command| &*last.insert(command))
+        };
+        match command {
+            Ok(DebugCommand::Quit) => return Ok(()),
+            Ok(command) => match debugger.execute(command) {
+                Ok(output) => println!("{}", output),
+                Err(err) => println!("error: {}", err),
+            },
+            Err(err) => println!("error: {}", err),
+        }
+    }
+}
+
+/// Describes a trap for a report, naming the data word a division by zero read.
+fn trap_message(trap: &Trap, machine: &Machine, addressed: &AddressedProgram) -> String {
+    use AddressedInstruction::*;
+
+    let mut message = trap.to_string();
+    if let Trap::DivisionByZero { instr, .. } = trap {
+        let divisor = match *instr {
+            Divide(addr) | Remainder(addr) => Some(addr),
+            DivideIndirect(ptr) | RemainderIndirect(ptr) => Some(machine.read(ptr) as Address),
+            _ => None,
+        };
+        if let Some(addr) = divisor {
+            match addressed.symbol_at(Section::Data, usize::from(addr)) {
+                Some(symbol) => message += &format!(" (`{}` is 0)", symbol.name),
+                None => message += &format!(" (data word {:#04x} is 0)", addr),
+            }
+        }
+    }
+    format!("{} after {} steps", message, machine.steps)
+}
+
+/// How often `--watch` checks the inputs for changes.
+const WATCH_POLL: Duration = Duration::from_millis(200);
+
+/// How long the inputs must stay unchanged before `--watch` re-assembles.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Last modification time of each input, or `None` for inputs that cannot be read.
+fn modified_times(paths: &[&Path]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| fs::metadata(path).and_then(|meta| meta.modified()).ok())
+        .collect()
+}
+
+/// Assembles the inputs, then again each time one of them changes; outputs are only
+/// rewritten by runs that succeed. Runs until interrupted. Only the inputs themselves are
+/// polled, not the files they pull in with `.incbin` or `.csv`.
+fn watch(matches: &ArgMatches) -> Result<(), Failure> {
+    let input_files = input_files(matches);
+    if input_files.contains(&Path::new("-")) {
+        clap::Error::with_description(
+            "--watch cannot be used when reading from stdin",
+            clap::ErrorKind::ArgumentConflict,
         )
-        .arg(
-            Arg::with_name("text")
-                .help("text output file")
-                .short("t")
-                .takes_value(true)
-                .value_name("TEXT"),
+        .exit();
+    }
+
+    let interactive = atty::is(atty::Stream::Stderr);
+    loop {
+        let mut seen = modified_times(&input_files);
+        if interactive {
+            // Clear the screen so only the latest run's diagnostics are shown.
+            eprint!("\x1b[2J\x1b[H");
+        }
+        let status = match assemble(matches) {
+            Ok(()) => "ok",
+            Err(_) => "failed",
+        };
+        diag!(
+            "[{}] watching {} file(s) for changes; press Ctrl-C to stop",
+            status,
+            input_files.len()
+        );
+
+        while modified_times(&input_files) == seen {
+            thread::sleep(WATCH_POLL);
+        }
+        // Editors often save in several steps; wait for the files to settle.
+        loop {
+            seen = modified_times(&input_files);
+            thread::sleep(WATCH_DEBOUNCE);
+            if modified_times(&input_files) == seen {
+                break;
+            }
+        }
+    }
+}
+
+/// Assembles a program and reports diagnostics without writing any files.
+fn check(matches: &ArgMatches) -> Result<(), Failure> {
+    if matches.is_present("each") {
+        return each_input(matches, check_files);
+    }
+    check_files(matches, &input_files(matches))
+}
+
+/// Checks `input_files` as one program.
+fn check_files(matches: &ArgMatches, input_files: &[&Path]) -> Result<(), Failure> {
+    let sources = load_sources(input_files, matches)?;
+    let (_, addressed) = assemble_program(matches, &sources)?;
+
+    if matches.is_present("verbose") {
+        diag!("{}", Stats::new(&addressed));
+    }
+    out!(
+        "{}: ok ({} instructions, {} data words)",
+        source_name(input_files[0]),
+        addressed.text.len(),
+        addressed.data.len()
+    );
+    Ok(())
+}
+
+/// Runs `job` on each input as a program of its own, for `--each`, several at a time. Each
+/// input's output is held until it finishes and printed in input order, followed by a
+/// count of passes and failures. Fails like the first input that failed.
+fn each_input(
+    matches: &ArgMatches,
+    job: fn(&ArgMatches, &[&Path]) -> Result<(), Failure>,
+) -> Result<(), Failure> {
+    let input_files = input_files(matches);
+    if input_files.contains(&Path::new("-")) {
+        clap::Error::with_description(
+            "--each cannot be used when reading from stdin",
+            clap::ErrorKind::ArgumentConflict,
         )
-        .get_matches();
+        .exit();
+    }
+    let jobs = match matches.value_of("jobs") {
+        Some(jobs) => jobs.parse().unwrap(),
+        None => thread::available_parallelism().map_or(1, |n| n.get()),
+    };
 
-    let input_file = Path::new(matches.value_of("input").unwrap());
+    let next = AtomicUsize::new(0);
+    let (send, receive) = mpsc::channel();
+    let mut finished: Vec<Option<_>> = input_files.iter().map(|_| None).collect();
+    let (mut passed, mut failed) = (0, 0);
+    let mut first_failure = None;
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(input_files.len()) {
+            let (next, input_files, send) = (&next, &input_files, send.clone());
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let path = match input_files.get(index) {
+                    Some(&path) => path,
+                    None => break,
+                };
+                let _ = send.send((index, holding_output(|| job(matches, &[path]))));
+            });
+        }
+        drop(send);
+
+        // Print each input as soon as every one before it has been printed.
+        let mut printed = 0;
+        for (index, outcome) in receive {
+            finished[index] = Some(outcome);
+            while let Some((result, lines)) = finished.get_mut(printed).and_then(Option::take) {
+                release_output(lines);
+                match result {
+                    Ok(()) => passed += 1,
+                    Err(failure) => {
+                        failed += 1;
+                        first_failure.get_or_insert(failure);
+                    }
+                }
+                printed += 1;
+            }
+        }
+    });
+
+    println!("{} passed, {} failed", passed, failed);
+    match first_failure {
+        Some(failure) => Err(failure),
+        None => Ok(()),
+    }
+}
+
+/// Runs a program and checks each `--expect`, reporting every one when any fails.
+fn grade(matches: &ArgMatches) -> Result<(), Failure> {
+    let sources = load_sources(&input_files(matches), matches)?;
+    let (_, addressed) = assemble_program(matches, &sources)?;
+    let expectations: Vec<Expectation> = matches
+        .values_of("expect")
+        .unwrap()
+        .map(|spec| spec.parse().unwrap())
+        .collect();
+
+    let max_steps = matches.value_of("max-steps").unwrap().parse().unwrap();
+    let mut machine = Machine::new(&addressed);
+    match machine.run(max_steps) {
+        Ok(_) => {}
+        Err(Trap::StepLimit(steps)) => {
+            diag!("error: timed out: still running after {} steps", steps);
+            return Err(Failure::Timeout);
+        }
+        Err(trap) => {
+            let message = trap_message(&trap, &machine, &addressed);
+            let span = addressed.text_spans.get(machine.pc).cloned();
+            diag!("{}", sources.render(Severity::Error, message, span));
+            return Err(Failure::Trap);
+        }
+    }
+
+    if expectations
+        .iter()
+        .all(|expectation| expectation.holds(&machine, &addressed))
+    {
+        return Ok(());
+    }
+    for expectation in &expectations {
+        match expectation.actual(&machine, &addressed) {
+            Ok(actual) if actual == expectation.expected => println!("pass  {}", expectation),
+            Ok(actual) => println!("FAIL  {} (got {})", expectation, actual),
+            Err(err) => println!("FAIL  {} ({})", expectation, err),
+        }
+    }
+    Err(Failure::Mismatch)
+}
+
+/// Writes the assembled text and data into the labeled memories of a `.circ` file, keeping
+/// the original as `FILE.bak`.
+fn inject(matches: &ArgMatches) -> Result<(), Failure> {
+    let sources = load_sources(&input_files(matches), matches)?;
+    let (_, addressed) = assemble_program(matches, &sources)?;
+
+    let path = Path::new(matches.value_of("circ").unwrap());
+    let original = fs::read_to_string(path).map_err(|e| io_failure("read", path, e))?;
+    let mut updated = original.clone();
+    let mut injected = Vec::new();
+    for (flag, section) in &[("rom", Section::Text), ("ram", Section::Data)] {
+        let label = match matches.value_of(flag) {
+            Some(label) => label,
+            None => continue,
+        };
+        let result = circ::find(&updated, label).and_then(|component| {
+            let (values, bits) = match section {
+                Section::Text => (addressed.text_words(), 16),
+                // An 8-bit RAM gets each data word as two cells, high byte first.
+                Section::Data if component.data_bits == 8 => {
+                    let bytes = addressed.data_bytes(Endian::Big);
+                    (bytes.into_iter().map(u16::from).collect(), 8)
+                }
+                Section::Data => (addressed.data_words(), 16),
+            };
+            circ::inject(&updated, &component, &values, bits).map(|circ| (circ, values.len()))
+        });
+        match result {
+            Ok((circ, count)) => {
+                updated = circ;
+                injected.push(format!("{} values into `{}`", count, label));
+            }
+            Err(err) => {
+                diag!("{}: error: --{}: {}", path.display(), flag, err);
+                return Err(Failure::Mismatch);
+            }
+        }
+    }
+
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    let backup = PathBuf::from(backup);
+    fs::write(&backup, &original).map_err(|e| io_failure("write", &backup, e))?;
+    fs::write(path, &updated).map_err(|e| io_failure("write", path, e))?;
+    diag!(
+        "Wrote {} in {} (original kept as {})",
+        injected.join(" and "),
+        path.display(),
+        backup.display()
+    );
+    Ok(())
+}
+
+/// Assembles a program and writes its symbol table to `--output`, or stdout.
+fn symbols(matches: &ArgMatches) -> Result<(), Failure> {
+    let sources = load_sources(&input_files(matches), matches)?;
+    let (_, addressed) = assemble_program(matches, &sources)?;
+
+    let output = Path::new(matches.value_of("output").unwrap_or("-"));
+    write_symbols(output, &addressed).map_err(|e| io_failure("write", output, e))
+}
+
+/// Runs each test case in `--dir` and prints a line per case, then a summary.
+fn test(matches: &ArgMatches) -> Result<(), Failure> {
+    let dir = Path::new(matches.value_of("dir").unwrap());
+    let filter = matches.value_of("filter").unwrap_or("");
+    let mut cases = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| io_failure("read", dir, e))? {
+        let path = entry.map_err(|e| io_failure("read", dir, e))?.path();
+        let name = match path.file_stem() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => continue,
+        };
+        if path.extension() == Some(OsStr::new("s"))
+            && path.with_extension("expect").is_file()
+            && name.contains(filter)
+        {
+            cases.push((name, path));
+        }
+    }
+    cases.sort();
+    if cases.is_empty() {
+        diag!(
+            "error: no test cases in `{}`; each needs a NAME.s with a sibling NAME.expect",
+            dir.display()
+        );
+        return Err(Failure::Io);
+    }
+
+    let max_steps = matches.value_of("max-steps").unwrap().parse().unwrap();
+    let width = cases.iter().map(|(name, _)| name.len()).max().unwrap();
+    let mut failed = 0;
+    for (name, path) in &cases {
+        match test_case(matches, path, max_steps) {
+            Ok(()) => println!("{:width$}  pass", name, width = width),
+            Err(reason) => {
+                failed += 1;
+                println!("{:width$}  FAIL  {}", name, reason, width = width);
+            }
+        }
+    }
+    println!("\n{} passed, {} failed", cases.len() - failed, failed);
+    if failed > 0 {
+        Err(Failure::Mismatch)
+    } else {
+        Ok(())
+    }
+}
+
+/// Assembles and runs the program at `path` against its .expect file, or says why it failed.
+fn test_case(matches: &ArgMatches, path: &Path, max_steps: usize) -> Result<(), String> {
+    let expect_path = path.with_extension("expect");
+    let contents = fs::read_to_string(&expect_path)
+        .map_err(|err| format!("could not read `{}`: {}", expect_path.display(), err))?;
+    let file = ExpectFile::parse(&contents)
+        .map_err(|err| format!("{}:{}: {}", expect_path.display(), err.line, err.message))?;
+    let sources =
+        load_sources(&[path], matches).map_err(|_| "could not read the program".to_owned())?;
+    let (_, addressed) =
+        assemble_program(matches, &sources).map_err(|_| "does not assemble".to_owned())?;
+
+    let mut machine = Machine::new(&addressed);
+    for initial in &file.initial {
+        initial
+            .apply(&mut machine, &addressed)
+            .map_err(|err| format!("init {}: {}", initial, err))?;
+    }
+    match machine.run(file.max_steps.unwrap_or(max_steps)) {
+        Ok(_) => {}
+        Err(Trap::StepLimit(steps)) => {
+            return Err(format!("timed out: still running after {} steps", steps))
+        }
+        Err(trap) => {
+            return Err(format!(
+                "trapped: {}",
+                trap_message(&trap, &machine, &addressed)
+            ))
+        }
+    }
+
+    let mismatches: Vec<String> = file
+        .expectations
+        .iter()
+        .filter_map(
+            |expectation| match expectation.actual(&machine, &addressed) {
+                Ok(actual) if actual == expectation.expected => None,
+                Ok(actual) => Some(format!("{} (got {})", expectation, actual)),
+                Err(err) => Some(format!("{} ({})", expectation, err)),
+            },
+        )
+        .collect();
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(mismatches.join(", "))
+    }
+}
+
+/// Round-trips the program's text through the disassembler and reports the first word
+/// that comes back different.
+fn verify(matches: &ArgMatches) -> Result<(), Failure> {
+    let input_files = input_files(matches);
+    let sources = load_sources(&input_files, matches)?;
+    let (_, addressed) = assemble_program(matches, &sources)?;
+
+    if let Err(divergence) = verify::round_trip(&addressed) {
+        let span = addressed.text_spans.get(divergence.address()).cloned();
+        diag!("{}", sources.render(Severity::Error, &divergence, span));
+        return Err(Failure::Mismatch);
+    }
+    println!(
+        "{}: ok ({} words round-trip)",
+        source_name(input_files[0]),
+        addressed.text_words().len()
+    );
+    Ok(())
+}
+
+/// Runs a program and writes the state on each `--pins` output as a test vector file.
+fn vectors(matches: &ArgMatches) -> Result<(), Failure> {
+    let sources = load_sources(&input_files(matches), matches)?;
+    let (_, addressed) = assemble_program(matches, &sources)?;
+
+    let pin_path = Path::new(matches.value_of("pins").unwrap());
+    let contents = fs::read_to_string(pin_path).map_err(|e| io_failure("read", pin_path, e))?;
+    let mut pins = PinFile::parse(&contents).map_err(|err| {
+        diag!(
+            "{}:{}: error: {}",
+            pin_path.display(),
+            err.line,
+            err.message
+        );
+        Failure::Io
+    })?;
+    if let Some(every) = matches.value_of("every") {
+        pins.every = every.parse().unwrap();
+    }
+
+    let max_steps = matches.value_of("max-steps").unwrap().parse().unwrap();
+    let mut machine = Machine::new(&addressed);
+    let rows = match vectors::record(&mut machine, &addressed, &pins, max_steps) {
+        Ok(rows) => rows,
+        Err(RecordError::Trap(Trap::StepLimit(steps))) => {
+            diag!("error: timed out: still running after {} steps", steps);
+            return Err(Failure::Timeout);
+        }
+        Err(RecordError::Trap(trap)) => {
+            let message = trap_message(&trap, &machine, &addressed);
+            let span = addressed.text_spans.get(machine.pc).cloned();
+            diag!("{}", sources.render(Severity::Error, message, span));
+            return Err(Failure::Trap);
+        }
+        Err(err) => {
+            diag!("{}: error: {}", pin_path.display(), err);
+            return Err(Failure::Mismatch);
+        }
+    };
+
+    let output = Path::new(matches.value_of("output").unwrap_or("-"));
+    create_output(output)
+        .and_then(|mut out| out.write_all(vectors::render(&pins, &rows).as_bytes()))
+        .map_err(|e| io_failure("write", output, e))
+}
+
+fn assemble_or_watch(matches: &ArgMatches) -> Result<(), Failure> {
+    if matches.is_present("watch") {
+        watch(matches)
+    } else {
+        assemble(matches)
+    }
+}
+
+fn assemble(matches: &ArgMatches) -> Result<(), Failure> {
+    if matches.is_present("check") {
+        return check(matches);
+    }
+    if !matches.is_present("each") {
+        return assemble_files(matches, &input_files(matches));
+    }
+
+    // Every input writes its own images, so no output can be named once for all of them.
+    let single = [
+        ("text", "-t"),
+        ("data", "-d"),
+        ("combined", "--combined"),
+        ("listing", "-l"),
+        ("symbols", "--symbols"),
+        ("xref", "--xref"),
+        ("map", "--map"),
+        ("debug-info", "--debug-info"),
+        ("emit-json", "--emit-json"),
+    ];
+    if let Some((_, flag)) = single.iter().find(|(name, _)| matches.is_present(name)) {
+        clap::Error::with_description(
+            &format!(
+                "--each names each input's images after the input and cannot be used with {}",
+                flag
+            ),
+            clap::ErrorKind::ArgumentConflict,
+        )
+        .exit();
+    }
+    each_input(matches, assemble_files)
+}
+
+/// Assembles `input_files` as one program, writing the images and other outputs asked for.
+fn assemble_files(matches: &ArgMatches, input_files: &[&Path]) -> Result<(), Failure> {
+    let input_file = input_files[0];
+    let filename = source_name(input_file);
+
+    let explicit_outputs = matches.is_present("text") && matches.is_present("data");
+    if input_file == Path::new("-") && !matches.is_present("combined") && !explicit_outputs {
+        clap::Error::with_description(
+            "reading from stdin requires explicit -t and -d output paths",
+            clap::ErrorKind::MissingRequiredArgument,
+        )
+        .exit();
+    }
+
+    let mut image_options = image_options(matches);
+    let (text_extension, data_extension) = image_options.format.extensions();
 
     let data_out = if let Some(data) = matches.value_of("data") {
         PathBuf::from(data)
     } else {
         let mut data = input_file.to_path_buf();
-        data.set_extension("dat");
+        data.set_extension(data_extension);
         data
     };
 
@@ -55,41 +1506,284 @@ fn main() -> Result<(), std::io::Error> {
         PathBuf::from(text)
     } else {
         let mut text = input_file.to_path_buf();
-        text.set_extension("mc");
+        text.set_extension(text_extension);
         text
     };
 
-    let input = fs::read_to_string(input_file)?;
+    if matches.is_present("split-text") && text_out == Path::new("-") {
+        clap::Error::with_description(
+            "--split-text writes two files and cannot write the text output to stdout",
+            clap::ErrorKind::ArgumentConflict,
+        )
+        .exit();
+    }
 
-    let mut parser = Parser::parse(&input).unwrap();
+    if data_out == Path::new("-") && text_out == Path::new("-") {
+        clap::Error::with_description(
+            "the text and data outputs cannot both be written to stdout",
+            clap::ErrorKind::ArgumentConflict,
+        )
+        .exit();
+    }
 
-    let addressed = parser.address_program().unwrap();
+    let sources = load_sources(input_files, matches)?;
+    let (parser, addressed) = assemble_program(matches, &sources)?;
+    if matches.is_present("header") || matches.is_present("reproducible") {
+        image_options.header = provenance(matches, input_files, &sources);
+    }
 
-    {
-        let mut data_outfile = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .create(true)
-            .open(&data_out)?;
+    let verbose = matches.is_present("verbose");
+    if verbose {
+        diag!("{}", Stats::new(&addressed));
+    }
+
+    let mut written = Vec::new();
+
+    if let Some(listing_out) = matches.value_of("listing").map(Path::new) {
+        let options = listing::ListingOptions {
+            radix: listing::Radix::from_name(matches.value_of("radix").unwrap()),
+            annotate_bits: matches.is_present("annotate-bits"),
+        };
+        write_listing(listing_out, &parser, &addressed, &options)
+            .map_err(|e| io_failure("write", listing_out, e))?;
+        written.push(listing_out.to_path_buf());
+    }
+
+    if let Some(json_out) = matches.value_of("emit-json").map(Path::new) {
+        write_json(json_out, &addressed).map_err(|e| io_failure("write", json_out, e))?;
+        written.push(json_out.to_path_buf());
+    }
 
-        writeln!(data_outfile, "v2.0 raw")?;
-        for byte in addressed.data_bytes() {
-            writeln!(data_outfile, "{:02x}", byte)?;
+    if let Some(symbols_out) = matches.value_of("symbols").map(Path::new) {
+        write_symbols(symbols_out, &addressed).map_err(|e| io_failure("write", symbols_out, e))?;
+        written.push(symbols_out.to_path_buf());
+    }
+
+    if let Some(map_out) = matches.value_of("map").map(Path::new) {
+        write_map(map_out, &parser, &addressed, &sources)
+            .map_err(|e| io_failure("write", map_out, e))?;
+        written.push(map_out.to_path_buf());
+    }
+
+    if let Some(debug_out) = matches.value_of("debug-info").map(Path::new) {
+        write_debug_info(debug_out, &addressed, &sources)
+            .map_err(|e| io_failure("write", debug_out, e))?;
+        written.push(debug_out.to_path_buf());
+    }
+
+    if let Some(xref_out) = matches.value_of("xref").map(Path::new) {
+        write_xref(
+            xref_out,
+            matches.value_of("xref-format"),
+            &parser,
+            &addressed,
+            &sources,
+        )
+        .map_err(|e| io_failure("write", xref_out, e))?;
+        written.push(xref_out.to_path_buf());
+    }
+
+    if let Some(combined) = matches.value_of("combined").map(PathBuf::from) {
+        // Combined images have always held whole data words.
+        let data_width = image_options.data_width.unwrap_or(CellWidth::Words);
+        let words = match addressed.combined_words(
+            parser.text_base,
+            parser.data_base,
+            data_width,
+            image_options.data_endian,
+        ) {
+            Some(words) => words,
+            None => {
+                diag!(
+                    "{}: error: {} instructions at {:#x} overlap {} data words at {:#x}; choose a different --text-base or --data-base",
+                    filename,
+                    addressed.text.len(),
+                    parser.text_base,
+                    addressed.data.len(),
+                    parser.data_base
+                );
+                return Err(Failure::Overflow);
+            }
+        };
+        let end = (parser.text_base + parser.cpu.text_words())
+            .max(parser.data_base + parser.cpu.data_words() * data_width.cells_per_word());
+        let words = image::pad_words(words, end, &image_options);
+        write_combined(&combined, &words, &image_options)
+            .map_err(|e| io_failure("write", &combined, e))?;
+        written.push(combined);
+    } else {
+        write_data(&data_out, &addressed, &image_options)
+            .map_err(|e| io_failure("write", &data_out, e))?;
+        written.push(data_out);
+        if addressed.text.is_empty() {
+            diag!(
+                "note: no instructions to assemble; not writing `{}`",
+                text_out.display()
+            );
+        } else if matches.is_present("split-text") {
+            let images = addressed.split_text_images(&image_options);
+            for (half, image) in ["hi", "lo"].iter().zip(&images) {
+                let path = split_text_path(&text_out, half);
+                create_output(&path)
+                    .and_then(|mut out| out.write_all(image))
+                    .map_err(|e| io_failure("write", &path, e))?;
+                written.push(path);
+            }
+        } else {
+            write_text(&text_out, &addressed, &image_options)
+                .map_err(|e| io_failure("write", &text_out, e))?;
+            written.push(text_out);
         }
     }
 
+    if verbose {
+        for path in &written {
+            diag!("wrote {}", path.display());
+        }
+    }
+    for path in written
+        .iter()
+        .filter(|path| path.as_path() != Path::new("-"))
     {
-        let mut text_outfile = OpenOptions::new()
-            .read(true)
+        if let Ok(metadata) = fs::metadata(path) {
+            single_address_assembler::log!(
+                Debug,
+                "wrote {} bytes to {}",
+                metadata.len(),
+                path.display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The `--header` comment lines saying where the images came from; `--reproducible` leaves
+/// out the time so the same input always gives the same output.
+fn provenance(matches: &ArgMatches, input_files: &[&Path], sources: &SourceMap) -> Vec<String> {
+    let names: Vec<String> = input_files.iter().map(|path| source_name(path)).collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    // The program by name only, so the header does not depend on where it is installed.
+    let program = env::args()
+        .next()
+        .map(|arg| {
+            Path::new(&arg)
+                .file_name()
+                .map_or(arg.clone(), |name| name.to_string_lossy().into_owned())
+        })
+        .into_iter();
+    let command: Vec<String> = program
+        .chain(env::args().skip(1))
+        .map(|arg| {
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                format!("{:?}", arg)
+            } else {
+                arg
+            }
+        })
+        .collect();
+    let generated = if matches.is_present("reproducible") {
+        None
+    } else {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .ok()
+            .map(|elapsed| elapsed.as_secs())
+    };
+    image::provenance(&names, &sources.text, &command.join(" "), generated)
+}
+
+/// `prog.mc` with `half` inserted before the extension, e.g. `prog.hi.mc`.
+fn split_text_path(path: &Path, half: &str) -> PathBuf {
+    let mut name = path.file_stem().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(half);
+    if let Some(extension) = path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+    path.with_file_name(name)
+}
+
+/// Opens `path` for writing, truncating it, or returns stdout when the path is `-`.
+fn create_output(path: &Path) -> io::Result<Box<dyn Write>> {
+    if path == Path::new("-") {
+        Ok(Box::new(io::stdout()))
+    } else {
+        let file = OpenOptions::new()
             .write(true)
             .create(true)
-            .open(&text_out)?;
+            .truncate(true)
+            .open(path)?;
+        Ok(Box::new(file))
+    }
+}
 
-        writeln!(text_outfile, "v2.0 raw")?;
-        for instr in &addressed.text {
-            writeln!(text_outfile, "{}", instr.hex_string())?;
-        }
+fn write_listing(
+    path: &Path,
+    parser: &Parser,
+    addressed: &AddressedProgram,
+    options: &listing::ListingOptions,
+) -> io::Result<()> {
+    let mut outfile = create_output(path)?;
+    write!(outfile, "{}", listing::listing(parser, addressed, options))
+}
+
+fn write_json(path: &Path, addressed: &AddressedProgram) -> io::Result<()> {
+    let mut outfile = create_output(path)?;
+    write!(outfile, "{}", json::program_json(addressed))
+}
+
+fn write_symbols(path: &Path, addressed: &AddressedProgram) -> io::Result<()> {
+    let mut outfile = create_output(path)?;
+    for symbol in &addressed.symbols {
+        writeln!(outfile, "{}", symbol)?;
     }
 
     Ok(())
 }
+
+fn write_xref(
+    path: &Path,
+    format: Option<&str>,
+    parser: &Parser,
+    addressed: &AddressedProgram,
+    sources: &SourceMap,
+) -> io::Result<()> {
+    let xrefs = xref::cross_reference(parser, addressed);
+    let rendered = match format {
+        Some("json") => xref::render_json(&xrefs, sources),
+        _ => xref::render_text(&xrefs, sources),
+    };
+    create_output(path)?.write_all(rendered.as_bytes())
+}
+
+fn write_map(
+    path: &Path,
+    parser: &Parser,
+    addressed: &AddressedProgram,
+    sources: &SourceMap,
+) -> io::Result<()> {
+    let xrefs = xref::cross_reference(parser, addressed);
+    create_output(path)?.write_all(xref::render_map(&xrefs, addressed, sources).as_bytes())
+}
+
+fn write_debug_info(
+    path: &Path,
+    addressed: &AddressedProgram,
+    sources: &SourceMap,
+) -> io::Result<()> {
+    create_output(path)?.write_all(json::debug_info_json(addressed, sources).as_bytes())
+}
+
+fn write_combined(path: &Path, words: &[u16], options: &ImageOptions) -> io::Result<()> {
+    create_output(path)?.write_all(&image::render(words, 4, options))
+}
+
+fn write_data(path: &Path, addressed: &AddressedProgram, options: &ImageOptions) -> io::Result<()> {
+    create_output(path)?.write_all(&addressed.data_image(options))
+}
+
+fn write_text(path: &Path, addressed: &AddressedProgram, options: &ImageOptions) -> io::Result<()> {
+    create_output(path)?.write_all(&addressed.text_image(options))
+}
@@ -0,0 +1,360 @@
+//! Optional rewrites of the parsed program, run between parsing and addressing under `-O`.
+//!
+//! The passes work on [`Parser::text`], where labels are instruction indices, so removing
+//! or merging instructions only means moving each label to where its instruction ended up.
+//! Every pass must leave the accumulator and memory exactly as the original program would
+//! at each instruction a label points at; only the number of steps taken may change.
+
+use crate::{DataWord, Immediate, Instruction, Parser, Span};
+use std::collections::HashSet;
+use std::convert::TryFrom;
+
+/// A run of consecutive instructions removed as unreachable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EliminatedRange {
+    pub count: usize,
+    /// From the start of the first removed instruction to the end of the last.
+    pub span: Span,
+}
+
+impl<'a> Parser<'a> {
+    /// Runs every pass, returning how many instructions they removed between them. Programs
+    /// that branch to address literals are left untouched, as those targets cannot be moved.
+    pub fn optimize(&mut self) -> usize {
+        let literal_target = self.text.iter().any(|instr| match instr {
+            Instruction::Branch(target, _) | Instruction::BranchZero(target, _) => {
+                target.starts_with('@')
+            }
+            _ => false,
+        });
+        if literal_target {
+            crate::log!(
+                Debug,
+                "not optimizing: the program branches to an address literal"
+            );
+            return 0;
+        }
+        let eliminated = self.eliminate_dead_code();
+        crate::log!(Debug, "dead code: removed {} instructions", eliminated);
+        let folded = self.fold_peephole();
+        crate::log!(Debug, "peephole: removed {} instructions", folded);
+        let reduced = self.reduce_strength();
+        crate::log!(
+            Debug,
+            "strength reduction: rewrote {} instructions",
+            reduced
+        );
+        eliminated + folded
+    }
+
+    /// Removes every instruction that no path from the first instruction, or from a label
+    /// whose address is stored in data, reaches, recording each removed run in
+    /// [`eliminated`](Parser::eliminated). Labels into removed code can only have been used
+    /// by other removed code, so they are removed too.
+    ///
+    /// ```
+    /// use single_address_assembler::{AddressedInstruction::*, Parser};
+    ///
+    /// let source = ".text .label top clac beqz end addi 1 br top
+    ///               .label orphan addi 2 br orphan
+    ///               .label end halt";
+    /// let mut parser = Parser::new(source);
+    /// parser.parse_program().unwrap();
+    /// assert_eq!(parser.optimize(), 2);
+    /// assert_eq!(&source[parser.eliminated[0].span.clone()], "addi 2 br orphan");
+    ///
+    /// let program = parser.address_program().unwrap();
+    /// assert_eq!(program.text, [ClearAc, BranchZero(4), AddImmediate(1), Branch(0), Branch(4)]);
+    /// assert!(program.symbol("orphan").is_none());
+    /// ```
+    fn eliminate_dead_code(&mut self) -> usize {
+        let len = self.text.len();
+        let target = |label: &str| self.text_labels.get(label).map(|(index, _)| *index);
+        let mut live = vec![false; len];
+        let mut pending = vec![0];
+        for word in &self.data {
+            if let DataWord::TextAddress(label, _) = word {
+                pending.extend(target(label));
+            }
+        }
+        while let Some(index) = pending.pop() {
+            if index >= len || live[index] {
+                continue;
+            }
+            live[index] = true;
+            match &self.text[index] {
+                Instruction::Halt => {}
+                Instruction::Branch(label, _) => pending.extend(target(label)),
+                Instruction::BranchZero(label, _) => {
+                    pending.extend(target(label));
+                    pending.push(index + 1);
+                }
+                _ => pending.push(index + 1),
+            }
+        }
+        if live.iter().all(|&live| live) {
+            return 0;
+        }
+
+        let mut moved = Vec::with_capacity(len + 1);
+        let mut kept = 0;
+        for (index, &live) in live.iter().enumerate() {
+            moved.push(live.then_some(kept));
+            if live {
+                kept += 1;
+            } else if index > 0 && moved[index - 1].is_none() {
+                let range = self.eliminated.last_mut().unwrap();
+                range.count += 1;
+                range.span.end = self.text_spans[index].end;
+            } else {
+                self.eliminated.push(EliminatedRange {
+                    count: 1,
+                    span: self.text_spans[index].clone(),
+                });
+            }
+        }
+        moved.push(Some(kept));
+
+        self.text_labels
+            .retain(|_, (index, _)| moved[*index].is_some());
+        for (index, _) in self.text_labels.values_mut() {
+            *index = moved[*index].unwrap();
+        }
+        let text = std::mem::take(&mut self.text);
+        let spans = std::mem::take(&mut self.text_spans);
+        let (text, spans) = text
+            .into_iter()
+            .zip(spans)
+            .zip(&live)
+            .filter_map(|(pair, &live)| live.then_some(pair))
+            .unzip();
+        self.text = text;
+        self.text_spans = spans;
+        len - kept
+    }
+
+    /// Runs the peephole pass, returning how many instructions it removed.
+    ///
+    /// It drops instructions that cannot change the accumulator (`noop`, `addi 0`, `subi 0`,
+    /// `muli 1`, `divi 1`, `andi -1`, and `shift 0`) and folds an immediate `addi`/`subi`,
+    /// `muli`, `andi`, or same-direction shift into the one before it when the combined
+    /// operand still fits. Nothing is folded into an instruction across a label, since a
+    /// branch to the label must still see the second instruction alone.
+    ///
+    /// ```
+    /// use single_address_assembler::emulator::Machine;
+    /// use single_address_assembler::{AddressedInstruction::*, Parser};
+    ///
+    /// let source = ".data .label x .number 9
+    ///               .text clac noop addi 3 subi 3 add x addi 0 .label again addi 2 addi 1 halt";
+    /// let mut parser = Parser::new(source);
+    /// parser.parse_program().unwrap();
+    /// assert_eq!(parser.optimize(), 5);
+    /// let program = parser.address_program().unwrap();
+    /// assert_eq!(program.text, [ClearAc, Add(0), AddImmediate(3), Branch(3)]);
+    /// assert_eq!(program.symbol("again").unwrap().address, 2);
+    ///
+    /// let mut machine = Machine::new(&program);
+    /// machine.run(100).unwrap();
+    /// assert_eq!(machine.ac, 12);
+    /// ```
+    fn fold_peephole(&mut self) -> usize {
+        let before = self.text.len();
+        let labeled: HashSet<usize> = self.text_labels.values().map(|(index, _)| *index).collect();
+        let text = std::mem::take(&mut self.text);
+        let spans = std::mem::take(&mut self.text_spans);
+
+        // `moved[i]` is where the instruction at old index `i` (or the one after it, if it was
+        // removed) now sits; labels at the position in `pinned` block folding there.
+        let mut moved = Vec::with_capacity(before + 1);
+        let mut pinned = HashSet::new();
+        let mut out: Vec<(Instruction<'a>, Span)> = Vec::with_capacity(before);
+        for (index, (instr, span)) in text.into_iter().zip(spans).enumerate() {
+            moved.push(out.len());
+            if labeled.contains(&index) {
+                pinned.insert(out.len());
+            }
+            if is_identity(&instr) {
+                continue;
+            }
+            let previous = match out.last() {
+                Some(_) if pinned.contains(&out.len()) => None,
+                Some((last, _)) => Some(last),
+                None => None,
+            };
+            match previous.map(|last| fold(last, &instr)) {
+                Some(Some(folded)) => {
+                    let (_, last_span) = out.pop().unwrap();
+                    if let Some(folded) = folded.filter(|folded| !is_identity(folded)) {
+                        out.push((folded, last_span.start..span.end));
+                    }
+                }
+                _ => out.push((instr, span)),
+            }
+        }
+        moved.push(out.len());
+
+        for (index, _) in self.text_labels.values_mut() {
+            *index = moved[*index];
+        }
+        let (text, spans) = out.into_iter().unzip();
+        self.text = text;
+        self.text_spans = spans;
+        before - self.text.len()
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// Replaces immediate multiplies, divides, and remainders by powers of two with shifts
+    /// and masks, returning how many instructions it rewrote. Each rewrite is only made
+    /// where it gives the same result for every accumulator value it can see:
+    ///
+    /// - `muli 2^k` becomes `shift k` always, since wrapping multiplication by a power of
+    ///   two is exactly a left shift, sign included.
+    /// - `divi 2^k` becomes `shift -k` only when the accumulator is known not to be negative.
+    ///   Division truncates toward zero while an arithmetic shift rounds down, so `-5 / 4`
+    ///   is `-1` but `-5 >> 2` is `-2`.
+    /// - `remi 2^k` becomes `andi 2^k-1` (every such mask fits in an immediate) only when
+    ///   the accumulator is known not to be negative, since the remainder takes the sign of
+    ///   the dividend: `-5 % 4` is `-1` but `-5 & 3` is `3`.
+    ///
+    /// The accumulator is known not to be negative after `clac` or an `andi` with a
+    /// non-negative mask, for as long as the instructions that follow without a label in
+    /// between keep it that way. Rewrites the selected CPU has no `shift` or `andi` for
+    /// are skipped.
+    ///
+    /// ```
+    /// use single_address_assembler::emulator::Machine;
+    /// use single_address_assembler::{AddressedInstruction::*, Parser};
+    ///
+    /// let source = ".data .label x .number 0
+    ///               .text clac add x muli 8 divi 4 remi 4 andi 0x7f divi 4 remi 8 halt";
+    /// let mut parser = Parser::new(source);
+    /// parser.parse_program().unwrap();
+    /// parser.optimize();
+    /// let program = parser.address_program().unwrap();
+    /// assert_eq!(
+    ///     program.text,
+    ///     [ClearAc, Add(0), Shift(3), DivideImmediate(4), RemainderImmediate(4),
+    ///      AndImmediate(0x7f), Shift(-2), AndImmediate(7), Branch(8)]
+    /// );
+    ///
+    /// // Every value of `x` gives the same result as the unoptimized program.
+    /// let mut plain = Parser::new(source);
+    /// plain.parse_program().unwrap();
+    /// let plain = plain.address_program().unwrap();
+    /// for x in (i16::MIN..=i16::MAX).step_by(97) {
+    ///     let run = |program| {
+    ///         let mut machine = Machine::new(program);
+    ///         machine.data[0] = x;
+    ///         machine.run(100).unwrap();
+    ///         machine.ac
+    ///     };
+    ///     assert_eq!(run(&program), run(&plain));
+    /// }
+    /// ```
+    fn reduce_strength(&mut self) -> usize {
+        let labeled: HashSet<usize> = self.text_labels.values().map(|(index, _)| *index).collect();
+        let (has_shift, has_andi) = (self.cpu.has("shift"), self.cpu.has("andi"));
+        let mut rewritten = 0;
+        let mut non_negative = false;
+        for (index, instr) in self.text.iter_mut().enumerate() {
+            if labeled.contains(&index) {
+                non_negative = false;
+            }
+            let reduced = match *instr {
+                Instruction::MultiplyImmediate(i) if has_shift => log2(i).map(Instruction::Shift),
+                Instruction::DivideImmediate(i) if has_shift && non_negative => {
+                    log2(i).map(|k| Instruction::Shift(-k))
+                }
+                Instruction::RemainderImmediate(i) if has_andi && non_negative => {
+                    log2(i).map(|_| Instruction::AndImmediate(i - 1))
+                }
+                _ => None,
+            };
+            if let Some(reduced) = reduced {
+                *instr = reduced;
+                rewritten += 1;
+            }
+            non_negative = match *instr {
+                Instruction::ClearAc => true,
+                Instruction::AndImmediate(mask) => mask >= 0 || non_negative,
+                Instruction::Shift(amount) => amount < 0 && non_negative,
+                Instruction::DivideImmediate(i) | Instruction::RemainderImmediate(i) => {
+                    i > 0 && non_negative
+                }
+                Instruction::Store(..)
+                | Instruction::StoreIndirect(..)
+                | Instruction::NoOp
+                | Instruction::BranchZero(..) => non_negative,
+                _ => false,
+            };
+        }
+        rewritten
+    }
+}
+
+/// `k` when `i` is `2^k` for some `k` of at least one.
+fn log2(i: Immediate) -> Option<Immediate> {
+    if i > 1 && i.count_ones() == 1 {
+        Some(i.trailing_zeros() as Immediate)
+    } else {
+        None
+    }
+}
+
+/// Whether `instr` leaves the accumulator and memory as they were.
+fn is_identity(instr: &Instruction) -> bool {
+    matches!(
+        instr,
+        Instruction::NoOp
+            | Instruction::AddImmediate(0)
+            | Instruction::SubtractImmediate(0)
+            | Instruction::MultiplyImmediate(1)
+            | Instruction::DivideImmediate(1)
+            | Instruction::AndImmediate(-1)
+            | Instruction::Shift(0)
+    )
+}
+
+/// The single instruction equivalent to `first` followed by `second`: `Some(None)` when the
+/// pair adds up to nothing, `None` when they cannot be combined.
+fn fold<'a>(first: &Instruction<'a>, second: &Instruction<'a>) -> Option<Option<Instruction<'a>>> {
+    use Instruction::*;
+
+    // Addition wraps at 16 bits, so any two offsets combine; only the operand must fit.
+    let offset = |instr: &Instruction| match instr {
+        AddImmediate(i) => Some(i16::from(*i)),
+        SubtractImmediate(i) => Some(-i16::from(*i)),
+        _ => None,
+    };
+    if let (Some(a), Some(b)) = (offset(first), offset(second)) {
+        let sum = a + b;
+        return if sum == 0 {
+            Some(None)
+        } else if let Ok(i) = Immediate::try_from(sum) {
+            Some(Some(AddImmediate(i)))
+        } else {
+            Immediate::try_from(-sum)
+                .ok()
+                .map(|i| Some(SubtractImmediate(i)))
+        };
+    }
+
+    match (first, second) {
+        // Wrapping multiplication is associative, so `x * a * b` is `x * (a * b)`.
+        (MultiplyImmediate(a), MultiplyImmediate(b)) => {
+            Immediate::try_from(i16::from(*a) * i16::from(*b))
+                .ok()
+                .map(|i| Some(MultiplyImmediate(i)))
+        }
+        (AndImmediate(a), AndImmediate(b)) => Some(Some(AndImmediate(a & b))),
+        // Shifts in one direction add up while the total stays within a word.
+        (Shift(a), Shift(b))
+            if (*a > 0) == (*b > 0) && (i16::from(*a) + i16::from(*b)).abs() <= 15 =>
+        {
+            Some(Some(Shift(a + b)))
+        }
+        _ => None,
+    }
+}
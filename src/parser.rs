@@ -1,40 +1,876 @@
 use logos::{Lexer, Logos, Span};
 
-use super::{AddressedInstruction, Immediate, Instruction, Token};
-use std::collections::HashMap;
+use super::cpu::CpuSpec;
+use super::diagnostic::{Diagnostic, Severity};
+use super::image::{self, Annotations, CellWidth, Endian, ImageOptions, OutputFormat};
+use super::optimize::EliminatedRange;
+use super::{Address, AddressedInstruction, EncodeError, Immediate, Instruction, Token, MNEMONICS};
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::fmt;
+use std::path::PathBuf;
+
+mod expr;
+mod label_style;
+mod lint;
+mod options;
+
+pub(crate) use expr::MAX_DEPTH;
+pub use label_style::LabelStyle;
+pub use lint::{Lint, LintLevel, LINTS};
+pub use options::ParseOptions;
+
+/// Number of instructions that fit in text memory, unless the CPU spec says otherwise.
+///
+/// ```
+/// use single_address_assembler::{ParseError, Parser, MAX_TEXT};
+///
+/// let text = |n: usize| {
+///     Parser::parse(&format!(".text {}", "noop ".repeat(n))).map(|parser| parser.text.len())
+/// };
+/// assert_eq!(text(MAX_TEXT).unwrap(), 256);
+/// let err = text(MAX_TEXT + 1).unwrap_err();
+/// assert!(matches!(err, ParseError::InstructionOverflow(_, 256, _)));
+/// assert_eq!(err.to_string(), "too many instructions: `noop` exceeds the 256-word text limit");
+/// ```
+pub const MAX_TEXT: usize = 256;
+/// Number of words that fit in data memory, unless the CPU spec says otherwise.
+///
+/// ```
+/// use single_address_assembler::{ParseError, Parser, MAX_DATA};
+///
+/// let data = |n: usize| {
+///     let source = format!(".data .label xs {} .text add xs halt", "7 ".repeat(n));
+///     Parser::parse(&source).map(|parser| parser.data.len())
+/// };
+/// assert_eq!(data(MAX_DATA).unwrap(), 256);
+/// let err = data(MAX_DATA + 1).unwrap_err();
+/// assert!(matches!(err, ParseError::DataOverflow(_, 256, _)));
+/// assert_eq!(err.to_string(), "too much data: `7` exceeds the 256-word data limit");
+/// ```
+pub const MAX_DATA: usize = 256;
 
 #[derive(Debug, Clone)]
 pub enum ParseError {
     InvalidToken(String, String, Span),
     UnexpectedEof(String),
     DuplicateLabel(String, Span, Span),
-    InstructionOverflow(String, Span),
-    DataOverflow(String, Span),
-    InvalidNumber(i16, Span),
-    UnknownLabel(String),
+    /// An instruction past the end of text memory, with the number of words it holds.
+    InstructionOverflow(String, usize, Span),
+    /// A data word past the end of data memory, with the number of words it holds.
+    DataOverflow(String, usize, Span),
+    ImmediateOutOfRange(String, String, Span),
+    /// A label that is not defined, with a suggestion if one is close. The span is the
+    /// operand naming it, not the instruction:
+    ///
+    /// ```
+    /// use single_address_assembler::{assemble, ParseError};
+    ///
+    /// for operand in ["add nope", "sub nope", "and nope", "stor nope", "beqz nope", "br nope"] {
+    ///     let source = format!(".data .label x 1 .text .label top add x {} br top", operand);
+    ///     let at = source.find("nope").unwrap();
+    ///     match assemble(&source) {
+    ///         Err(ParseError::UnknownLabel(label, _, span)) => {
+    ///             assert_eq!((&*label, span), ("nope", at..at + 4), "{}", operand)
+    ///         }
+    ///         other => panic!("unexpected result for `{}`: {:?}", operand, other),
+    ///     }
+    /// }
+    /// ```
+    UnknownLabel(String, Option<String>, Span),
+    /// A text label used as a data operand or the other way around, with the section it is
+    /// in. The span is the operand naming it:
+    ///
+    /// ```
+    /// use single_address_assembler::{assemble, ParseError, Section};
+    ///
+    /// let cases = [
+    ///     ("add top", "top", Section::Text),
+    ///     ("stor top", "top", Section::Text),
+    ///     ("beqz x", "x", Section::Data),
+    ///     ("br x", "x", Section::Data),
+    /// ];
+    /// for (operand, name, section) in cases {
+    ///     let source = format!(".data .label x 1 .text .label top add x {} br top", operand);
+    ///     let at = source.rfind(operand).unwrap() + operand.find(name).unwrap();
+    ///     match assemble(&source) {
+    ///         Err(ParseError::WrongLabelKind(label, found, span)) => {
+    ///             assert_eq!((&*label, found, span), (name, section, at..at + name.len()))
+    ///         }
+    ///         other => panic!("unexpected result for `{}`: {:?}", operand, other),
+    ///     }
+    /// }
+    /// ```
+    WrongLabelKind(String, Section, Span),
+    DivisionByZero(String, Span),
+    EmptyInput,
+    AddressOutOfRange(String, Section, usize, usize, Span),
+    AddressLiteralOutOfRange(String, Span),
+    /// A constant defined again with a different value; the first span is `None` when it
+    /// came from `--define`.
+    DuplicateConstant(String, Option<Span>, Span),
+    /// A mnemonic the selected CPU variant does not implement.
+    UnsupportedMnemonic(String, Span),
+    /// A bracketed operand for a mnemonic with no indirect form on the selected CPU.
+    UnsupportedIndirect(String, Span),
+    /// A bracketed operand on a branch, which no CPU variant supports.
+    IndirectBranch(String, Span),
+    /// A `rep` that would take the data past its limit, with the size it would have reached
+    /// and the limit.
+    RepeatOverflow(String, usize, usize, Span),
+    /// A branch to a text label past `0xff` on a CPU without a `prefix` opcode to reach it,
+    /// with the label's address.
+    BranchOutOfReach(String, usize, Span),
+    /// A constant expression with no 16-bit value, and why.
+    InvalidConstant(String, String, Span),
+    /// An `.incbin` or `.csv` whose file could not be embedded, with the path as written
+    /// and why.
+    ///
+    /// ```
+    /// use single_address_assembler::Parser;
+    ///
+    /// let dir = std::env::temp_dir();
+    /// std::fs::write(dir.join("csv-doc.csv"), "1\n0x10\n\n-3\nten\n").unwrap();
+    /// let mut parser = Parser::new(".data .label v .csv \"csv-doc.csv\" .text halt");
+    /// parser.file_dirs = vec![dir.clone()];
+    /// let err = parser.parse_program().unwrap_err();
+    /// assert_eq!(err.to_string(), "could not include \"csv-doc.csv\": row 5: invalid token `ten`: expected an integer");
+    ///
+    /// std::fs::write(dir.join("csv-doc.csv"), "1, 0x10, -3\n").unwrap();
+    /// let mut parser = Parser::new(".data .label v .csv \"csv-doc.csv\" .text halt");
+    /// parser.file_dirs = vec![dir];
+    /// parser.parse_program().unwrap();
+    /// assert_eq!(parser.address_program().unwrap().data, [1, 16, -3]);
+    /// ```
+    IncludeFailed(String, String, Span),
+    /// A warning that [`ParseOptions::strict`] turned into an error.
+    DeniedWarning(Warning),
+    /// An alias spelled like an existing mnemonic or directive, with the mnemonic it was to
+    /// stand for.
+    AliasShadows(String, String, Span),
+    /// An alias defined twice; the first span is `None` when it came from `--alias`.
+    DuplicateAlias(String, Option<Span>, Span),
+    /// A code in a `#![allow(..)]`-style directive that is not in [`LINTS`].
+    UnknownLint(String, Span),
+}
+
+impl ParseError {
+    /// The location in the input the error points at, if it has one.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Self::InvalidToken(_, _, span)
+            | Self::DuplicateLabel(_, _, span)
+            | Self::InstructionOverflow(_, _, span)
+            | Self::DataOverflow(_, _, span)
+            | Self::RepeatOverflow(_, _, _, span)
+            | Self::BranchOutOfReach(_, _, span)
+            | Self::InvalidConstant(_, _, span)
+            | Self::IncludeFailed(_, _, span)
+            | Self::ImmediateOutOfRange(_, _, span)
+            | Self::UnknownLabel(_, _, span)
+            | Self::WrongLabelKind(_, _, span)
+            | Self::DivisionByZero(_, span)
+            | Self::AddressOutOfRange(_, _, _, _, span)
+            | Self::DuplicateConstant(_, _, span)
+            | Self::UnsupportedMnemonic(_, span)
+            | Self::UnsupportedIndirect(_, span)
+            | Self::IndirectBranch(_, span)
+            | Self::AliasShadows(_, _, span)
+            | Self::DuplicateAlias(_, _, span)
+            | Self::UnknownLint(_, span)
+            | Self::AddressLiteralOutOfRange(_, span) => Some(span.clone()),
+            Self::DeniedWarning(warning) => warning.span(),
+            Self::UnexpectedEof(_) | Self::EmptyInput => None,
+        }
+    }
+
+    /// A secondary location relevant to the error, with a short note describing it.
+    pub fn note(&self) -> Option<(&'static str, Span)> {
+        match self {
+            Self::DuplicateLabel(_, first, _)
+            | Self::DuplicateConstant(_, Some(first), _)
+            | Self::DuplicateAlias(_, Some(first), _) => {
+                Some(("first defined here", first.clone()))
+            }
+            Self::DeniedWarning(warning) => warning.note(),
+            _ => None,
+        }
+    }
+
+    /// An owned, renderable copy of the error with excerpts of `source`, the text it was
+    /// parsed from, so it can be shown after the source is gone.
+    ///
+    /// ```
+    /// use single_address_assembler::assemble;
+    ///
+    /// let source = String::from(".text\n.label top clac\n.label top halt");
+    /// let diagnostic = assemble(&source).unwrap_err().with_source(&source);
+    /// drop(source);
+    /// assert_eq!(diagnostic.note.as_ref().unwrap().excerpt.as_ref().unwrap().line, 2);
+    /// assert_eq!(
+    ///     diagnostic.render("prog.s", false),
+    ///     "prog.s:3:8: error: label 'top' is already defined\n    .label top halt\n           ^^^\n\
+    ///      prog.s:2:8: note: first defined here\n    .label top clac\n           ^^^"
+    /// );
+    /// ```
+    pub fn with_source(&self, source: &str) -> Diagnostic {
+        let diagnostic = Diagnostic::new(Severity::Error, self, source, self.span());
+        match self.note() {
+            Some((note, span)) => diagnostic.with_note(note, source, span),
+            None => diagnostic,
+        }
+    }
+}
+
+/// A data word as parsed: a value, or the address of a label, which is only known once the
+/// program is addressed.
+///
+/// ```
+/// use single_address_assembler::{assemble, ParseError};
+///
+/// let program = assemble(
+///     ".data .label table @even @odd .label buf 0 0 .label ptr .number &buf
+///      .text .label even halt .label odd halt",
+/// )
+/// .unwrap();
+/// assert_eq!(program.data, vec![0, 1, 0, 0, 2]);
+///
+/// match assemble(".data .label p &nowhere .text halt") {
+///     Err(ParseError::UnknownLabel(label, _, span)) => assert_eq!((&*label, span), ("nowhere", 16..23)),
+///     other => panic!("unexpected result: {:?}", other),
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataWord<'a> {
+    Value(i16),
+    /// `&label`, the address of a data label.
+    DataAddress(&'a str, Span),
+    /// `@label`, the address of a text label.
+    TextAddress(&'a str, Span),
+}
+
+impl DataWord<'_> {
+    /// The label this word holds the address of, with its span.
+    pub fn label(&self) -> Option<(&str, &Span)> {
+        match self {
+            Self::Value(_) => None,
+            Self::DataAddress(label, span) | Self::TextAddress(label, span) => Some((label, span)),
+        }
+    }
+}
+
+impl fmt::Display for DataWord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Value(value) => write!(f, "{}", value),
+            Self::DataAddress(label, _) => write!(f, "&{}", label),
+            Self::TextAddress(label, _) => write!(f, "@{}", label),
+        }
+    }
+}
+
+/// The section a label was defined in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Section {
+    Text,
+    Data,
+}
+
+impl Section {
+    /// Mnemonics whose operand must be a label from this section.
+    pub fn mnemonics(self) -> &'static str {
+        match self {
+            Self::Text => "beqz/br",
+            Self::Data => "add/sub/mul/div/rem/and/stor",
+        }
+    }
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Text => write!(f, "text"),
+            Self::Data => write!(f, "data"),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidToken(token, expected, _) => {
+                write!(f, "invalid token `{}`: {}", token, expected)
+            }
+            Self::UnexpectedEof(expected) => write!(f, "unexpected end of input: {}", expected),
+            Self::EmptyInput => write!(f, "input contains no instructions"),
+            Self::AddressOutOfRange(label, section, address, base, _) => write!(
+                f,
+                "'{}' resolves to address {:#x} with a {} base of {:#x}, which does not fit in an 8-bit operand",
+                label, address, section, base
+            ),
+            Self::DuplicateLabel(label, _, _) => write!(f, "label '{}' is already defined", label),
+            Self::AddressLiteralOutOfRange(literal, _) => write!(
+                f,
+                "address `{}` does not fit in an 8-bit operand",
+                literal
+            ),
+            Self::DuplicateConstant(name, None, _) => write!(
+                f,
+                "constant '{}' is already defined with a different value on the command line with --define",
+                name
+            ),
+            Self::DuplicateConstant(name, Some(_), _) => {
+                write!(f, "constant '{}' is already defined with a different value", name)
+            }
+            Self::InstructionOverflow(instr, limit, _) => write!(
+                f,
+                "too many instructions: `{}` exceeds the {}-word text limit",
+                instr, limit
+            ),
+            Self::DataOverflow(data, limit, _) => write!(
+                f,
+                "too much data: `{}` exceeds the {}-word data limit",
+                data, limit
+            ),
+            // `halt` is a branch to itself; no label can be named after a mnemonic.
+            Self::BranchOutOfReach(label, address, _) if label == "halt" => write!(
+                f,
+                "`halt` at text address {:#x} branches to itself, past the 8-bit branch operand; branches beyond 0xff need a `prefix` opcode in the CPU spec",
+                address
+            ),
+            Self::BranchOutOfReach(label, address, _) => write!(
+                f,
+                "'{}' is at text address {:#x}, past the 8-bit branch operand; branches beyond 0xff need a `prefix` opcode in the CPU spec",
+                label, address
+            ),
+            Self::InvalidConstant(expr, reason, _) => write!(f, "`{}` {}", expr, reason),
+            Self::IncludeFailed(path, reason, _) => {
+                write!(f, "could not include \"{}\": {}", path, reason)
+            }
+            Self::RepeatOverflow(data, total, limit, _) => write!(
+                f,
+                "too much data: `{}` would bring the data to {} words, past the {}-word data limit",
+                data, total, limit
+            ),
+            Self::ImmediateOutOfRange(mnemonic, literal, _) if mnemonic == "shl" || mnemonic == "shr" => {
+                write!(
+                    f,
+                    "shift amount {} is out of range for `{}`; expected a value in 0..=15",
+                    literal, mnemonic
+                )
+            }
+            Self::ImmediateOutOfRange(mnemonic, literal, _) => {
+                write!(
+                    f,
+                    "immediate {} is out of range for `{}`; expected a value in {}..={}",
+                    literal,
+                    mnemonic,
+                    Immediate::MIN,
+                    Immediate::MAX
+                )?;
+                if mnemonic == "shift" {
+                    write!(f, " (only shift amounts in -15..=15 are meaningful)")?;
+                }
+                Ok(())
+            }
+            Self::UnknownLabel(label, None, _) => write!(f, "unknown label '{}'", label),
+            Self::UnknownLabel(label, Some(hint), _) => {
+                write!(f, "unknown label '{}'; {}", label, hint)
+            }
+            Self::WrongLabelKind(label, section, _) => write!(
+                f,
+                "'{}' is a {} label and can only be used by {}",
+                label,
+                section,
+                section.mnemonics()
+            ),
+            Self::DivisionByZero(mnemonic, _) => write!(
+                f,
+                "`{} 0` always divides by zero; use --allow-div-zero to assemble it anyway",
+                mnemonic
+            ),
+            Self::UnsupportedMnemonic(mnemonic, _) => {
+                write!(f, "the selected CPU has no `{}` instruction", mnemonic)
+            }
+            Self::UnsupportedIndirect(mnemonic, _) => write!(
+                f,
+                "the selected CPU has no indirect `{} [...]`; its CPU spec needs an encoding for `{}_indirect`",
+                mnemonic, mnemonic
+            ),
+            Self::IndirectBranch(mnemonic, _) => write!(
+                f,
+                "`{}` cannot branch indirectly; its operand must be a label or address",
+                mnemonic
+            ),
+            Self::DeniedWarning(warning) => write!(f, "{} [{}]", warning, warning.code()),
+            Self::AliasShadows(alias, mnemonic, _) => write!(
+                f,
+                "cannot make `{}` an alias for `{}`: `{}` is already a {}",
+                alias,
+                mnemonic,
+                alias,
+                if alias.starts_with('.') {
+                    "directive"
+                } else {
+                    "mnemonic"
+                }
+            ),
+            Self::DuplicateAlias(alias, None, _) => write!(
+                f,
+                "alias '{}' is already defined on the command line with --alias",
+                alias
+            ),
+            Self::DuplicateAlias(alias, Some(_), _) => {
+                write!(f, "alias '{}' is already defined", alias)
+            }
+            Self::UnknownLint(code, _) => write!(f, "unknown warning code `{}`", code),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Warning {
+    UnusedLabel(String, Section, Span),
+    UnreachableCode(usize, Span),
+    ShadowedLabel(String, Span, Span),
+    MissingHalt(Span),
+    NoInstructions,
+    ImplicitText(Span),
+    /// A label that does not match [`ParseOptions::label_style`], with that style.
+    MisnamedLabel(String, Section, String, Span),
+    /// A `.equ` constant named like a label, with the label's section and span.
+    ConstantShadowsLabel(String, Section, Span, Span),
+    UnusedConstant(String, Span),
+}
+
+impl Warning {
+    /// Stable short code identifying the kind of warning.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::UnusedLabel(..) => "W001",
+            Self::UnreachableCode(..) => "W002",
+            Self::ShadowedLabel(..) => "W003",
+            Self::MissingHalt(..) => "W004",
+            Self::NoInstructions => "W005",
+            Self::ImplicitText(..) => "W006",
+            Self::MisnamedLabel(..) => "W007",
+            Self::ConstantShadowsLabel(..) => "W008",
+            Self::UnusedConstant(..) => "W009",
+        }
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Self::UnusedLabel(_, _, span)
+            | Self::UnreachableCode(_, span)
+            | Self::ShadowedLabel(_, _, span)
+            | Self::MissingHalt(span)
+            | Self::ImplicitText(span)
+            | Self::MisnamedLabel(_, _, _, span)
+            | Self::ConstantShadowsLabel(_, _, _, span)
+            | Self::UnusedConstant(_, span) => Some(span.clone()),
+            Self::NoInstructions => None,
+        }
+    }
+
+    /// A secondary location relevant to the warning, with a short note describing it.
+    pub fn note(&self) -> Option<(&'static str, Span)> {
+        match self {
+            Self::ShadowedLabel(_, text_span, _) => {
+                Some(("text label defined here", text_span.clone()))
+            }
+            Self::ConstantShadowsLabel(_, _, label_span, _) => {
+                Some(("label defined here", label_span.clone()))
+            }
+            _ => None,
+        }
+    }
+
+    /// An owned, renderable copy of the warning with excerpts of `source`; see
+    /// [`ParseError::with_source`].
+    pub fn with_source(&self, source: &str) -> Diagnostic {
+        let diagnostic = Diagnostic::new(Severity::Warning, self, source, self.span());
+        match self.note() {
+            Some((note, span)) => diagnostic.with_note(note, source, span),
+            None => diagnostic,
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnusedLabel(label, section, _) => {
+                write!(f, "{} label '{}' is never used", section, label)
+            }
+            Self::ShadowedLabel(label, _, _) => write!(
+                f,
+                "'{}' is defined as both a text label and a data label",
+                label
+            ),
+            Self::UnreachableCode(index, _) => write!(
+                f,
+                "instruction {} is unreachable; it follows an unconditional `br` with no label in between",
+                index
+            ),
+            Self::MissingHalt(_) => write!(
+                f,
+                "execution can run past the last instruction; end the program with `halt` or an explicit `br` loop"
+            ),
+            Self::NoInstructions => write!(
+                f,
+                "input defines data but no instructions"
+            ),
+            Self::ImplicitText(_) => write!(
+                f,
+                "code before any section directive is assembled as if it followed `.text`"
+            ),
+            Self::ConstantShadowsLabel(name, section, _, _) => write!(
+                f,
+                "constant '{}' has the same name as a {} label, so operands naming it are ambiguous",
+                name, section
+            ),
+            Self::UnusedConstant(name, _) => write!(f, "constant '{}' is never used", name),
+            Self::MisnamedLabel(label, section, style, _) => write!(
+                f,
+                "{} label '{}' does not match the label style `{}`",
+                section, label, style
+            ),
+        }
+    }
+}
+
+/// The spelling of `mnemonic`, one of the [`MNEMONICS`].
+fn mnemonic_name(mnemonic: Token) -> &'static str {
+    let name = mnemonic.to_string();
+    MNEMONICS.iter().copied().find(|&m| m == name).unwrap()
+}
+
+/// The address named by an `@N` or `@0xN` operand, or `None` if `operand` is a label.
+fn address_literal(operand: &str) -> Option<usize> {
+    let digits = operand.strip_prefix('@')?;
+    match digits.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => digits.parse().ok(),
+    }
+}
+
+/// Finds the label in `labels` closest to `label`, if any is within a couple of edits.
+fn closest_label<'a, V>(label: &str, labels: &HashMap<&'a str, V>) -> Option<&'a str> {
+    closest_match(label, labels.keys().copied())
+}
+
+pub(crate) fn closest_match<'a>(
+    word: &str,
+    candidates: impl Iterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let max_distance = if word.len() <= 3 { 1 } else { 2 };
+    candidates
+        .map(|candidate| (strsim::levenshtein(word, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// The value of one `.csv` cell, parsed like a `.number` operand without constants, or why
+/// it has none.
+fn parse_cell(cell: &str) -> Result<i16, String> {
+    let mut parser = Parser::new(cell);
+    let (value, _) = parser.parse_value().map_err(|err| match err {
+        // Cells cannot name constants, so do not suggest one.
+        ParseError::InvalidToken(token, _, span) => {
+            ParseError::InvalidToken(token, "expected an integer".to_owned(), span).to_string()
+        }
+        ParseError::UnexpectedEof(_) => {
+            ParseError::UnexpectedEof("expected an integer".to_owned()).to_string()
+        }
+        err => err.to_string(),
+    })?;
+    match parser.next_token_opt() {
+        None => Ok(value),
+        Some(_) => Err(format!(
+            "`{}` has `{}` after its value",
+            cell,
+            parser.lexer.slice()
+        )),
+    }
+}
+
+/// Whether parsing cannot usefully go on after `err`: memory is full, so everything after
+/// would fail the same way, or the input has run out.
+fn is_fatal(err: &ParseError) -> bool {
+    matches!(
+        err,
+        ParseError::InstructionOverflow(..)
+            | ParseError::DataOverflow(..)
+            | ParseError::RepeatOverflow(..)
+            | ParseError::UnexpectedEof(_)
+            | ParseError::EmptyInput
+    )
 }
 
 #[derive(Debug, Clone)]
 pub struct AddressedProgram {
     pub text: Vec<AddressedInstruction>,
     pub data: Vec<i16>,
+    /// Every label with its resolved address, sorted by address.
+    pub symbols: Vec<Symbol>,
+    /// Source span of each instruction in `text`; a pseudo-instruction's expansion all maps
+    /// to the pseudo-instruction. Empty for programs not assembled from source.
+    pub text_spans: Vec<Span>,
+    /// Source span of the literal for each word in `data`. Empty for programs not
+    /// assembled from source.
+    pub data_spans: Vec<Span>,
+    /// Address of the first instruction in `text`.
+    pub text_base: usize,
+    /// Address of the first word in `data`.
+    pub data_base: usize,
+    /// Offset in `data` of each word holding a text address, from `@label` or `.jumptable`,
+    /// so tools can show it as a label rather than a number.
+    pub code_addresses: Vec<usize>,
+    /// Offset in `text` of each word the assembler wrote for something else: the `prefix` of
+    /// a long branch, or the branch a `halt` becomes, however it was spelled. Empty for
+    /// programs not assembled from source.
+    pub synthesized: Vec<usize>,
+    /// The CPU variant the program was assembled for, which decides how it is encoded.
+    pub cpu: CpuSpec,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub section: Section,
+    pub address: usize,
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} = {:#04x}  [{}]",
+            self.name, self.address, self.section
+        )
+    }
+}
+
+/// An assembled program bundled with the source it came from and the warnings raised while
+/// assembling it, borrowing nothing; returned by [`Parser::parse_owned`].
+#[derive(Debug, Clone)]
+pub struct OwnedProgram {
+    pub source: String,
+    pub program: AddressedProgram,
+    pub warnings: Vec<Warning>,
 }
 
 impl AddressedProgram {
+    /// The symbol named `name`, if the program defines one.
+    pub fn symbol(&self, name: &str) -> Option<&Symbol> {
+        self.symbols.iter().find(|symbol| symbol.name == name)
+    }
+
+    /// The first symbol at `address` in `section`, if any label names that address.
+    ///
+    /// ```
+    /// use single_address_assembler::{assemble, Section};
+    ///
+    /// let program = assemble(".data .label x 1 .label y 2 .text halt").unwrap();
+    /// assert_eq!(program.symbol_at(Section::Data, 1).unwrap().name, "y");
+    /// assert!(program.symbol_at(Section::Text, 1).is_none());
+    /// ```
+    pub fn symbol_at(&self, section: Section, address: usize) -> Option<&Symbol> {
+        self.symbols
+            .iter()
+            .find(|symbol| symbol.section == section && symbol.address == address)
+    }
+
+    /// Each instruction as the 16-bit word stored in text memory.
+    pub fn text_words(&self) -> Vec<u16> {
+        self.text.iter().map(|instr| self.cpu.word(instr)).collect()
+    }
+
+    /// The text memory image in the given format, exactly as written to disk, with each
+    /// instruction split into cells per [`ImageOptions::text_width`]. Padding fills the image
+    /// out to the CPU's text size.
+    ///
+    /// ```
+    /// use single_address_assembler::assemble;
+    /// use single_address_assembler::image::{CellWidth, ImageOptions, OutputFormat};
+    ///
+    /// let program = assemble(".data .label x .number 5 .text clac add x halt").unwrap();
+    /// let image = program.text_image(&ImageOptions::new(OutputFormat::V2Raw));
+    /// assert_eq!(String::from_utf8(image).unwrap(), "v2.0 raw\n3000\n2000\n6002\n");
+    ///
+    /// let image = program.text_image(&ImageOptions::new(OutputFormat::V3Hex));
+    /// assert_eq!(
+    ///     String::from_utf8(image).unwrap(),
+    ///     "v3.0 hex words addressed\n00: 3000 2000 6002\n"
+    /// );
+    ///
+    /// let mut options = ImageOptions::new(OutputFormat::V2Raw);
+    /// options.text_width = CellWidth::Bytes;
+    /// let image = program.text_image(&options);
+    /// assert_eq!(String::from_utf8(image).unwrap(), "v2.0 raw\n30\n00\n20\n00\n60\n02\n");
+    ///
+    /// let mut options = ImageOptions::new(OutputFormat::V2Raw);
+    /// options.annotate = true;
+    /// let image = program.text_image(&options);
+    /// assert_eq!(
+    ///     String::from_utf8(image).unwrap(),
+    ///     "v2.0 raw\n3000 # clac\n2000 # add @0x0\n6002 # br @0x2\n"
+    /// );
+    /// ```
+    pub fn text_image(&self, options: &ImageOptions) -> Vec<u8> {
+        let words = image::pad_words(self.text_words(), self.cpu.text_words(), options);
+        let width = options.text_width;
+        let cells = width.cells(&words);
+        if !options.annotate {
+            return image::render(&cells, width.digits(), options);
+        }
+        let mut notes = self.label_annotations(Section::Text, width);
+        for (index, instr) in self.text.iter().enumerate() {
+            notes
+                .after
+                .insert(index * width.cells_per_word(), instr.to_string());
+        }
+        image::render_annotated(&cells, width.digits(), options, &notes)
+    }
+
+    /// The labels in `section` as comments above the first cell of the word they name.
+    fn label_annotations(&self, section: Section, width: CellWidth) -> Annotations {
+        let base = match section {
+            Section::Text => self.text_base,
+            Section::Data => self.data_base,
+        };
+        let mut notes = Annotations::default();
+        for symbol in self
+            .symbols
+            .iter()
+            .filter(|symbol| symbol.section == section)
+        {
+            notes
+                .above
+                .entry((symbol.address - base) * width.cells_per_word())
+                .or_default()
+                .push(format!("{}:", symbol.name));
+        }
+        notes
+    }
+
+    /// Text images for a pair of 8-bit ROMs: the high byte of every instruction, then the
+    /// low byte. Padding fills each out to the CPU's text size in bytes.
+    ///
+    /// ```
+    /// use single_address_assembler::assemble;
+    /// use single_address_assembler::image::{ImageOptions, OutputFormat};
+    ///
+    /// let program = assemble(".data .label x .number 5 .text clac add x halt").unwrap();
+    /// let [high, low] = program.split_text_images(&ImageOptions::new(OutputFormat::V2Raw));
+    /// assert_eq!(String::from_utf8(high).unwrap(), "v2.0 raw\n30\n20\n60\n");
+    /// assert_eq!(String::from_utf8(low).unwrap(), "v2.0 raw\n00\n00\n02\n");
+    /// ```
+    pub fn split_text_images(&self, options: &ImageOptions) -> [Vec<u8>; 2] {
+        let words = image::pad_words(self.text_words(), self.cpu.text_words(), options);
+        let high: Vec<u16> = words.iter().map(|word| word >> 8).collect();
+        let low: Vec<u16> = words.iter().map(|word| word & 0xff).collect();
+        [
+            image::render(&high, 2, options),
+            image::render(&low, 2, options),
+        ]
+    }
+
+    /// Each data word as the 16 bits stored in memory.
+    pub fn data_words(&self) -> Vec<u16> {
+        self.data.iter().map(|data| *data as u16).collect()
+    }
+
+    /// The data memory image in the given format, exactly as written to disk, with each word
+    /// split into cells per [`ImageOptions::data_width`]. Padding fills the image out to
+    /// the CPU's data RAM size.
+    ///
+    /// ```
+    /// use single_address_assembler::assemble;
+    /// use single_address_assembler::image::{CellWidth, ImageOptions, OutputFormat};
+    ///
+    /// let program = assemble(".data .label x .number 5 .number -1 .text add x").unwrap();
+    /// let image = program.data_image(&ImageOptions::new(OutputFormat::V2Raw));
+    /// assert_eq!(String::from_utf8(image).unwrap(), "v2.0 raw\n00\n05\nff\nff\n");
+    ///
+    /// let mut options = ImageOptions::new(OutputFormat::V2Raw);
+    /// options.rle = true;
+    /// options.pad = Some(0);
+    /// let image = program.data_image(&options);
+    /// assert_eq!(String::from_utf8(image).unwrap(), "v2.0 raw\n00 05 2*ff 508*00\n");
+    ///
+    /// let mut options = ImageOptions::new(OutputFormat::V2Raw);
+    /// options.data_width = Some(CellWidth::Words);
+    /// let image = program.data_image(&options);
+    /// assert_eq!(String::from_utf8(image).unwrap(), "v2.0 raw\n0005\nffff\n");
+    /// ```
+    pub fn data_image(&self, options: &ImageOptions) -> Vec<u8> {
+        let words = image::pad_words(self.data_words(), self.cpu.data_words(), options);
+        let width = options.data_width();
+        let mut cells = width.data_cells(&words, options.data_endian);
+        // A binary image writes whole-word cells high byte first, so swap them beforehand.
+        if width == CellWidth::Words
+            && options.format == OutputFormat::Binary
+            && options.data_endian == Endian::Little
+        {
+            cells.iter_mut().for_each(|cell| *cell = cell.swap_bytes());
+        }
+        if options.annotate {
+            let notes = self.label_annotations(Section::Data, width);
+            return image::render_annotated(&cells, width.digits(), options, &notes);
+        }
+        image::render(&cells, width.digits(), options)
+    }
+
     pub fn assemble_text(&self) -> Vec<u8> {
         let mut assembled = Vec::with_capacity(self.text.len() * 2);
         for instr in &self.text {
-            assembled.extend(&instr.bytes());
+            assembled.extend(&self.cpu.encode(instr));
         }
         assembled
     }
 
-    pub fn data_bytes(&self) -> Vec<u8> {
+    /// Lays text and data out in one 16-bit word address space, with the text at
+    /// `text_base` and the data, split into cells of `data_width` in `data_endian` order, at
+    /// `data_base`. Returns `None` if the two would overlap.
+    pub fn combined_words(
+        &self,
+        text_base: usize,
+        data_base: usize,
+        data_width: CellWidth,
+        data_endian: Endian,
+    ) -> Option<Vec<u16>> {
+        let data = data_width.data_cells(&self.data_words(), data_endian);
+        let text_end = text_base + self.text.len();
+        let data_end = data_base + data.len();
+        if text_base < data_end && data_base < text_end {
+            return None;
+        }
+
+        let mut words = vec![0; text_end.max(data_end)];
+        for (word, instr) in words[text_base..].iter_mut().zip(&self.text) {
+            *word = self.cpu.word(instr);
+        }
+        words[data_base..data_end].copy_from_slice(&data);
+        Some(words)
+    }
+
+    /// The data words as bytes, each word's two in `endian` order.
+    ///
+    /// ```
+    /// use single_address_assembler::assemble;
+    /// use single_address_assembler::image::Endian;
+    ///
+    /// let program = assemble(".data .label x .number 0x1234 .text add x").unwrap();
+    /// assert_eq!(program.data_bytes(Endian::Big), [0x12, 0x34]);
+    /// assert_eq!(program.data_bytes(Endian::Little), [0x34, 0x12]);
+    /// ```
+    pub fn data_bytes(&self, endian: Endian) -> Vec<u8> {
         let mut bytes = Vec::with_capacity(self.data.len() * 2);
-        for data in &self.data {
-            bytes.extend(&data.to_be_bytes());
+        for &data in &self.data {
+            bytes.extend(&endian.bytes(data as u16));
         }
 
         bytes
@@ -43,15 +879,141 @@ impl AddressedProgram {
 
 pub struct Parser<'a> {
     pub input: &'a str,
-    pub lexer: Lexer<'a, Token<'a>>,
+    lexer: Lexer<'a, Token<'a>>,
 
     pub text: Vec<Instruction<'a>>,
-    pub data: Vec<i16>,
+    /// Source span of each instruction in `text`, from mnemonic through operand.
+    pub text_spans: Vec<Span>,
+    pub data: Vec<DataWord<'a>>,
+    /// Source span of the literal for each word in `data`.
+    pub data_spans: Vec<Span>,
+
+    /// Index in `text` of the instruction each text label precedes; see
+    /// [`address_program`](Self::address_program) for how that becomes an address.
+    pub text_labels: HashMap<&'a str, (usize, Span)>,
+    pub data_labels: HashMap<&'a str, (usize, Span)>,
+
+    pub warnings: Vec<Warning>,
+
+    /// Every error [`parse_program`](Self::parse_program) found, in source order; it returns
+    /// the first.
+    pub errors: Vec<ParseError>,
+
+    /// Offsets in `input` where each source file begins, when several files were
+    /// concatenated into one input.
+    pub file_starts: Vec<usize>,
+
+    /// Directory of each source file in `file_starts`, against which `.incbin` paths are
+    /// resolved; files without one resolve against the working directory.
+    ///
+    /// ```
+    /// use single_address_assembler::Parser;
+    ///
+    /// let dir = std::env::temp_dir();
+    /// std::fs::write(dir.join("incbin-doc.bin"), [0x12, 0x34, 0xff, 0xfe, 0x07]).unwrap();
+    /// let source = ".data .label table .incbin \"incbin-doc.bin\" offset 2 .text halt";
+    /// let mut parser = Parser::new(source);
+    /// parser.file_dirs = vec![dir.clone()];
+    /// let err = parser.parse_program().unwrap_err();
+    /// assert!(err.to_string().ends_with("3 bytes do not make whole 16-bit words; pass --pad-byte to pad the last one"));
+    ///
+    /// let mut parser = Parser::new(source);
+    /// parser.file_dirs = vec![dir];
+    /// parser.pad_byte = Some(0);
+    /// parser.parse_program().unwrap();
+    /// assert_eq!(parser.address_program().unwrap().data, [-2, 0x0700]);
+    /// ```
+    pub file_dirs: Vec<PathBuf>,
+
+    /// Byte that completes an `.incbin` file of odd length; without one, odd lengths are
+    /// an error.
+    pub pad_byte: Option<u8>,
+
+    /// Address of the first data word, added to every data label when resolving operands.
+    pub data_base: usize,
+
+    /// Address of the first instruction, added to every text label when resolving branches.
+    pub text_base: usize,
+
+    /// Let the text grow past the CPU's limit while parsing, for [`optimize`](Self::optimize)
+    /// to shrink; [`address_program`](Self::address_program) still rejects what does not fit.
+    pub optimizing: bool,
+
+    /// Which extensions to accept and how strictly to treat warnings.
+    pub options: ParseOptions,
 
-    pub text_labels: HashMap<&'a str, (u8, Span)>,
-    pub data_labels: HashMap<&'a str, (u8, Span)>,
+    /// Values of `.equ` constants, with the span of their definition; the span is `None`
+    /// for constants seeded before parsing, such as those from `--define`.
+    ///
+    /// A constant may be defined again with the same value, but not a different one. `.equ`
+    /// constants that are never used, or that share a label's name, are warned about:
+    ///
+    /// ```
+    /// use single_address_assembler::{ParseError, Parser, Warning};
+    ///
+    /// let parser = Parser::parse(".equ n 2 .equ n 2 .text addi n halt").unwrap();
+    /// assert!(parser.warnings.is_empty());
+    ///
+    /// let err = Parser::parse(".equ n 2 .equ n 3 .text addi n halt").unwrap_err();
+    /// assert!(matches!(err, ParseError::DuplicateConstant(_, Some(_), _)));
+    /// assert_eq!(err.note().unwrap().0, "first defined here");
+    ///
+    /// let parser = Parser::parse(".equ spare 1 .equ top 0 .text .label top addi top br top")
+    ///     .unwrap();
+    /// assert!(matches!(&parser.warnings[0], Warning::UnusedConstant(name, _) if name == "spare"));
+    /// assert_eq!(
+    ///     parser.warnings[1].to_string(),
+    ///     "constant 'top' has the same name as a text label, so operands naming it are ambiguous"
+    /// );
+    /// ```
+    pub constants: HashMap<String, (i16, Option<Span>)>,
 
-    pub peeked: Option<Token<'a>>,
+    /// Other spellings of mnemonics defined by `.alias`, with the mnemonic each stands for
+    /// and the span of its definition; the span is `None` for aliases seeded before
+    /// parsing with [`define_alias`](Self::define_alias).
+    ///
+    /// ```
+    /// use single_address_assembler::listing::{listing, ListingOptions};
+    /// use single_address_assembler::{AddressedInstruction::*, ParseError, Parser};
+    ///
+    /// let source = ".alias jmp br .text .label top clac jz top jmp top";
+    /// let mut parser = Parser::new(source);
+    /// parser.define_alias("jz", "beqz").unwrap();
+    /// parser.parse_program().unwrap();
+    /// let program = parser.address_program().unwrap();
+    /// assert_eq!(program.text, [ClearAc, BranchZero(0), Branch(0)]);
+    /// assert!(listing(&parser, &program, &ListingOptions::default()).contains("jmp top"));
+    ///
+    /// let err = Parser::parse(".alias stor br .text halt").unwrap_err();
+    /// assert!(matches!(err, ParseError::AliasShadows(..)));
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "cannot make `stor` an alias for `br`: `stor` is already a mnemonic"
+    /// );
+    ///
+    /// let err = Parser::parse(".alias jmp br .text .label top jnp top").unwrap_err();
+    /// assert!(err.to_string().ends_with("did you mean 'jmp'?"));
+    /// ```
+    pub aliases: HashMap<String, (&'static str, Option<Span>)>,
+
+    /// The CPU variant to assemble for; instructions it lacks are rejected.
+    pub cpu: CpuSpec,
+
+    /// Unreachable code removed by [`optimize`](Self::optimize), in source order.
+    pub eliminated: Vec<EliminatedRange>,
+
+    /// Names of the constants that some value has used.
+    used_constants: HashSet<&'a str>,
+
+    /// Warning levels set by the directives at the top of each file in `file_starts`.
+    file_levels: Vec<HashMap<&'static str, LintLevel>>,
+
+    peeked: Option<Token<'a>>,
+    instr_start: usize,
+    /// Parentheses and unary operators open in the constant expression being parsed.
+    expr_depth: usize,
+    /// Tokens consumed so far, for logging.
+    tokens_read: usize,
 }
 
 impl fmt::Debug for Parser<'_> {
@@ -62,112 +1024,728 @@ impl fmt::Debug for Parser<'_> {
             .field("data", &self.data)
             .field("text_labels", &self.text_labels)
             .field("data_labels", &self.data_labels)
+            .field("warnings", &self.warnings)
             .finish()
     }
 }
 
 impl<'a> Parser<'a> {
     pub fn new(input: &'a str) -> Self {
+        Self::with_options(input, ParseOptions::default())
+    }
+
+    /// A parser for `input` that accepts what `options` allow, for callers that configure
+    /// more of the parser before calling [`parse_program`](Self::parse_program).
+    pub fn with_options(input: &'a str, options: ParseOptions) -> Self {
         Parser {
             input,
             lexer: Token::lexer(input),
             text: vec![],
+            text_spans: vec![],
             data: vec![],
+            data_spans: vec![],
             text_labels: HashMap::new(),
             data_labels: HashMap::new(),
+            warnings: vec![],
+            errors: vec![],
+            file_starts: vec![0],
+            file_dirs: vec![],
+            pad_byte: None,
+            data_base: 0,
+            text_base: 0,
+            optimizing: false,
+            options,
+            constants: HashMap::new(),
+            aliases: HashMap::new(),
+            cpu: CpuSpec::default(),
+            eliminated: vec![],
+            used_constants: HashSet::new(),
+            file_levels: Vec::new(),
             peeked: None,
+            instr_start: 0,
+            expr_depth: 0,
+            tokens_read: 0,
         }
     }
 
+    /// Parses `input` with the default options. Like [`parse_program`](Self::parse_program)
+    /// and [`address_program`](Self::address_program), it reports malformed input as an
+    /// error and never panics; `fuzz/` holds a fuzz target that checks this.
     pub fn parse(input: &'a str) -> Result<Self, ParseError> {
         let mut parser = Self::new(input);
-        parser.parse_input()?;
+        parser.parse_program()?;
         Ok(parser)
     }
 
-    pub fn address_program(&mut self) -> Result<AddressedProgram, ParseError> {
-        let mut text = Vec::with_capacity(self.text.len());
-        let data = self.data.clone();
-
-        for instr in self.text.iter() {
-            let addressed = match instr {
-                Instruction::Add(label) => {
-                    let address = self
-                        .data_label_address(label)
-                        .ok_or_else(|| ParseError::UnknownLabel((*label).to_owned()))?;
-                    AddressedInstruction::Add(address)
+    /// Parses `input` like [`parse`](Self::parse), accepting what `options` allow.
+    ///
+    /// ```
+    /// use single_address_assembler::{ParseError, ParseOptions, Parser, Warning};
+    ///
+    /// let source = "clac .label unused halt";
+    /// assert!(Parser::parse_with(source, ParseOptions::new()).is_ok());
+    ///
+    /// let err = Parser::parse_with(source, ParseOptions::new().implicit_text(false)).unwrap_err();
+    /// assert_eq!(err.to_string(), "invalid token `clac`: expected `.text` or `.data`");
+    ///
+    /// match Parser::parse_with(source, ParseOptions::new().strict(true)) {
+    ///     Err(ParseError::DeniedWarning(Warning::UnusedLabel(label, _, _))) => {
+    ///         assert_eq!(label, "unused")
+    ///     }
+    ///     other => panic!("unexpected result: {:?}", other),
+    /// }
+    /// ```
+    pub fn parse_with(input: &'a str, options: ParseOptions) -> Result<Self, ParseError> {
+        let mut parser = Self::with_options(input, options);
+        parser.parse_program()?;
+        Ok(parser)
+    }
+
+    /// Parses and addresses `source` with the default options, taking ownership of it so the
+    /// result can outlive the caller's copy of the text.
+    ///
+    /// ```
+    /// use single_address_assembler::Parser;
+    ///
+    /// let owned = {
+    ///     let source = String::from(".data .label x .number 2 .text add x halt");
+    ///     Parser::parse_owned(source).unwrap()
+    /// };
+    /// assert_eq!(owned.program.symbol("x").unwrap().address, 0);
+    /// assert_eq!(&owned.source[owned.program.text_spans[0].clone()], "add x");
+    /// ```
+    pub fn parse_owned(source: String) -> Result<OwnedProgram, ParseError> {
+        let (program, warnings) = {
+            let mut parser = Parser::parse(&source)?;
+            let warnings = std::mem::take(&mut parser.warnings);
+            (parser.into_program()?, warnings)
+        };
+        Ok(OwnedProgram {
+            source,
+            program,
+            warnings,
+        })
+    }
+
+    /// Parses the whole input and runs the post-parse checks, for callers that need to
+    /// configure the parser between `new` and parsing.
+    ///
+    /// A file that starts with an instruction or `.label` is parsed as if it began with
+    /// `.text`, which [`pedantic`](Self::pedantic) parsers warn about:
+    ///
+    /// ```
+    /// use single_address_assembler::{Parser, Warning};
+    ///
+    /// let mut parser = Parser::new("clac addi 1 halt");
+    /// parser.options.pedantic = true;
+    /// parser.parse_program().unwrap();
+    /// assert_eq!(parser.text.len(), 3);
+    /// assert!(matches!(parser.warnings[..], [Warning::ImplicitText(_)]));
+    ///
+    /// let err = Parser::parse(".number 5").unwrap_err();
+    /// assert!(err.to_string().contains("add `.data`"));
+    /// ```
+    ///
+    /// Parsing goes on past an error, resuming at the next line or statement, and collects
+    /// each independent mistake once in [`errors`](Self::errors):
+    ///
+    /// ```
+    /// use single_address_assembler::Parser;
+    ///
+    /// let source = ".data
+    ///     .label x .number 1 2 3 )
+    ///     .label y .number 2
+    ///     .text
+    ///     ad x
+    ///     addi 999 clac
+    ///     shl 40
+    ///     .label top clac
+    ///     .label top
+    ///     add y
+    ///     halt";
+    /// let mut parser = Parser::new(source);
+    /// let first = parser.parse_program().unwrap_err();
+    /// let lines: Vec<usize> = parser
+    ///     .errors
+    ///     .iter()
+    ///     .map(|err| source[..err.span().unwrap().start].matches('\n').count() + 1)
+    ///     .collect();
+    /// assert_eq!(lines, [2, 5, 6, 7, 9]);
+    /// assert_eq!(first.to_string(), parser.errors[0].to_string());
+    /// ```
+    pub fn parse_program(&mut self) -> Result<(), ParseError> {
+        for (index, &start) in self.file_starts.clone().iter().enumerate() {
+            let end = self
+                .file_starts
+                .get(index + 1)
+                .copied()
+                .unwrap_or(self.input.len());
+            match lint::file_levels(&self.input[start..end], start) {
+                Ok(levels) => self.file_levels.push(levels),
+                Err(err) => {
+                    self.file_levels.push(HashMap::new());
+                    self.errors.push(err);
                 }
-                Instruction::Subtract(label) => {
-                    let address = self
-                        .data_label_address(label)
-                        .ok_or_else(|| ParseError::UnknownLabel((*label).to_owned()))?;
-                    AddressedInstruction::Subtract(address)
+            }
+            // Each file starts with a fresh lexer, so it must open its own section.
+            self.lexer = Token::lexer(&self.input[..end]);
+            self.lexer.bump(start);
+            self.peeked = None;
+            if let Err(err) = self.parse_input() {
+                let fatal = is_fatal(&err);
+                self.errors.push(err);
+                if fatal {
+                    break;
                 }
-                Instruction::Multiply(label) => {
-                    let address = self
-                        .data_label_address(label)
-                        .ok_or_else(|| ParseError::UnknownLabel((*label).to_owned()))?;
-                    AddressedInstruction::Multiply(address)
+            }
+        }
+        match self.errors.first() {
+            Some(err) => Err(err.clone()),
+            None => self.finish(),
+        }
+    }
+
+    /// Runs the checks that need the whole program, once every instruction, word, and label
+    /// has been added.
+    pub(crate) fn finish(&mut self) -> Result<(), ParseError> {
+        if self.text.is_empty() {
+            if self.data.is_empty() {
+                return Err(ParseError::EmptyInput);
+            }
+            self.warnings.push(Warning::NoInstructions);
+        }
+        self.check_unused_labels();
+        self.check_shadowed_labels();
+        self.check_reachability();
+        self.check_program_end();
+        self.check_label_style();
+        self.check_constants();
+        self.warnings
+            .sort_by_key(|warning| warning.span().map(|span| span.start));
+        for warning in std::mem::take(&mut self.warnings) {
+            let level = match warning {
+                Warning::MissingHalt(_) if self.options.require_halt => LintLevel::Deny,
+                _ => self.level(&warning),
+            };
+            match level {
+                LintLevel::Allow => {}
+                LintLevel::Warn => self.warnings.push(warning),
+                LintLevel::Deny => self.errors.push(ParseError::DeniedWarning(warning)),
+            }
+        }
+        match self.errors.first() {
+            Some(err) => Err(err.clone()),
+            None => Ok(()),
+        }
+    }
+
+    /// The level `warning` is reported at; see [`LintLevel`] for where it comes from.
+    /// Warnings without a span take the directives of the only file, if there is just one.
+    pub fn level(&self, warning: &Warning) -> LintLevel {
+        let lint = Lint::find(warning.code()).unwrap();
+        let file = match warning.span() {
+            Some(span) => self
+                .file_starts
+                .iter()
+                .rposition(|&start| start <= span.start),
+            None if self.file_levels.len() == 1 => Some(0),
+            None => None,
+        };
+        let from_file = file
+            .and_then(|file| self.file_levels.get(file))
+            .and_then(|levels| levels.get(lint.code));
+        let mut default = lint.default;
+        if self.options.pedantic && lint.pedantic {
+            default = LintLevel::Warn;
+        }
+        if self.options.strict && default == LintLevel::Warn {
+            default = LintLevel::Deny;
+        }
+        from_file
+            .or_else(|| self.options.levels.get(lint.code))
+            .copied()
+            .unwrap_or(default)
+    }
+
+    fn check_unused_labels(&mut self) {
+        let mut used = HashSet::with_capacity(self.text_labels.len() + self.data_labels.len());
+        used.extend(
+            self.text
+                .iter()
+                .filter_map(|instr| instr.label())
+                .chain(self.data.iter().filter_map(DataWord::label))
+                .map(|(label, _)| label),
+        );
+
+        for (labels, section) in &[
+            (&self.text_labels, Section::Text),
+            (&self.data_labels, Section::Data),
+        ] {
+            for (label, (_, span)) in labels.iter() {
+                if !used.contains(label) {
+                    self.warnings.push(Warning::UnusedLabel(
+                        (*label).to_owned(),
+                        *section,
+                        span.clone(),
+                    ));
                 }
-                Instruction::Divide(label) => {
-                    let address = self
-                        .data_label_address(label)
-                        .ok_or_else(|| ParseError::UnknownLabel((*label).to_owned()))?;
-                    AddressedInstruction::Divide(address)
+            }
+        }
+    }
+
+    /// Warns about `.equ` constants that are never used or share a label's name. Constants
+    /// from `--define` are left alone, since one definition may serve many programs.
+    fn check_constants(&mut self) {
+        for (name, (_, span)) in &self.constants {
+            let span = match span {
+                Some(span) => span,
+                None => continue,
+            };
+            if !self.used_constants.contains(name.as_str()) {
+                self.warnings
+                    .push(Warning::UnusedConstant(name.clone(), span.clone()));
+            }
+            for (labels, section) in &[
+                (&self.text_labels, Section::Text),
+                (&self.data_labels, Section::Data),
+            ] {
+                if let Some((_, label_span)) = labels.get(name.as_str()) {
+                    self.warnings.push(Warning::ConstantShadowsLabel(
+                        name.clone(),
+                        *section,
+                        label_span.clone(),
+                        span.clone(),
+                    ));
                 }
-                Instruction::Remainder(label) => {
-                    let address = self
-                        .data_label_address(label)
-                        .ok_or_else(|| ParseError::UnknownLabel((*label).to_owned()))?;
-                    AddressedInstruction::Remainder(address)
+            }
+        }
+    }
+
+    /// Warns about labels that do not match the label style, when anything turns `W007` on.
+    fn check_label_style(&mut self) {
+        let code = "W007";
+        let enabled = self
+            .options
+            .levels
+            .get(code)
+            .into_iter()
+            .chain(
+                self.file_levels
+                    .iter()
+                    .filter_map(|levels| levels.get(code)),
+            )
+            .any(|&level| level != LintLevel::Allow);
+        if !enabled {
+            return;
+        }
+        let style = &self.options.label_style;
+        for (labels, section) in &[
+            (&self.text_labels, Section::Text),
+            (&self.data_labels, Section::Data),
+        ] {
+            for (label, (_, span)) in labels.iter() {
+                if !style.matches(label) {
+                    self.warnings.push(Warning::MisnamedLabel(
+                        (*label).to_owned(),
+                        *section,
+                        style.to_string(),
+                        span.clone(),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Warns about names defined in both sections, which makes operands easy to misread.
+    fn check_shadowed_labels(&mut self) {
+        for (label, (_, text_span)) in &self.text_labels {
+            if let Some((_, data_span)) = self.data_labels.get(label) {
+                self.warnings.push(Warning::ShadowedLabel(
+                    (*label).to_owned(),
+                    text_span.clone(),
+                    data_span.clone(),
+                ));
+            }
+        }
+    }
+
+    /// Warns about instructions that follow an unconditional branch with no label in between,
+    /// since nothing can ever jump to them.
+    fn check_reachability(&mut self) {
+        // Whether each instruction, or the end of the text, has a label or literal target.
+        let mut labeled = vec![false; self.text.len() + 1];
+        for (loc, _) in self.text_labels.values() {
+            labeled[*loc] = true;
+        }
+        // Literal branch targets make their instruction reachable just like a label does.
+        let literals = self
+            .text
+            .iter()
+            .filter_map(|instr| match instr {
+                Instruction::BranchZero(target, _) | Instruction::Branch(target, _) => {
+                    address_literal(target)
+                }
+                _ => None,
+            })
+            .filter_map(|address| address.checked_sub(self.text_base));
+        for index in literals {
+            if let Some(labeled) = labeled.get_mut(index) {
+                *labeled = true;
+            }
+        }
+        let mut reachable = true;
+
+        for (index, instr) in self.text.iter().enumerate() {
+            if labeled[index] {
+                reachable = true;
+            }
+
+            if !reachable {
+                self.warnings.push(Warning::UnreachableCode(
+                    index,
+                    self.text_spans[index].clone(),
+                ));
+                // Report each unreachable run once, at its first instruction.
+                reachable = true;
+            }
+
+            if let Instruction::Branch(..) | Instruction::Halt = instr {
+                reachable = false;
+            }
+        }
+    }
+
+    /// Warns when the last instruction can fall through into whatever follows the program.
+    fn check_program_end(&mut self) {
+        match self.text.last() {
+            None | Some(Instruction::Branch(..)) | Some(Instruction::Halt) => {}
+            Some(_) => {
+                let span = self.text_spans[self.text.len() - 1].clone();
+                self.warnings.push(Warning::MissingHalt(span));
+            }
+        }
+    }
+
+    /// Resolves every label to an address, producing the program as it will be encoded.
+    ///
+    /// On a CPU with long branches (see [`cpu`](crate::cpu)), a branch past `0xff` takes an
+    /// extra `prefix` word, which moves every later label. Layout starts with every branch
+    /// short and lengthens the ones whose targets are out of reach until nothing changes;
+    /// branches only ever grow, so this settles.
+    ///
+    /// ```
+    /// use single_address_assembler::cpu::CpuSpec;
+    /// use single_address_assembler::{AddressedInstruction::*, Parser};
+    ///
+    /// let source = format!(".text br far {} .label far halt", "noop ".repeat(300));
+    /// let mut parser = Parser::new(&source);
+    /// parser.cpu = CpuSpec::parse(&format!(
+    ///     "{}prefix = {{ opcode = 7 }}\n[memory]\ntext_words = 512",
+    ///     single_address_assembler::cpu::DEFAULT_SPEC
+    /// ))
+    /// .unwrap();
+    /// parser.parse_program().unwrap();
+    /// let program = parser.address_program().unwrap();
+    ///
+    /// assert_eq!(program.symbol("far").unwrap().address, 302);
+    /// assert_eq!(program.text[..2], [Prefix(1), Branch(46)]);
+    /// assert_eq!(program.text[302..], [Prefix(1), Branch(46)]);
+    /// assert_eq!(program.text_spans[0], program.text_spans[1]);
+    /// ```
+    pub fn address_program(&mut self) -> Result<AddressedProgram, ParseError> {
+        self.address(self.data_spans.clone())
+    }
+
+    /// Addresses the program like [`address_program`](Self::address_program), moving what
+    /// it can out of the parser instead of copying it; for callers done with the parser.
+    ///
+    /// ```
+    /// use single_address_assembler::Parser;
+    ///
+    /// let program = Parser::parse(".data .label x 3 .text add x halt")
+    ///     .unwrap()
+    ///     .into_program()
+    ///     .unwrap();
+    /// assert_eq!((program.text.len(), program.data), (2, vec![3]));
+    /// ```
+    pub fn into_program(mut self) -> Result<AddressedProgram, ParseError> {
+        let data_spans = std::mem::take(&mut self.data_spans);
+        self.address(data_spans)
+    }
+
+    /// Resolves every label, producing a program whose data words cover `data_spans`.
+    fn address(&self, data_spans: Vec<Span>) -> Result<AddressedProgram, ParseError> {
+        let starts = self.layout()?;
+        let mut text = Vec::with_capacity(starts[self.text.len()]);
+        let mut text_spans = Vec::with_capacity(text.capacity());
+        let mut synthesized = Vec::new();
+        let data = self
+            .data
+            .iter()
+            .map(|word| match word {
+                DataWord::Value(value) => Ok(*value),
+                DataWord::DataAddress(label, span) => self
+                    .resolve_data_label(label, span)
+                    .map(|address| address as i16),
+                DataWord::TextAddress(label, span) => self
+                    .resolve_text_label(label, span, &starts)
+                    .map(|address| address as i16),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (index, instr) in self.text.iter().enumerate() {
+            let branch_target = |label, span| {
+                self.resolve_text_label(label, span, &starts)
+                    .map(|target| ((target >> 8) as u8, (target & 0xff) as Address))
+            };
+            let (high, addressed) = match instr {
+                Instruction::Add(label, span) => (
+                    0,
+                    AddressedInstruction::Add(self.resolve_data_label(label, span)?),
+                ),
+                Instruction::Subtract(label, span) => (
+                    0,
+                    AddressedInstruction::Subtract(self.resolve_data_label(label, span)?),
+                ),
+                Instruction::Multiply(label, span) => (
+                    0,
+                    AddressedInstruction::Multiply(self.resolve_data_label(label, span)?),
+                ),
+                Instruction::Divide(label, span) => (
+                    0,
+                    AddressedInstruction::Divide(self.resolve_data_label(label, span)?),
+                ),
+                Instruction::Remainder(label, span) => (
+                    0,
+                    AddressedInstruction::Remainder(self.resolve_data_label(label, span)?),
+                ),
+                Instruction::And(label, span) => (
+                    0,
+                    AddressedInstruction::And(self.resolve_data_label(label, span)?),
+                ),
+                Instruction::BranchZero(label, span) => {
+                    let (high, low) = branch_target(label, span)?;
+                    (high, AddressedInstruction::BranchZero(low))
                 }
-                Instruction::And(label) => {
-                    let address = self
-                        .data_label_address(label)
-                        .ok_or_else(|| ParseError::UnknownLabel((*label).to_owned()))?;
-                    AddressedInstruction::And(address)
+                Instruction::Branch(label, span) => {
+                    let (high, low) = branch_target(label, span)?;
+                    (high, AddressedInstruction::Branch(low))
                 }
-                Instruction::BranchZero(label) => {
-                    let address = self
-                        .text_label_address(label)
-                        .ok_or_else(|| ParseError::UnknownLabel((*label).to_owned()))?;
-                    AddressedInstruction::BranchZero(address)
+                Instruction::Store(label, span) => (
+                    0,
+                    AddressedInstruction::Store(self.resolve_data_label(label, span)?),
+                ),
+                Instruction::AddImmediate(i) => (0, AddressedInstruction::AddImmediate(*i)),
+                Instruction::SubtractImmediate(i) => {
+                    (0, AddressedInstruction::SubtractImmediate(*i))
                 }
-                Instruction::Branch(label) => {
-                    let address = self
-                        .text_label_address(label)
-                        .ok_or_else(|| ParseError::UnknownLabel((*label).to_owned()))?;
-                    AddressedInstruction::Branch(address)
+                Instruction::MultiplyImmediate(i) => {
+                    (0, AddressedInstruction::MultiplyImmediate(*i))
                 }
-                Instruction::Store(label) => {
-                    let address = self
-                        .data_label_address(label)
-                        .ok_or_else(|| ParseError::UnknownLabel((*label).to_owned()))?;
-                    AddressedInstruction::Store(address)
+                Instruction::DivideImmediate(i) => (0, AddressedInstruction::DivideImmediate(*i)),
+                Instruction::RemainderImmediate(i) => {
+                    (0, AddressedInstruction::RemainderImmediate(*i))
+                }
+                Instruction::Shift(i) => (0, AddressedInstruction::Shift(*i)),
+                Instruction::AndImmediate(i) => (0, AddressedInstruction::AndImmediate(*i)),
+
+                Instruction::AddIndirect(label, span) => (
+                    0,
+                    AddressedInstruction::AddIndirect(self.resolve_data_label(label, span)?),
+                ),
+                Instruction::SubtractIndirect(label, span) => (
+                    0,
+                    AddressedInstruction::SubtractIndirect(self.resolve_data_label(label, span)?),
+                ),
+                Instruction::MultiplyIndirect(label, span) => (
+                    0,
+                    AddressedInstruction::MultiplyIndirect(self.resolve_data_label(label, span)?),
+                ),
+                Instruction::DivideIndirect(label, span) => (
+                    0,
+                    AddressedInstruction::DivideIndirect(self.resolve_data_label(label, span)?),
+                ),
+                Instruction::RemainderIndirect(label, span) => (
+                    0,
+                    AddressedInstruction::RemainderIndirect(self.resolve_data_label(label, span)?),
+                ),
+                Instruction::AndIndirect(label, span) => (
+                    0,
+                    AddressedInstruction::AndIndirect(self.resolve_data_label(label, span)?),
+                ),
+                Instruction::StoreIndirect(label, span) => (
+                    0,
+                    AddressedInstruction::StoreIndirect(self.resolve_data_label(label, span)?),
+                ),
+                Instruction::ClearAc => (0, AddressedInstruction::ClearAc),
+                Instruction::NoOp => (0, AddressedInstruction::NoOp),
+                Instruction::Halt => {
+                    let target = self.halt_target(index, &starts)?;
+                    let low = (target & 0xff) as Address;
+                    ((target >> 8) as u8, AddressedInstruction::Branch(low))
                 }
-                Instruction::AddImmediate(i) => AddressedInstruction::AddImmediate(*i),
-                Instruction::SubtractImmediate(i) => AddressedInstruction::SubtractImmediate(*i),
-                Instruction::MultiplyImmediate(i) => AddressedInstruction::MultiplyImmediate(*i),
-                Instruction::DivideImmediate(i) => AddressedInstruction::DivideImmediate(*i),
-                Instruction::RemainderImmediate(i) => AddressedInstruction::RemainderImmediate(*i),
-                Instruction::Shift(i) => AddressedInstruction::Shift(*i),
-                Instruction::AndImmediate(i) => AddressedInstruction::AndImmediate(*i),
-
-                Instruction::ClearAc => AddressedInstruction::ClearAc,
-                Instruction::NoOp => AddressedInstruction::NoOp,
             };
+            // A data address is only known to fit the 8-bit value field once it is encoded;
+            // branches past it were split into a `prefix` and the low byte above.
+            if let Err(EncodeError::OperandOutOfRange(_, address)) = self.cpu.try_encode(&addressed)
+            {
+                let (label, span) = instr.label().unwrap();
+                return Err(ParseError::AddressOutOfRange(
+                    label.to_owned(),
+                    Section::Data,
+                    address.into(),
+                    self.data_base,
+                    span.clone(),
+                ));
+            }
+            let span = &self.text_spans[index];
+            if starts[index + 1] - starts[index] == 2 {
+                synthesized.push(text.len());
+                text.push(AddressedInstruction::Prefix(high));
+                text_spans.push(span.clone());
+            }
+            if let Instruction::Halt = instr {
+                synthesized.push(text.len());
+            }
             text.push(addressed);
+            text_spans.push(span.clone());
+        }
+
+        let mut symbols: Vec<Symbol> = self
+            .text_labels
+            .iter()
+            .map(|(name, (index, _))| Symbol {
+                name: (*name).to_owned(),
+                section: Section::Text,
+                address: starts[*index] + self.text_base,
+            })
+            .chain(self.data_labels.iter().map(|(name, (offset, _))| Symbol {
+                name: (*name).to_owned(),
+                section: Section::Data,
+                address: offset + self.data_base,
+            }))
+            .collect();
+        // No two symbols are equal, so an unstable sort gives the same order.
+        symbols.sort_unstable_by(|a, b| {
+            (a.address, a.section)
+                .cmp(&(b.address, b.section))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        let code_addresses = self
+            .data
+            .iter()
+            .enumerate()
+            .filter(|(_, word)| matches!(word, DataWord::TextAddress(..)))
+            .map(|(offset, _)| offset)
+            .collect();
+        crate::log!(
+            Debug,
+            "addressed {} instructions in {} words at {:#x} and {} data words at {:#x}",
+            self.text.len(),
+            text.len(),
+            self.text_base,
+            data.len(),
+            self.data_base
+        );
+        Ok(AddressedProgram {
+            text,
+            data,
+            symbols,
+            text_spans,
+            data_spans,
+            text_base: self.text_base,
+            data_base: self.data_base,
+            code_addresses,
+            synthesized,
+            cpu: self.cpu.clone(),
+        })
+    }
+
+    /// The word offset of each instruction in `text`, followed by the total size in words,
+    /// with branches lengthened as needed to reach their targets.
+    fn layout(&self) -> Result<Vec<usize>, ParseError> {
+        let mut long = vec![false; self.text.len()];
+        loop {
+            let mut starts = Vec::with_capacity(long.len() + 1);
+            let mut words = 0;
+            for &long in &long {
+                starts.push(words);
+                words += if long { 2 } else { 1 };
+            }
+            starts.push(words);
+
+            if words > self.text_limit() {
+                // Report the first instruction that does not fit.
+                let index = starts
+                    .iter()
+                    .position(|&end| end > self.text_limit())
+                    .unwrap()
+                    - 1;
+                let span = self.text_spans[index].clone();
+                let text = self.input.get(span.clone()).unwrap_or_default().to_owned();
+                return Err(ParseError::InstructionOverflow(
+                    text,
+                    self.text_limit(),
+                    span,
+                ));
+            }
+
+            let mut grew = false;
+            for (index, instr) in self.text.iter().enumerate() {
+                let (target, label, span) = match instr {
+                    Instruction::Branch(label, span) | Instruction::BranchZero(label, span) => {
+                        (self.resolve_text_label(label, span, &starts)?, *label, span)
+                    }
+                    Instruction::Halt => (
+                        self.halt_target(index, &starts)?,
+                        "halt",
+                        &self.text_spans[index],
+                    ),
+                    _ => continue,
+                };
+                if target > 0xff && !self.cpu.has_long_branches() {
+                    return Err(ParseError::BranchOutOfReach(
+                        label.to_owned(),
+                        target,
+                        span.clone(),
+                    ));
+                }
+                if target > 0xff && !long[index] {
+                    long[index] = true;
+                    grew = true;
+                }
+            }
+            if !grew {
+                return Ok(starts);
+            }
         }
+    }
 
-        Ok(AddressedProgram { text, data })
+    /// The address `halt` branches to, which is its own (or its prefix's) address.
+    fn halt_target(&self, index: usize, starts: &[usize]) -> Result<usize, ParseError> {
+        let address = starts[index] + self.text_base;
+        if address < self.cpu.text_words() {
+            Ok(address)
+        } else {
+            Err(ParseError::AddressOutOfRange(
+                "halt".to_owned(),
+                Section::Text,
+                address,
+                self.text_base,
+                self.text_spans[index].clone(),
+            ))
+        }
     }
 
     fn next_token_opt(&mut self) -> Option<Token<'a>> {
-        if self.peeked.is_some() {
+        let token = if self.peeked.is_some() {
             std::mem::take(&mut self.peeked)
         } else {
-            self.lexer.next()
-        }
+            self.lexer.next().map(Token::classify)
+        };
+        self.tokens_read += token.is_some() as usize;
+        token
     }
 
     fn next_token<S: ToString>(&mut self, expected: S) -> Result<Token<'a>, ParseError> {
@@ -176,23 +1754,49 @@ impl<'a> Parser<'a> {
     }
 
     fn peek_token(&mut self) -> Option<Token<'a>> {
-        if let t @ Some(_) = self.peeked.as_ref().cloned() {
-            t
-        } else {
-            self.peeked = self.lexer.next();
-            self.peeked.as_ref().cloned()
+        if self.peeked.is_none() {
+            self.peeked = self.lexer.next().map(Token::classify);
         }
+        self.peeked
     }
 
     fn parse_input(&mut self) -> Result<(), ParseError> {
-        let token = self.next_token("expected `.text` or `.data`")?;
+        let mut token = match self.next_token_opt() {
+            Some(token) => token,
+            None => return Err(ParseError::EmptyInput),
+        };
+        while token == Token::Equ || token == Token::Alias {
+            if token == Token::Equ {
+                self.parse_equ()?;
+            } else {
+                self.parse_alias()?;
+            }
+            token = match self.next_token_opt() {
+                Some(token) => token,
+                None => return Ok(()),
+            };
+        }
 
         match token {
             Token::Text => self.parse_text()?,
             Token::Data => self.parse_data()?,
-            other => {
+            // A file may start straight into code, as if it began with `.text`.
+            Token::Label if self.options.implicit_text => self.parse_implicit_text(token)?,
+            _ if (token.is_mnemonic() || self.alias_for(token).is_some())
+                && self.options.implicit_text =>
+            {
+                self.parse_implicit_text(token)?
+            }
+            Token::Number => {
+                return Err(ParseError::InvalidToken(
+                    self.lexer.slice().to_owned(),
+                    "data words belong in a data section; add `.data` before it".to_owned(),
+                    self.lexer.span(),
+                ))
+            }
+            _ => {
                 return Err(ParseError::InvalidToken(
-                    other.to_string(),
+                    self.lexer.slice().to_owned(),
                     "expected `.text` or `.data`".to_owned(),
                     self.lexer.span(),
                 ))
@@ -202,68 +1806,264 @@ impl<'a> Parser<'a> {
         Ok(())
     }
 
-    fn text_label_address(&self, label: &str) -> Option<u8> {
+    /// Parses a text section that `token`, already read, starts without a `.text` line.
+    fn parse_implicit_text(&mut self, token: Token<'a>) -> Result<(), ParseError> {
+        self.warnings.push(Warning::ImplicitText(self.lexer.span()));
+        self.peeked = Some(token);
+        self.parse_text()
+    }
+
+    fn text_label_index(&self, label: &str) -> Option<usize> {
         self.text_labels.get(label).map(|(loc, _)| *loc)
     }
 
-    fn data_label_address(&self, label: &str) -> Option<u8> {
+    fn data_label_address(&self, label: &str) -> Option<usize> {
         self.data_labels.get(label).map(|(loc, _)| *loc)
     }
 
-    fn add_text_label(&mut self) -> Result<(), ParseError> {
-        let label = self.parse_label()?;
-        if self.text_labels.contains_key(label) {
-            let (_, span) = &self.text_labels[label];
-            Err(ParseError::DuplicateLabel(
+    /// Resolves a branch operand to a word address, given the word offset of each
+    /// instruction. Address literals are absolute and ignore `text_base`.
+    fn resolve_text_label(
+        &self,
+        label: &str,
+        span: &Span,
+        starts: &[usize],
+    ) -> Result<usize, ParseError> {
+        if let Some(address) = address_literal(label) {
+            return Ok(address);
+        }
+        let index = self.text_label_index(label).ok_or_else(|| {
+            if self.data_labels.contains_key(label) {
+                ParseError::WrongLabelKind(label.to_owned(), Section::Data, span.clone())
+            } else {
+                let hint = closest_label(label, &self.text_labels)
+                    .map(|s| format!("did you mean '{}'?", s));
+                ParseError::UnknownLabel(label.to_owned(), hint, span.clone())
+            }
+        })?;
+
+        let address = starts[index] + self.text_base;
+        if address < self.cpu.text_words() {
+            Ok(address)
+        } else {
+            Err(ParseError::AddressOutOfRange(
                 label.to_owned(),
+                Section::Text,
+                address,
+                self.text_base,
                 span.clone(),
-                self.lexer.span(),
             ))
-        } else {
-            let location = self.current_text();
-            let span = self.lexer.span();
-
-            self.text_labels.insert(label, (location, span));
+        }
+    }
 
-            Ok(())
+    /// Resolves a data operand. Address literals are absolute and ignore `data_base`.
+    fn resolve_data_label(&self, label: &str, span: &Span) -> Result<Address, ParseError> {
+        // Literals were checked to fit in 8 bits when parsed.
+        if let Some(address) = address_literal(label) {
+            return Ok(address as Address);
         }
+        let offset = self.data_label_address(label).ok_or_else(|| {
+            if self.text_labels.contains_key(label) {
+                ParseError::WrongLabelKind(label.to_owned(), Section::Text, span.clone())
+            } else {
+                let hint = closest_label(label, &self.data_labels)
+                    .map(|s| format!("did you mean '{}'?", s));
+                ParseError::UnknownLabel(label.to_owned(), hint, span.clone())
+            }
+        })?;
+
+        let address = offset + self.data_base;
+        Address::try_from(address).map_err(|_| {
+            ParseError::AddressOutOfRange(
+                label.to_owned(),
+                Section::Data,
+                address,
+                self.data_base,
+                span.clone(),
+            )
+        })
+    }
+
+    fn add_text_label(&mut self) -> Result<(), ParseError> {
+        let label = self.parse_label()?;
+        self.define_label(Section::Text, label, self.lexer.span())
     }
 
     fn add_data_label(&mut self) -> Result<(), ParseError> {
         let label = self.parse_label()?;
-        if self.data_labels.contains_key(label) {
-            let (_, span) = &self.data_labels[label];
-            Err(ParseError::DuplicateLabel(
+        self.define_label(Section::Data, label, self.lexer.span())
+    }
+
+    /// Defines `label` at the next free address of `section`.
+    pub(crate) fn define_label(
+        &mut self,
+        section: Section,
+        label: &'a str,
+        span: Span,
+    ) -> Result<(), ParseError> {
+        let labels = match section {
+            Section::Text => &self.text_labels,
+            Section::Data => &self.data_labels,
+        };
+        if let Some((_, first)) = labels.get(label) {
+            return Err(ParseError::DuplicateLabel(
                 label.to_owned(),
-                span.clone(),
-                self.lexer.span(),
-            ))
-        } else {
-            let location = self.current_data();
-            let span = self.lexer.span();
+                first.clone(),
+                span,
+            ));
+        }
+
+        let name = || format!(".label {}", label);
+        let location = match section {
+            Section::Text => self.current_text().ok_or_else(|| {
+                ParseError::InstructionOverflow(name(), self.text_limit(), span.clone())
+            })?,
+            Section::Data => self
+                .current_data()
+                .ok_or_else(|| ParseError::DataOverflow(name(), self.data_limit(), span.clone()))?,
+        };
+        match section {
+            Section::Text => self.text_labels.insert(label, (location, span)),
+            Section::Data => self.data_labels.insert(label, (location, span)),
+        };
+        crate::log!(
+            Debug,
+            "defined {} label `{}` at {}",
+            section,
+            label,
+            location
+        );
 
-            self.data_labels.insert(label, (location, span));
+        Ok(())
+    }
 
-            Ok(())
+    fn parse_immediate(&mut self, mnemonic: &Token) -> Result<(Immediate, Span), ParseError> {
+        let (value, span) = self.parse_value()?;
+        match Immediate::try_from(value) {
+            Ok(value) => Ok((value, span)),
+            Err(_) => Err(ParseError::ImmediateOutOfRange(
+                mnemonic.to_string(),
+                self.input[span.clone()].to_owned(),
+                span,
+            )),
         }
     }
 
-    fn parse_immediate(&mut self) -> Result<Immediate, ParseError> {
-        match self.next_token("expected an integer")? {
-            Token::NumLiteral(i) => match i8::try_from(i) {
-                Ok(i) => Ok(i),
-                Err(_) => Err(ParseError::InvalidNumber(i, self.lexer.span())),
-            },
-            other => Err(ParseError::InvalidToken(
-                other.to_string(),
-                "expected an integer".to_owned(),
-                self.lexer.span(),
-            )),
+    /// Parses `.alias NAME MNEMONIC` after the `.alias` token. The mnemonic may itself be
+    /// an alias, which stands for the same mnemonic.
+    fn parse_alias(&mut self) -> Result<(), ParseError> {
+        let name_token = self.next_token("expected an alias name")?;
+        let span = self.lexer.span();
+        let name = &self.input[span.clone()];
+        let target = self.next_token("expected a mnemonic")?;
+        let mnemonic = match self.alias_for(target) {
+            Some(token) => token,
+            None if target.is_mnemonic() => target,
+            None => {
+                return Err(ParseError::InvalidToken(
+                    self.lexer.slice().to_owned(),
+                    "expected a mnemonic for the alias to stand for".to_owned(),
+                    self.lexer.span(),
+                ))
+            }
+        };
+        let mnemonic = mnemonic_name(mnemonic);
+        match name_token {
+            Token::LabelIdent(_) => {}
+            token if token.is_mnemonic() || token.is_directive() => {
+                return Err(ParseError::AliasShadows(
+                    name.to_owned(),
+                    mnemonic.to_owned(),
+                    span,
+                ))
+            }
+            _ => {
+                return Err(ParseError::InvalidToken(
+                    name.to_owned(),
+                    "expected an alias name".to_owned(),
+                    span,
+                ))
+            }
+        }
+        if let Some((_, first)) = self.aliases.get(name) {
+            return Err(ParseError::DuplicateAlias(
+                name.to_owned(),
+                first.clone(),
+                span,
+            ));
+        }
+        crate::log!(Debug, "defined alias `{}` for `{}`", name, mnemonic);
+        self.aliases.insert(name.to_owned(), (mnemonic, Some(span)));
+        Ok(())
+    }
+
+    /// Makes `name` another spelling of `mnemonic` before parsing, as `--alias` does, or
+    /// says why it cannot be one.
+    pub fn define_alias(&mut self, name: &str, mnemonic: &str) -> Result<(), String> {
+        let mnemonic = match Token::from_mnemonic(mnemonic) {
+            Some(token) => mnemonic_name(token),
+            None => return Err(format!("`{}` is not a mnemonic", mnemonic)),
+        };
+        let mut lexer = Token::lexer(name);
+        match (lexer.next().map(Token::classify), lexer.next()) {
+            (Some(Token::LabelIdent(_)), None) => {}
+            (Some(token), None) if token.is_mnemonic() || token.is_directive() => {
+                return Err(
+                    ParseError::AliasShadows(name.to_owned(), mnemonic.to_owned(), 0..0)
+                        .to_string(),
+                )
+            }
+            _ => return Err(format!("`{}` is not a valid alias name", name)),
         }
+        if self.aliases.contains_key(name) {
+            return Err(format!("alias '{}' is already defined", name));
+        }
+        self.aliases.insert(name.to_owned(), (mnemonic, None));
+        Ok(())
+    }
+
+    /// The mnemonic `token` stands for, if it is an alias.
+    fn alias_for(&self, token: Token) -> Option<Token<'static>> {
+        match token {
+            Token::LabelIdent(name) => {
+                let (mnemonic, _) = self.aliases.get(name)?;
+                Token::from_mnemonic(mnemonic)
+            }
+            _ => None,
+        }
+    }
+
+    /// Parses `.equ NAME VALUE` after the `.equ` token.
+    fn parse_equ(&mut self) -> Result<(), ParseError> {
+        let name = self.parse_label()?;
+        let span = self.lexer.span();
+        let (value, _) = self.parse_value()?;
+        match self.constants.get(name) {
+            // Repeating a definition, as a shared header might, changes nothing.
+            Some((first_value, _)) if *first_value == value => {}
+            Some((_, first)) => {
+                return Err(ParseError::DuplicateConstant(
+                    name.to_owned(),
+                    first.clone(),
+                    span,
+                ))
+            }
+            None => {
+                self.constants.insert(name.to_owned(), (value, Some(span)));
+            }
+        }
+        Ok(())
     }
 
     fn parse_immediate_instr(&mut self, token: Token) -> Result<(), ParseError> {
-        let ival = self.parse_immediate()?;
+        let (ival, span) = self.parse_immediate(&token)?;
+        if ival == 0
+            && !self.options.allow_div_zero
+            && matches!(token, Token::DivideImmediate | Token::RemainderImmediate)
+        {
+            return Err(ParseError::DivisionByZero(token.to_string(), span));
+        }
+
         let instr = match token {
             Token::AddImmediate => Instruction::AddImmediate(ival),
             Token::SubtractImmediate => Instruction::SubtractImmediate(ival),
@@ -275,29 +2075,109 @@ impl<'a> Parser<'a> {
             _ => unreachable!(),
         };
 
-        self.add_instr(instr)
+        self.push_instr(instr, self.instr_start..span.end)
+    }
+
+    /// Parses the amount of `shl n` or `shr n`, which both assemble to `shift` with the
+    /// direction in the sign.
+    fn parse_directional_shift(&mut self, token: Token) -> Result<(), ParseError> {
+        let (value, span) = self.parse_value()?;
+        if !(0..=15).contains(&value) {
+            return Err(ParseError::ImmediateOutOfRange(
+                token.to_string(),
+                self.input[span.clone()].to_owned(),
+                span,
+            ));
+        }
+        let amount = value as Immediate;
+        let amount = if token == Token::ShiftRight {
+            -amount
+        } else {
+            amount
+        };
+        self.push_instr(Instruction::Shift(amount), self.instr_start..span.end)
     }
 
     fn parse_alu_instr(&mut self, token: Token) -> Result<(), ParseError> {
-        let label = self.parse_label()?;
-        let instr = match token {
-            Token::Add => Instruction::Add(label),
-            Token::Subtract => Instruction::Subtract(label),
-            Token::Multiply => Instruction::Multiply(label),
-            Token::Divide => Instruction::Divide(label),
-            Token::Remainder => Instruction::Remainder(label),
-            Token::And => Instruction::And(label),
+        let (label, span, indirect) = self.parse_data_operand()?;
+        let instr = match (token, indirect) {
+            (Token::Add, false) => Instruction::Add(label, span),
+            (Token::Subtract, false) => Instruction::Subtract(label, span),
+            (Token::Multiply, false) => Instruction::Multiply(label, span),
+            (Token::Divide, false) => Instruction::Divide(label, span),
+            (Token::Remainder, false) => Instruction::Remainder(label, span),
+            (Token::And, false) => Instruction::And(label, span),
+            (Token::Add, true) => Instruction::AddIndirect(label, span),
+            (Token::Subtract, true) => Instruction::SubtractIndirect(label, span),
+            (Token::Multiply, true) => Instruction::MultiplyIndirect(label, span),
+            (Token::Divide, true) => Instruction::DivideIndirect(label, span),
+            (Token::Remainder, true) => Instruction::RemainderIndirect(label, span),
+            (Token::And, true) => Instruction::AndIndirect(label, span),
             _ => unreachable!(),
         };
 
         self.add_instr(instr)
     }
 
+    /// Parses a data operand that may be wrapped in brackets, returning whether it was.
+    fn parse_data_operand(&mut self) -> Result<(&'a str, Span, bool), ParseError> {
+        if self.peek_token() != Some(Token::LBracket) {
+            let (label, span) = self.parse_operand(Section::Data)?;
+            return Ok((label, span, false));
+        }
+        self.next_token_opt();
+        let (label, span) = self.parse_operand(Section::Data)?;
+        match self.next_token("expected `]`")? {
+            Token::RBracket => Ok((label, span, true)),
+            _ => Err(ParseError::InvalidToken(
+                self.lexer.slice().to_owned(),
+                "expected `]`".to_owned(),
+                self.lexer.span(),
+            )),
+        }
+    }
+
+    /// Parses a branch target, rejecting the bracketed form that data operands allow.
+    fn parse_branch_operand(&mut self, token: &Token) -> Result<(&'a str, Span), ParseError> {
+        if self.peek_token() == Some(Token::LBracket) {
+            self.next_token_opt();
+            return Err(ParseError::IndirectBranch(
+                token.to_string(),
+                self.lexer.span(),
+            ));
+        }
+        self.parse_operand(Section::Text)
+    }
+
+    /// Parses a label or address literal operand into `section`, returning it along with
+    /// its span in the input. Literals are checked to fit in 8 bits, or in text memory for
+    /// branches on a CPU with long branches.
+    fn parse_operand(&mut self, section: Section) -> Result<(&'a str, Span), ParseError> {
+        if let Some(Token::AddressLiteral(literal)) = self.peek_token() {
+            self.next_token_opt();
+            let span = self.lexer.span();
+            let limit = match section {
+                Section::Text if self.cpu.has_long_branches() => self.cpu.text_words(),
+                _ => 0x100,
+            };
+            return match address_literal(literal) {
+                Some(address) if address < limit => Ok((literal, span)),
+                _ => Err(ParseError::AddressLiteralOutOfRange(
+                    literal.to_owned(),
+                    span,
+                )),
+            };
+        }
+
+        let label = self.parse_label()?;
+        Ok((label, self.lexer.span()))
+    }
+
     fn parse_label(&mut self) -> Result<&'a str, ParseError> {
         match self.next_token("expected a label")? {
             Token::LabelIdent(val) => Ok(val),
-            other => Err(ParseError::InvalidToken(
-                other.to_string(),
+            _ => Err(ParseError::InvalidToken(
+                self.lexer.slice().to_owned(),
                 "expected a label".to_owned(),
                 self.lexer.span(),
             )),
@@ -305,137 +2185,618 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_text(&mut self) -> Result<(), ParseError> {
+        let start = (self.tokens_read, self.text.len());
         loop {
-            match self.next_token_opt() {
-                Some(Token::Label) => self.add_text_label()?,
-                Some(Token::Data) => return self.parse_data(),
-                Some(t @ Token::Add)
-                | Some(t @ Token::Subtract)
-                | Some(t @ Token::Multiply)
-                | Some(t @ Token::Divide)
-                | Some(t @ Token::Remainder)
-                | Some(t @ Token::And) => self.parse_alu_instr(t)?,
-
-                Some(t @ Token::AddImmediate)
-                | Some(t @ Token::SubtractImmediate)
-                | Some(t @ Token::MultiplyImmediate)
-                | Some(t @ Token::DivideImmediate)
-                | Some(t @ Token::RemainderImmediate)
-                | Some(t @ Token::AndImmediate)
-                | Some(t @ Token::Shift) => self.parse_immediate_instr(t)?,
-
-                Some(Token::BranchZero) => {
-                    let label = self.parse_label()?;
-                    self.add_instr(Instruction::BranchZero(label))?;
+            let token = self.next_token_opt();
+            self.instr_start = self.lexer.span().start;
+            match token {
+                Some(Token::Data) => {
+                    self.log_section(Section::Text, start);
+                    return self.parse_data();
                 }
-                Some(Token::Branch) => {
-                    let label = self.parse_label()?;
-                    self.add_instr(Instruction::Branch(label))?;
+                Some(token) => {
+                    if let Err(err) = self.parse_text_statement(token) {
+                        self.recover(err, Section::Text)?;
+                    }
                 }
-                Some(Token::ClearAc) => {
-                    self.add_instr(Instruction::ClearAc)?;
+                None => break,
+            }
+        }
+
+        self.log_section(Section::Text, start);
+        Ok(())
+    }
+
+    /// Parses the label, `.equ`, or instruction that `token`, already read, starts.
+    fn parse_text_statement(&mut self, token: Token<'a>) -> Result<(), ParseError> {
+        match token {
+            Token::Label => self.add_text_label(),
+            Token::Equ => self.parse_equ(),
+            Token::Alias => self.parse_alias(),
+            token if self.alias_for(token).is_some() => {
+                let mnemonic = self.alias_for(token).unwrap();
+                self.parse_text_statement(mnemonic)
+            }
+            t @ Token::Add
+            | t @ Token::Subtract
+            | t @ Token::Multiply
+            | t @ Token::Divide
+            | t @ Token::Remainder
+            | t @ Token::And => self.parse_alu_instr(t),
+
+            t @ Token::AddImmediate
+            | t @ Token::SubtractImmediate
+            | t @ Token::MultiplyImmediate
+            | t @ Token::DivideImmediate
+            | t @ Token::RemainderImmediate
+            | t @ Token::AndImmediate
+            | t @ Token::Shift => self.parse_immediate_instr(t),
+            t @ Token::ShiftLeft | t @ Token::ShiftRight => self.parse_directional_shift(t),
+
+            t @ Token::BranchZero => {
+                let (label, span) = self.parse_branch_operand(&t)?;
+                self.add_instr(Instruction::BranchZero(label, span))
+            }
+            t @ Token::Branch => {
+                let (label, span) = self.parse_branch_operand(&t)?;
+                self.add_instr(Instruction::Branch(label, span))
+            }
+            Token::ClearAc => self.add_instr(Instruction::ClearAc),
+            Token::Store => {
+                let instr = match self.parse_data_operand()? {
+                    (label, span, false) => Instruction::Store(label, span),
+                    (label, span, true) => Instruction::StoreIndirect(label, span),
+                };
+                self.add_instr(instr)
+            }
+            Token::NoOp => self.add_instr(Instruction::NoOp),
+            Token::Halt => self.add_instr(Instruction::Halt),
+            Token::LabelIdent(ident) => {
+                let names = MNEMONICS
+                    .iter()
+                    .copied()
+                    .chain(self.aliases.keys().map(String::as_str));
+                let expected = match closest_match(ident, names) {
+                    Some(mnemonic) => {
+                        format!("unknown mnemonic '{}'; did you mean '{}'?", ident, mnemonic)
+                    }
+                    None => "expected mnemonic, label, or `.data`".to_owned(),
+                };
+                Err(ParseError::InvalidToken(
+                    ident.to_owned(),
+                    expected,
+                    self.lexer.span(),
+                ))
+            }
+            _ => Err(ParseError::InvalidToken(
+                self.lexer.slice().to_owned(),
+                "expected mnemonic, label, or `.data`".to_owned(),
+                self.lexer.span(),
+            )),
+        }
+    }
+
+    /// Records `err` and skips the rest of the statement it interrupted, so that one mistake
+    /// is reported once rather than as a run of errors about the tokens after it. Text
+    /// resumes at the next mnemonic or directive, or at the next line; data, whose values
+    /// may continue over several lines, only at the next directive that starts a statement.
+    /// Running out of memory or input cannot be recovered from and is returned instead.
+    fn recover(&mut self, err: ParseError, section: Section) -> Result<(), ParseError> {
+        if is_fatal(&err) {
+            return Err(err);
+        }
+        self.errors.push(err);
+        while let Some(token) = self.peek_token() {
+            let start = self.lexer.span().start;
+            let line_start = self.input[..start].rfind('\n').map_or(0, |i| i + 1);
+            let starts_line = self.input[line_start..start].trim().is_empty();
+            let resumes = match section {
+                Section::Text => {
+                    starts_line || token.is_mnemonic() || self.alias_for(token).is_some()
                 }
-                Some(Token::Store) => {
-                    let label = self.parse_label()?;
-                    self.add_instr(Instruction::Store(label))?;
+                Section::Data => false,
+            };
+            if resumes
+                || matches!(
+                    token,
+                    Token::Label | Token::Equ | Token::Alias | Token::Text | Token::Data
+                )
+            {
+                break;
+            }
+            self.next_token_opt();
+        }
+        Ok(())
+    }
+
+    fn parse_number(&mut self) -> Result<(DataWord<'a>, Span), ParseError> {
+        match self.next_token("expected `.number`")? {
+            Token::Number => self.parse_data_word(),
+            _ => Err(ParseError::InvalidToken(
+                self.lexer.slice().to_owned(),
+                "expected `.number`".to_owned(),
+                self.lexer.span(),
+            )),
+        }
+    }
+
+    /// Parses consecutive `.number` entries and, unless the options forbid them, bare values,
+    /// adding each to the data as it goes, repeated as often as its `rep` asks. A bare value
+    /// cannot start with a constant's name, which could not be told apart from `rep`.
+    fn parse_number_list(&mut self) -> Result<(), ParseError> {
+        loop {
+            let (number, span) = match self.peek_token() {
+                Some(Token::Number) => self.parse_number()?,
+                Some(Token::NumLiteral(_))
+                | Some(Token::Minus)
+                | Some(Token::Tilde)
+                | Some(Token::LParen)
+                | Some(Token::Ampersand)
+                | Some(Token::TextAddress(_)) => {
+                    // Without `bare_data`, `parse_number` rejects the missing `.number`.
+                    if self.options.bare_data {
+                        self.parse_data_word()?
+                    } else {
+                        self.parse_number()?
+                    }
+                }
+                Some(Token::JumpTable) => {
+                    self.next_token_opt();
+                    let mut targets = vec![self.parse_jump_target()?];
+                    while let Some(Token::LabelIdent(label)) = self.peek_token() {
+                        if label == "rep" {
+                            break;
+                        }
+                        targets.push(self.parse_jump_target()?);
+                    }
+                    // A `rep` after the table repeats its last entry, like any other value.
+                    let last = targets.pop().unwrap();
+                    for (word, span) in targets {
+                        self.add_data(word, span)?;
+                    }
+                    last
+                }
+                Some(token @ Token::String) | Some(token @ Token::StringZ) => {
+                    self.next_token_opt();
+                    let (words, span) = self.parse_string(token == Token::StringZ)?;
+                    self.add_words(words, span)?;
+                    continue;
                 }
-                Some(Token::NoOp) => {
-                    self.add_instr(Instruction::NoOp)?;
+                Some(token @ Token::IncBin) | Some(token @ Token::Csv) => {
+                    self.next_token_opt();
+                    let (words, span) = if token == Token::IncBin {
+                        self.parse_incbin()?
+                    } else {
+                        self.parse_csv()?
+                    };
+                    self.add_words(words, span)?;
+                    continue;
                 }
-                Some(other) => {
+                _ => break,
+            };
+            // `rep` is only a keyword here, where no label could appear.
+            if let Some(Token::LabelIdent("rep")) = self.peek_token() {
+                self.next_token_opt();
+                let (count, count_span) = self.parse_value()?;
+                if count <= 0 {
                     return Err(ParseError::InvalidToken(
-                        other.to_string(),
-                        "expected mnemonic, label, or `.data`".to_owned(),
-                        self.lexer.span(),
+                        self.input[count_span.clone()].to_owned(),
+                        "expected a positive repeat count".to_owned(),
+                        count_span,
                     ));
                 }
-                None => break,
+                self.add_repeated_data(number, count as usize, span.start..count_span.end)?;
+            } else {
+                self.add_data(number, span)?;
             }
         }
 
         Ok(())
     }
 
-    fn parse_number(&mut self) -> Result<i16, ParseError> {
-        match self.next_token("expected `.number`")? {
-            Token::Number => match self.next_token("expected an integer")? {
-                Token::NumLiteral(val) => Ok(val),
-                other => Err(ParseError::InvalidToken(
-                    other.to_string(),
-                    "expected an integer".to_owned(),
+    /// Appends `words`, all covering `span`; the directive that produced them has already
+    /// checked that they fit.
+    fn add_words(&mut self, words: Vec<i16>, span: Span) -> Result<(), ParseError> {
+        for word in words {
+            self.add_data(DataWord::Value(word), span.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Parses the rest of `.incbin "path" [offset N] [length N]`, reading the file's bytes
+    /// from `offset` as big-endian words, with the span of the whole directive. The file must
+    /// fit in the data memory that is left.
+    fn parse_incbin(&mut self) -> Result<(Vec<i16>, Span), ParseError> {
+        let start = self.lexer.span().start;
+        let path = self.parse_file_name()?;
+        let mut offset = 0;
+        let mut length = None;
+        // Like `rep`, `offset` and `length` are only keywords here.
+        while let Some(Token::LabelIdent(keyword @ "offset"))
+        | Some(Token::LabelIdent(keyword @ "length")) = self.peek_token()
+        {
+            self.next_token_opt();
+            let (value, value_span) = self.parse_value()?;
+            if value < 0 {
+                return Err(ParseError::InvalidToken(
+                    self.input[value_span.clone()].to_owned(),
+                    format!("expected a non-negative byte {}", keyword),
+                    value_span,
+                ));
+            }
+            if keyword == "offset" {
+                offset = value as usize;
+            } else {
+                length = Some(value as usize);
+            }
+        }
+        let span = start..self.lexer.span().end;
+        let failed =
+            |reason: String| ParseError::IncludeFailed(path.to_owned(), reason, span.clone());
+
+        let bytes = self.read_included(path, start).map_err(failed)?;
+        if offset > bytes.len() {
+            return Err(failed(format!(
+                "offset {} is past the end of the {}-byte file",
+                offset,
+                bytes.len()
+            )));
+        }
+        let end = match length {
+            Some(length) if offset + length > bytes.len() => {
+                return Err(failed(format!(
+                    "{} bytes from offset {} run past the end of the {}-byte file",
+                    length,
+                    offset,
+                    bytes.len()
+                )))
+            }
+            Some(length) => offset + length,
+            None => bytes.len(),
+        };
+        let mut bytes = bytes[offset..end].to_vec();
+        if bytes.len() % 2 == 1 {
+            match self.pad_byte {
+                Some(pad) => bytes.push(pad),
+                None => {
+                    return Err(failed(format!(
+                    "{} bytes do not make whole 16-bit words; pass --pad-byte to pad the last one",
+                    bytes.len()
+                )))
+                }
+            }
+        }
+
+        let words: Vec<i16> = bytes
+            .chunks(2)
+            .map(|pair| i16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        self.check_included_fits(words.len()).map_err(failed)?;
+        Ok((words, span))
+    }
+
+    /// Parses the rest of `.csv "path"`: a file of integers in a single column or a single
+    /// row, each written as `.number` would take it, with the span of the whole directive.
+    /// The file must fit in the data memory that is left.
+    fn parse_csv(&mut self) -> Result<(Vec<i16>, Span), ParseError> {
+        let start = self.lexer.span().start;
+        let path = self.parse_file_name()?;
+        let span = start..self.lexer.span().end;
+        let failed =
+            |reason: String| ParseError::IncludeFailed(path.to_owned(), reason, span.clone());
+
+        let bytes = self.read_included(path, start).map_err(failed)?;
+        let text =
+            crate::diagnostic::decode_source(&bytes).map_err(|err| failed(err.to_string()))?;
+        let rows: Vec<(usize, Vec<&str>)> = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| (index + 1, line.split(',').map(str::trim).collect()))
+            .collect();
+        if rows.len() > 1 {
+            if let Some((row, cells)) = rows.iter().find(|(_, cells)| cells.len() > 1) {
+                return Err(failed(format!(
+                    "row {} has {} cells; expected a single column or a single row",
+                    row,
+                    cells.len()
+                )));
+            }
+        }
+
+        let mut words = Vec::new();
+        for (row, cells) in &rows {
+            for cell in cells {
+                let value = parse_cell(cell)
+                    .map_err(|reason| failed(format!("row {}: {}", row, reason)))?;
+                words.push(value);
+            }
+        }
+        self.check_included_fits(words.len()).map_err(failed)?;
+        Ok((words, span))
+    }
+
+    /// Parses the rest of `.string "text"` or, with `terminated`, `.stringz "text"`: ASCII
+    /// packed two characters per word, high byte first, with a zero byte completing an odd
+    /// length and, for `.stringz`, a zero word after. The string must fit in the data
+    /// memory that is left.
+    fn parse_string(&mut self, terminated: bool) -> Result<(Vec<i16>, Span), ParseError> {
+        let start = self.lexer.span().start;
+        let text = match self.next_token("expected a quoted string")? {
+            Token::StringLiteral(text) => text,
+            _ => {
+                return Err(ParseError::InvalidToken(
+                    self.lexer.slice().to_owned(),
+                    "expected a quoted string".to_owned(),
                     self.lexer.span(),
-                )),
-            },
-            other => Err(ParseError::InvalidToken(
-                other.to_string(),
-                "expected `.number`".to_owned(),
+                ))
+            }
+        };
+        let span = start..self.lexer.span().end;
+        if !text.is_ascii() {
+            return Err(ParseError::InvalidToken(
+                self.lexer.slice().to_owned(),
+                "expected ASCII text; a packed string holds one byte per character".to_owned(),
+                self.lexer.span(),
+            ));
+        }
+
+        let mut words: Vec<i16> = text
+            .as_bytes()
+            .chunks(2)
+            .map(|pair| i16::from_be_bytes([pair[0], pair.get(1).copied().unwrap_or(0)]))
+            .collect();
+        if terminated {
+            words.push(0);
+        }
+        let total = self.data.len() + words.len();
+        if total > self.data_limit() {
+            return Err(ParseError::RepeatOverflow(
+                self.input[span.clone()].to_owned(),
+                total,
+                self.data_limit(),
+                span,
+            ));
+        }
+        Ok((words, span))
+    }
+
+    /// Parses the quoted file name after `.incbin` or `.csv`.
+    fn parse_file_name(&mut self) -> Result<&'a str, ParseError> {
+        match self.next_token("expected a quoted file name")? {
+            Token::StringLiteral(path) => Ok(path),
+            _ => Err(ParseError::InvalidToken(
+                self.lexer.slice().to_owned(),
+                "expected a quoted file name".to_owned(),
                 self.lexer.span(),
             )),
         }
     }
 
-    fn parse_number_list(&mut self) -> Result<Vec<i16>, ParseError> {
-        let mut numbers = Vec::new();
+    /// Reads the file `path` names, relative to the directory of the source file containing
+    /// `offset`.
+    fn read_included(&self, path: &str, offset: usize) -> Result<Vec<u8>, String> {
+        let file = self
+            .file_starts
+            .iter()
+            .rposition(|&file| file <= offset)
+            .unwrap_or(0);
+        let resolved = match self.file_dirs.get(file) {
+            Some(dir) => dir.join(path),
+            None => PathBuf::from(path),
+        };
+        let bytes = std::fs::read(&resolved).map_err(|err| err.to_string())?;
+        crate::log!(
+            Debug,
+            "included {} bytes from {}",
+            bytes.len(),
+            resolved.display()
+        );
+        Ok(bytes)
+    }
 
-        while let Some(Token::Number) = self.peek_token() {
-            numbers.push(self.parse_number()?);
+    /// Checks that `count` words from a file fit in the data memory that is left.
+    fn check_included_fits(&self, count: usize) -> Result<(), String> {
+        let total = self.data.len() + count;
+        if total > self.data_limit() {
+            return Err(format!(
+                "its {} words would bring the data to {} words, past the {}-word data limit",
+                count,
+                total,
+                self.data_limit()
+            ));
         }
+        Ok(())
+    }
 
-        Ok(numbers)
+    /// Parses one text label of a `.jumptable`, which stores its address like `@label`.
+    fn parse_jump_target(&mut self) -> Result<(DataWord<'a>, Span), ParseError> {
+        let label = self.parse_label()?;
+        let span = self.lexer.span();
+        Ok((DataWord::TextAddress(label, span.clone()), span))
+    }
+
+    /// Parses a data value: `&label`, `@label`, or a constant expression.
+    fn parse_data_word(&mut self) -> Result<(DataWord<'a>, Span), ParseError> {
+        match self.peek_token() {
+            Some(Token::Ampersand) => {
+                self.next_token_opt();
+                let start = self.lexer.span().start;
+                let label = self.parse_label()?;
+                let span = self.lexer.span();
+                Ok((DataWord::DataAddress(label, span.clone()), start..span.end))
+            }
+            Some(Token::TextAddress(label)) => {
+                self.next_token_opt();
+                let span = self.lexer.span();
+                Ok((DataWord::TextAddress(label, span.start + 1..span.end), span))
+            }
+            _ => {
+                let (value, span) = self.parse_value()?;
+                Ok((DataWord::Value(value), span))
+            }
+        }
     }
 
     fn parse_data(&mut self) -> Result<(), ParseError> {
+        let start = (self.tokens_read, self.data.len());
         loop {
             match self.next_token_opt() {
-                Some(Token::Label) => {
-                    self.add_data_label()?;
-                    for number in self.parse_number_list()? {
-                        self.add_data(number)?;
-                    }
+                Some(Token::Text) => {
+                    self.log_section(Section::Data, start);
+                    return self.parse_text();
                 }
-                Some(Token::Text) => return self.parse_text(),
-                Some(other) => {
-                    return Err(ParseError::InvalidToken(
-                        other.to_string(),
-                        "expected `.label`".to_owned(),
-                        self.lexer.span(),
-                    ))
+                Some(token) => {
+                    if let Err(err) = self.parse_data_statement(token) {
+                        self.recover(err, Section::Data)?;
+                    }
                 }
                 None => break,
             }
         }
 
+        self.log_section(Section::Data, start);
         Ok(())
     }
 
-    fn current_text(&self) -> u8 {
-        self.text.len() as u8
+    /// Logs the tokens a section consumed and what it added, given the token count and
+    /// section length when it began.
+    fn log_section(&self, section: Section, (tokens, len): (usize, usize)) {
+        let (added, what) = match section {
+            Section::Text => (self.text.len() - len, "instructions"),
+            Section::Data => (self.data.len() - len, "data words"),
+        };
+        crate::log!(
+            Debug,
+            "{} section: {} tokens, {} {}",
+            section,
+            self.tokens_read - tokens,
+            added,
+            what
+        );
+    }
+
+    /// Parses the labeled data or `.equ` that `token`, already read, starts.
+    fn parse_data_statement(&mut self, token: Token<'a>) -> Result<(), ParseError> {
+        match token {
+            Token::Label => {
+                self.add_data_label()?;
+                self.parse_number_list()
+            }
+            Token::Equ => self.parse_equ(),
+            Token::Alias => self.parse_alias(),
+            Token::NumLiteral(_)
+            | Token::Number
+            | Token::JumpTable
+            | Token::IncBin
+            | Token::Csv => Err(ParseError::InvalidToken(
+                self.lexer.slice().to_owned(),
+                "data words must follow a `.label` that names them".to_owned(),
+                self.lexer.span(),
+            )),
+            _ => Err(ParseError::InvalidToken(
+                self.lexer.slice().to_owned(),
+                "expected `.label`".to_owned(),
+                self.lexer.span(),
+            )),
+        }
+    }
+
+    /// Words of text the program may use: the CPU's text memory, or less if the options
+    /// cap it.
+    fn text_limit(&self) -> usize {
+        let words = self.cpu.text_words();
+        self.options.max_text.map_or(words, |max| max.min(words))
+    }
+
+    /// Words of data the program may use, like [`text_limit`](Self::text_limit).
+    fn data_limit(&self) -> usize {
+        let words = self.cpu.data_words();
+        self.options.max_data.map_or(words, |max| max.min(words))
+    }
+
+    /// Index of the next instruction, or `None` once text memory is full. Each instruction
+    /// takes at least one word, so this is also the fewest words the text can occupy.
+    fn current_text(&self) -> Option<usize> {
+        Some(self.text.len()).filter(|&len| self.optimizing || len < self.text_limit())
     }
 
-    fn current_data(&self) -> u8 {
-        self.data.len() as u8
+    /// Address the next data word will occupy, or `None` once data memory is full.
+    fn current_data(&self) -> Option<usize> {
+        Some(self.data.len()).filter(|&len| len < self.data_limit())
     }
 
     fn add_instr(&mut self, instr: Instruction<'a>) -> Result<(), ParseError> {
-        if self.text.len() == 255 {
+        let span = self.instr_start..self.lexer.span().end;
+        self.push_instr(instr, span)
+    }
+
+    /// Appends an instruction covering `span` of the source.
+    pub(crate) fn push_instr(
+        &mut self,
+        instr: Instruction<'a>,
+        span: Span,
+    ) -> Result<(), ParseError> {
+        if !self.cpu.has(instr.mnemonic()) {
+            match instr.mnemonic().strip_suffix("_indirect") {
+                Some(direct) => Err(ParseError::UnsupportedIndirect(direct.to_owned(), span)),
+                None => Err(ParseError::UnsupportedMnemonic(
+                    instr.mnemonic().to_owned(),
+                    span,
+                )),
+            }
+        } else if self.current_text().is_none() {
+            // Quote the source when there is some; builder instructions have empty spans.
+            let text = match self.input.get(span.clone()) {
+                Some(text) if !text.is_empty() => text.to_owned(),
+                _ => format!("{:?}", instr),
+            };
             Err(ParseError::InstructionOverflow(
-                format!("{:?}", instr),
-                self.lexer.span(),
+                text,
+                self.text_limit(),
+                span,
             ))
         } else {
             self.text.push(instr);
+            self.text_spans.push(span);
             Ok(())
         }
     }
 
-    fn add_data(&mut self, data: i16) -> Result<(), ParseError> {
-        if self.data.len() == 255 {
+    /// Appends `count` copies of `data`, all covering `span`, failing before adding any if
+    /// they would not all fit.
+    fn add_repeated_data(
+        &mut self,
+        data: DataWord<'a>,
+        count: usize,
+        span: Span,
+    ) -> Result<(), ParseError> {
+        let total = self.data.len() + count;
+        if count > 1 && total > self.data_limit() {
+            let text = self.input[span.clone()].to_owned();
+            return Err(ParseError::RepeatOverflow(
+                text,
+                total,
+                self.data_limit(),
+                span,
+            ));
+        }
+        for _ in 0..count {
+            self.add_data(data.clone(), span.clone())?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn add_data(&mut self, data: DataWord<'a>, span: Span) -> Result<(), ParseError> {
+        if self.data.len() >= self.data_limit() {
             Err(ParseError::DataOverflow(
                 format!("{}", data),
-                self.lexer.span(),
+                self.data_limit(),
+                span,
             ))
         } else {
             self.data.push(data);
+            self.data_spans.push(span);
             Ok(())
         }
     }
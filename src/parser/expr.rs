@@ -0,0 +1,238 @@
+//! Constant expressions, accepted wherever the grammar takes a value: `.equ` definitions,
+//! data values and `rep` counts, and instruction immediates.
+//!
+//! Operators follow C, from loosest to tightest binding: `|`, `^`, `&`, `<<` and `>>`,
+//! `+` and `-`, then `*`, `/`, and `%`, with unary `-` and `~` and parentheses above them
+//! all. Every intermediate result must fit in 16 bits, signed or unsigned, so overflow is
+//! reported where it happens rather than wrapping silently.
+//!
+//! A `-` written directly against the digits after it lexes as a negative literal, so
+//! `2-1` and `2 - 1` subtract, while `2 -1` is two values, as in a data list.
+//!
+//! Literals, like every other value, may be anything from `-32768` to `65535`; those
+//! above `32767` are taken as unsigned:
+//!
+//! ```
+//! use single_address_assembler::{assemble, ParseError, Parser};
+//!
+//! let program = assemble(".data .label x .number 32768 .number 0xffff -32768 .text halt");
+//! assert_eq!(program.unwrap().data, [-32768, -1, -32768]);
+//!
+//! for literal in &["65536", "0x10000", "-32769"] {
+//!     let source = format!(".data .label x .number {} .text halt", literal);
+//!     let err = Parser::parse(&source).unwrap_err();
+//!     assert!(matches!(err, ParseError::InvalidConstant(..)));
+//!     assert!(err.to_string().ends_with("which does not fit in 16 bits"));
+//! }
+//! ```
+//!
+//! Parentheses and unary operators may nest at most [`MAX_DEPTH`] deep, so that no input
+//! can overflow the stack:
+//!
+//! ```
+//! use single_address_assembler::{ParseError, Parser};
+//!
+//! let nested = format!("{}1{}", "(".repeat(64), ")".repeat(64));
+//! let source = format!(".data .label x .number {} .text halt", nested);
+//! assert!(Parser::parse(&source).is_ok());
+//!
+//! let source = format!(".data .label x .number {}1 .text halt", "(-".repeat(100_000));
+//! let err = Parser::parse(&source).unwrap_err();
+//! assert!(matches!(err, ParseError::InvalidConstant(..)));
+//! assert_eq!(err.to_string(), "`(` is nested more than 64 deep");
+//! ```
+
+use super::{ParseError, Parser};
+use crate::Token;
+use logos::Span;
+
+/// Smallest and largest values an expression may take along the way.
+const MIN: i64 = i16::MIN as i64;
+const MAX: i64 = u16::MAX as i64;
+
+/// How deeply parentheses and unary operators may nest in one expression.
+pub(crate) const MAX_DEPTH: usize = 64;
+
+#[derive(Clone, Copy)]
+enum BinaryOp {
+    Or,
+    Xor,
+    And,
+    ShiftLeft,
+    ShiftRight,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+impl BinaryOp {
+    /// The operator `token` stands for, with how tightly it binds.
+    fn of(token: &Token) -> Option<(Self, u8)> {
+        let op = match token {
+            Token::Pipe => (Self::Or, 0),
+            Token::Caret => (Self::Xor, 1),
+            Token::Ampersand => (Self::And, 2),
+            Token::ShiftLeftOp => (Self::ShiftLeft, 3),
+            Token::ShiftRightOp => (Self::ShiftRight, 3),
+            Token::Plus => (Self::Add, 4),
+            Token::Minus => (Self::Subtract, 4),
+            Token::Star => (Self::Multiply, 5),
+            Token::Slash => (Self::Divide, 5),
+            Token::Percent => (Self::Remainder, 5),
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    /// `lhs op rhs`, or why it has no value. Division truncates toward zero and `>>` is
+    /// arithmetic, as on the CPU.
+    fn apply(self, lhs: i64, rhs: i64) -> Result<i64, &'static str> {
+        let shift = || match rhs {
+            0..=15 => Ok(rhs as u32),
+            _ => Err("shifts by an amount outside 0..=15"),
+        };
+        Ok(match self {
+            Self::Or => lhs | rhs,
+            Self::Xor => lhs ^ rhs,
+            Self::And => lhs & rhs,
+            Self::ShiftLeft => lhs << shift()?,
+            Self::ShiftRight => lhs >> shift()?,
+            Self::Add => lhs + rhs,
+            Self::Subtract => lhs - rhs,
+            Self::Multiply => lhs * rhs,
+            Self::Divide | Self::Remainder if rhs == 0 => return Err("divides by zero"),
+            Self::Divide => lhs / rhs,
+            Self::Remainder => lhs % rhs,
+        })
+    }
+}
+
+impl<'a> Parser<'a> {
+    /// Parses a constant expression, returning its value and the span it covers. Values
+    /// above `i16::MAX` are taken as unsigned and wrap to the same 16 bits.
+    ///
+    /// The token after the expression is left peeked, so the span of the last token read
+    /// is not the lexer's; use the returned span instead.
+    pub(super) fn parse_value(&mut self) -> Result<(i16, Span), ParseError> {
+        let (value, span) = self.parse_binary(0)?;
+        Ok((value as i16, span))
+    }
+
+    /// Parses operands joined by operators that bind at least as tightly as `min_level`.
+    fn parse_binary(&mut self, min_level: u8) -> Result<(i64, Span), ParseError> {
+        let lhs = self.parse_unary()?;
+        self.parse_binary_rest(lhs, min_level)
+    }
+
+    /// Continues an expression whose first operand, `lhs`, has been read.
+    fn parse_binary_rest(
+        &mut self,
+        mut lhs: (i64, Span),
+        min_level: u8,
+    ) -> Result<(i64, Span), ParseError> {
+        while let Some(token) = self.peek_token() {
+            let next = self.lexer.span();
+            // A negative literal against the end of the operand is `-` and its digits.
+            let (op, level, operand) = match token {
+                Token::NumLiteral(value) if value < 0 && next.start == lhs.1.end => {
+                    (BinaryOp::Subtract, 4, Some(-value))
+                }
+                token => match BinaryOp::of(&token) {
+                    Some((op, level)) => (op, level, None),
+                    None => break,
+                },
+            };
+            if level < min_level {
+                break;
+            }
+            self.next_token_opt();
+            let rhs = match operand {
+                Some(value) => {
+                    self.parse_binary_rest((value, next.start + 1..next.end), level + 1)?
+                }
+                None => self.parse_binary(level + 1)?,
+            };
+            let span = lhs.1.start..rhs.1.end;
+            let value = op
+                .apply(lhs.0, rhs.0)
+                .map_err(|reason| self.invalid_constant(&span, reason.to_owned()))?;
+            lhs = (self.check_range(value, span.clone())?, span);
+        }
+        Ok(lhs)
+    }
+
+    /// Parses an operand: a literal, a `.equ` constant, a parenthesized expression, or one
+    /// of those after unary `-` or `~`.
+    fn parse_unary(&mut self) -> Result<(i64, Span), ParseError> {
+        let expected = "expected an integer or `.equ` constant";
+        let token = self.next_token(expected)?;
+        let span = self.lexer.span();
+        match token {
+            Token::NumLiteral(value) => self.check_range(value, span.clone()).map(|v| (v, span)),
+            Token::LabelIdent(name) if self.constants.contains_key(name) => {
+                self.used_constants.insert(name);
+                Ok((i64::from(self.constants[name].0), span))
+            }
+            Token::Minus | Token::Tilde | Token::LParen => {
+                if self.expr_depth == MAX_DEPTH {
+                    let reason = format!("is nested more than {} deep", MAX_DEPTH);
+                    return Err(self.invalid_constant(&span, reason));
+                }
+                self.expr_depth += 1;
+                let nested = self.parse_nested(token, span);
+                self.expr_depth -= 1;
+                nested
+            }
+            _ => Err(ParseError::InvalidToken(
+                self.lexer.slice().to_owned(),
+                expected.to_owned(),
+                span,
+            )),
+        }
+    }
+
+    /// Parses the rest of an operand that `token`, a unary operator or `(` at `span`,
+    /// starts.
+    fn parse_nested(&mut self, token: Token, span: Span) -> Result<(i64, Span), ParseError> {
+        match token {
+            Token::Minus | Token::Tilde => {
+                let (operand, operand_span) = self.parse_unary()?;
+                let value = if token == Token::Minus {
+                    -operand
+                } else {
+                    !operand
+                };
+                self.check_range(value, span.start..operand_span.end)
+                    .map(|value| (value, span.start..operand_span.end))
+            }
+            Token::LParen => {
+                let (value, _) = self.parse_binary(0)?;
+                match self.next_token("expected `)`")? {
+                    Token::RParen => Ok((value, span.start..self.lexer.span().end)),
+                    _ => Err(ParseError::InvalidToken(
+                        self.lexer.slice().to_owned(),
+                        "expected `)`".to_owned(),
+                        self.lexer.span(),
+                    )),
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// `value`, if it fits in 16 bits.
+    fn check_range(&self, value: i64, span: Span) -> Result<i64, ParseError> {
+        if (MIN..=MAX).contains(&value) {
+            Ok(value)
+        } else {
+            let reason = format!("evaluates to {}, which does not fit in 16 bits", value);
+            Err(self.invalid_constant(&span, reason))
+        }
+    }
+
+    fn invalid_constant(&self, span: &Span, reason: String) -> ParseError {
+        ParseError::InvalidConstant(self.input[span.clone()].to_owned(), reason, span.clone())
+    }
+}
@@ -0,0 +1,218 @@
+//! The pattern labels must match when the label style lint, `W007`, is on.
+//!
+//! Patterns are a small subset of regular expressions, always matched against the whole
+//! label: literal characters, `.`, classes such as `[a-z0-9_]` and `[^0-9]`, the shorthands
+//! `\d` and `\w`, and the quantifiers `?`, `*`, and `+`. A leading `^` and trailing `$` are
+//! accepted and change nothing.
+//!
+//! ```
+//! use single_address_assembler::LabelStyle;
+//!
+//! let style = LabelStyle::default();
+//! assert!(style.matches("loop_2"));
+//! assert!(!style.matches("Loop"));
+//!
+//! let screaming = LabelStyle::new("[A-Z_]+").unwrap();
+//! assert!(screaming.matches("MAIN_LOOP"));
+//! assert!(!screaming.matches("main"));
+//!
+//! // Stacked stars take time linear in the label, not exponential.
+//! let stars = LabelStyle::new(&format!("{}b", ".*".repeat(10))).unwrap();
+//! assert!(!stars.matches(&"a".repeat(200)));
+//! assert!(stars.matches(&format!("{}b", "a".repeat(200))));
+//!
+//! let err = LabelStyle::new("(a|b)+").unwrap_err();
+//! assert_eq!(err, "`(` is not supported in label styles");
+//! ```
+
+use std::fmt;
+
+/// The pattern labels are expected to match, `[a-z][a-z0-9_]*` unless configured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelStyle {
+    pattern: String,
+    items: Vec<(Atom, Repeat)>,
+}
+
+/// What one position in a pattern accepts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Atom {
+    Any,
+    Char(char),
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Repeat {
+    One,
+    Optional,
+    Star,
+    Plus,
+}
+
+impl Default for LabelStyle {
+    fn default() -> Self {
+        Self::new("[a-z][a-z0-9_]*").unwrap()
+    }
+}
+
+impl LabelStyle {
+    /// Compiles `pattern`, or says what in it is not supported.
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let body = pattern.strip_prefix('^').unwrap_or(pattern);
+        let body = body.strip_suffix('$').unwrap_or(body);
+        let mut chars = body.chars();
+        let mut items: Vec<(Atom, Repeat)> = Vec::new();
+        while let Some(c) = chars.next() {
+            let atom = match c {
+                '.' => Atom::Any,
+                '[' => parse_class(&mut chars)?,
+                '\\' => match chars.next() {
+                    Some(escaped) => escape(escaped),
+                    None => return Err("the pattern ends with `\\`".to_owned()),
+                },
+                '?' | '*' | '+' => {
+                    let repeat = match c {
+                        '?' => Repeat::Optional,
+                        '*' => Repeat::Star,
+                        _ => Repeat::Plus,
+                    };
+                    match items.last_mut() {
+                        Some((_, last @ Repeat::One)) => *last = repeat,
+                        _ => return Err(format!("`{}` has nothing to repeat", c)),
+                    }
+                    continue;
+                }
+                '(' | ')' | '|' | '{' | '}' | '^' | '$' | ']' => {
+                    return Err(format!("`{}` is not supported in label styles", c))
+                }
+                c => Atom::Char(c),
+            };
+            items.push((atom, Repeat::One));
+        }
+        Ok(Self {
+            pattern: pattern.to_owned(),
+            items,
+        })
+    }
+
+    /// Whether all of `label` matches the pattern.
+    pub fn matches(&self, label: &str) -> bool {
+        let chars: Vec<char> = label.chars().collect();
+        match_items(&self.items, &chars)
+    }
+}
+
+impl fmt::Display for LabelStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.pattern)
+    }
+}
+
+/// Parses a class after its `[`, through its `]`.
+fn parse_class(chars: &mut std::str::Chars) -> Result<Atom, String> {
+    let negated = chars.as_str().starts_with('^');
+    if negated {
+        chars.next();
+    }
+    let mut ranges = Vec::new();
+    let mut first = true;
+    loop {
+        let c = match chars.next() {
+            Some(']') if !first => break,
+            Some('\\') => match chars.next().map(escape) {
+                Some(Atom::Char(c)) => c,
+                Some(Atom::Class { ranges: more, .. }) => {
+                    ranges.extend(more);
+                    first = false;
+                    continue;
+                }
+                _ => return Err("a class ends with `\\`".to_owned()),
+            },
+            Some(c) => c,
+            None => return Err("a `[` has no matching `]`".to_owned()),
+        };
+        first = false;
+        let rest = chars.as_str();
+        match rest.strip_prefix('-') {
+            Some(after) if !after.is_empty() && !after.starts_with(']') => {
+                chars.next();
+                let end = chars.next().unwrap();
+                if end < c {
+                    return Err(format!("`{}-{}` is not a valid range", c, end));
+                }
+                ranges.push((c, end));
+            }
+            _ => ranges.push((c, c)),
+        }
+    }
+    Ok(Atom::Class { negated, ranges })
+}
+
+/// The atom for `\c`.
+fn escape(c: char) -> Atom {
+    let ranges = match c {
+        'd' => vec![('0', '9')],
+        'w' => vec![('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')],
+        c => return Atom::Char(c),
+    };
+    Atom::Class {
+        negated: false,
+        ranges,
+    }
+}
+
+impl Atom {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Char(expected) => c == *expected,
+            Self::Class { negated, ranges } => {
+                ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi) != *negated
+            }
+        }
+    }
+}
+
+/// Whether `items` match all of `text`. Tracks every item the match could be at after
+/// each character at once, so the time is linear in the length of `text` whatever the
+/// pattern.
+fn match_items(items: &[(Atom, Repeat)], text: &[char]) -> bool {
+    // `states[i]` is whether the match can be at item `i`; `states[items.len()]` accepts.
+    let mut states = vec![false; items.len() + 1];
+    states[0] = true;
+    skip_optional(items, &mut states);
+    for &c in text {
+        let mut next = vec![false; items.len() + 1];
+        for (i, (atom, repeat)) in items.iter().enumerate() {
+            if !states[i] || !atom.matches(c) {
+                continue;
+            }
+            match repeat {
+                Repeat::One | Repeat::Optional => next[i + 1] = true,
+                Repeat::Star | Repeat::Plus => {
+                    next[i] = true;
+                    next[i + 1] = true;
+                }
+            }
+        }
+        skip_optional(items, &mut next);
+        if !next.contains(&true) {
+            return false;
+        }
+        states = next;
+    }
+    states[items.len()]
+}
+
+/// Marks the items after each marked `?` or `*` item, which can match nothing.
+fn skip_optional(items: &[(Atom, Repeat)], states: &mut [bool]) {
+    for (i, (_, repeat)) in items.iter().enumerate() {
+        if states[i] && matches!(repeat, Repeat::Optional | Repeat::Star) {
+            states[i + 1] = true;
+        }
+    }
+}
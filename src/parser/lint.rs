@@ -0,0 +1,169 @@
+//! The warning codes the parser knows and the level each is reported at.
+//!
+//! A warning's level comes from, in order of precedence: a `#![allow(w001)]`, `#![warn(..)]`,
+//! or `#![deny(..)]` comment among the comments at the top of the file it is in; the
+//! [`levels`](super::ParseOptions::levels) the parser was given, such as from `--allow` and
+//! `--deny`; [`strict`](super::ParseOptions::strict); and the code's default in [`LINTS`].
+//! A directive may name several codes, separated by commas, in either case.
+//!
+//! ```
+//! use single_address_assembler::{LintLevel, ParseError, ParseOptions, Parser};
+//!
+//! let source = ".data .label unused 1 .text halt";
+//! assert_eq!(Parser::parse(source).unwrap().warnings.len(), 1);
+//!
+//! let quiet = format!("#![allow(w001)]\n{}", source);
+//! assert!(Parser::parse(&quiet).unwrap().warnings.is_empty());
+//!
+//! let options = ParseOptions::new().level("W001", LintLevel::Deny);
+//! assert!(matches!(
+//!     Parser::parse_with(source, options.clone()),
+//!     Err(ParseError::DeniedWarning(_))
+//! ));
+//! assert!(Parser::parse_with(&quiet, options).is_ok());
+//!
+//! let err = Parser::parse("#![allow(w999)]\n.text halt").unwrap_err();
+//! assert_eq!(err.to_string(), "unknown warning code `w999`");
+//! ```
+
+use super::ParseError;
+use std::collections::HashMap;
+
+/// How a warning is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintLevel {
+    /// Not reported at all.
+    Allow,
+    /// Reported in [`Parser::warnings`](super::Parser::warnings).
+    Warn,
+    /// Reported as a [`ParseError::DeniedWarning`].
+    Deny,
+}
+
+/// One kind of warning.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Lint {
+    /// The code [`Warning::code`](super::Warning::code) gives it, such as `W001`.
+    pub code: &'static str,
+    pub description: &'static str,
+    /// The level it is reported at unless something overrides it.
+    pub default: LintLevel,
+    /// Whether [`pedantic`](super::ParseOptions::pedantic) raises it from `Allow` to `Warn`.
+    pub pedantic: bool,
+}
+
+/// Every warning code, in order.
+pub const LINTS: &[Lint] = &[
+    Lint {
+        code: "W001",
+        description: "a label is never used",
+        default: LintLevel::Warn,
+        pedantic: false,
+    },
+    Lint {
+        code: "W002",
+        description: "an instruction can never run",
+        default: LintLevel::Warn,
+        pedantic: false,
+    },
+    Lint {
+        code: "W003",
+        description: "a data label shares its name with a text label",
+        default: LintLevel::Warn,
+        pedantic: false,
+    },
+    Lint {
+        code: "W004",
+        description: "the program can run past its last instruction",
+        default: LintLevel::Warn,
+        pedantic: false,
+    },
+    Lint {
+        code: "W005",
+        description: "the program has no instructions",
+        default: LintLevel::Warn,
+        pedantic: false,
+    },
+    Lint {
+        code: "W006",
+        description: "a file starts without `.text`",
+        default: LintLevel::Allow,
+        pedantic: true,
+    },
+    Lint {
+        code: "W007",
+        description: "a label does not match the label style",
+        default: LintLevel::Allow,
+        pedantic: false,
+    },
+    Lint {
+        code: "W008",
+        description: "a `.equ` constant has the same name as a label",
+        default: LintLevel::Warn,
+        pedantic: false,
+    },
+    Lint {
+        code: "W009",
+        description: "a `.equ` constant is never used",
+        default: LintLevel::Warn,
+        pedantic: false,
+    },
+];
+
+impl Lint {
+    /// The lint with `code`, ignoring case.
+    pub fn find(code: &str) -> Option<&'static Lint> {
+        LINTS
+            .iter()
+            .find(|lint| lint.code.eq_ignore_ascii_case(code))
+    }
+}
+
+/// The levels set by the directives among the comments that open `file`, which starts at
+/// offset `start` in the parser's input; later directives override earlier ones.
+pub(super) fn file_levels(
+    file: &str,
+    start: usize,
+) -> Result<HashMap<&'static str, LintLevel>, ParseError> {
+    let mut levels = HashMap::new();
+    let mut offset = start;
+    for line in file.split('\n') {
+        let trimmed = line.trim();
+        let line_start = offset + (line.len() - line.trim_start().len());
+        offset += line.len() + 1;
+        if trimmed.is_empty() || (trimmed.starts_with('#') && !trimmed.starts_with("#![")) {
+            continue;
+        }
+        let directive = match trimmed.strip_prefix("#![") {
+            Some(directive) => directive,
+            None => break,
+        };
+        let span = line_start..line_start + trimmed.len();
+        let malformed = || {
+            ParseError::InvalidToken(
+                trimmed.to_owned(),
+                "expected `#![allow(CODE, ...)]`, `#![warn(...)]`, or `#![deny(...)]`".to_owned(),
+                span.clone(),
+            )
+        };
+        let (name, codes) = directive
+            .strip_suffix(")]")
+            .and_then(|directive| directive.split_once('('))
+            .ok_or_else(malformed)?;
+        let level = match name.trim() {
+            "allow" => LintLevel::Allow,
+            "warn" => LintLevel::Warn,
+            "deny" => LintLevel::Deny,
+            _ => return Err(malformed()),
+        };
+        for code in codes.split(',').map(str::trim) {
+            let lint = Lint::find(code).ok_or_else(|| {
+                // `code` is a slice of `trimmed`, which starts at `span.start`.
+                let at = span.start + (code.as_ptr() as usize - trimmed.as_ptr() as usize);
+                ParseError::UnknownLint(code.to_owned(), at..at + code.len())
+            })?;
+            levels.insert(lint.code, level);
+        }
+    }
+    Ok(levels)
+}
@@ -0,0 +1,170 @@
+//! Which language extensions the parser accepts and how strictly it treats warnings.
+
+use super::{LabelStyle, Lint, LintLevel};
+use std::collections::HashMap;
+
+/// Options for [`Parser::with_options`](super::Parser::with_options) and
+/// [`Parser::parse_with`](super::Parser::parse_with). The default accepts every shorthand
+/// the assembler knows and only rejects what cannot be assembled.
+///
+/// Each setter takes and returns the options so calls chain:
+///
+/// ```
+/// use single_address_assembler::{ParseError, ParseOptions, Parser};
+///
+/// let source = ".data .label x 5 .text add x halt";
+/// assert!(Parser::parse_with(source, ParseOptions::default()).is_ok());
+///
+/// let options = ParseOptions::new().bare_data(false);
+/// let err = Parser::parse_with(source, options).unwrap_err();
+/// assert_eq!(err.to_string(), "invalid token `5`: expected `.number`");
+///
+/// let options = ParseOptions::new().max_text(1);
+/// assert!(matches!(
+///     Parser::parse_with(source, options),
+///     Err(ParseError::InstructionOverflow(_, 1, _))
+/// ));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Parse a file that starts with an instruction or `.label` as if it began with `.text`.
+    pub implicit_text: bool,
+    /// Accept data values after a label without `.number`, as in `.label x 1 2 3`.
+    pub bare_data: bool,
+    /// Accept `divi 0` and `remi 0`, e.g. to exercise the hardware's divide-by-zero behavior.
+    pub allow_div_zero: bool,
+    /// Warn about shorthand that is accepted but not written out in full, such as a file
+    /// that starts without `.text`.
+    pub pedantic: bool,
+    /// Turn every warning into a [`ParseError::DeniedWarning`](super::ParseError::DeniedWarning).
+    pub strict: bool,
+    /// Reject a program that can run past its last instruction, whatever level the
+    /// `W004` warning about it has.
+    pub require_halt: bool,
+    /// Levels for particular warning codes, over the defaults and `strict` but under the
+    /// directives in a file; see [`LintLevel`].
+    pub levels: HashMap<&'static str, LintLevel>,
+    /// The pattern labels should match, checked only when the `W007` lint is turned on,
+    /// here or by a directive.
+    ///
+    /// ```
+    /// use single_address_assembler::{LabelStyle, LintLevel, ParseOptions, Parser};
+    ///
+    /// let source = ".text .label Main clac beqz Main halt";
+    /// assert!(Parser::parse(source).unwrap().warnings.is_empty());
+    ///
+    /// let options = ParseOptions::new().level("W007", LintLevel::Warn);
+    /// let parser = Parser::parse_with(source, options.clone()).unwrap();
+    /// assert_eq!(
+    ///     parser.warnings[0].to_string(),
+    ///     "text label 'Main' does not match the label style `[a-z][a-z0-9_]*`"
+    /// );
+    ///
+    /// let options = options.label_style(LabelStyle::new("[A-Z][a-z]*").unwrap());
+    /// assert!(Parser::parse_with(source, options).unwrap().warnings.is_empty());
+    /// ```
+    pub label_style: LabelStyle,
+    /// Words of text the program may use, when fewer than the CPU's text memory.
+    pub max_text: Option<usize>,
+    /// Words of data the program may use, when fewer than the CPU's data memory.
+    pub max_data: Option<usize>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            implicit_text: true,
+            bare_data: true,
+            allow_div_zero: false,
+            pedantic: false,
+            strict: false,
+            require_halt: false,
+            levels: HashMap::new(),
+            label_style: LabelStyle::default(),
+            max_text: None,
+            max_data: None,
+        }
+    }
+}
+
+impl ParseOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets [`implicit_text`](Self::implicit_text).
+    pub fn implicit_text(mut self, allow: bool) -> Self {
+        self.implicit_text = allow;
+        self
+    }
+
+    /// Sets [`bare_data`](Self::bare_data).
+    pub fn bare_data(mut self, allow: bool) -> Self {
+        self.bare_data = allow;
+        self
+    }
+
+    /// Sets [`allow_div_zero`](Self::allow_div_zero).
+    pub fn allow_div_zero(mut self, allow: bool) -> Self {
+        self.allow_div_zero = allow;
+        self
+    }
+
+    /// Sets [`pedantic`](Self::pedantic).
+    pub fn pedantic(mut self, pedantic: bool) -> Self {
+        self.pedantic = pedantic;
+        self
+    }
+
+    /// Sets [`strict`](Self::strict).
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets [`require_halt`](Self::require_halt).
+    ///
+    /// ```
+    /// use single_address_assembler::{LintLevel, ParseError, ParseOptions, Parser};
+    ///
+    /// let options = ParseOptions::new().level("W004", LintLevel::Allow);
+    /// assert!(Parser::parse_with(".text clac", options.clone()).is_ok());
+    /// assert!(matches!(
+    ///     Parser::parse_with(".text clac", options.require_halt(true)),
+    ///     Err(ParseError::DeniedWarning(_))
+    /// ));
+    /// ```
+    pub fn require_halt(mut self, require: bool) -> Self {
+        self.require_halt = require;
+        self
+    }
+
+    /// Reports the warning with `code`, such as `W001`, at `level`.
+    ///
+    /// # Panics
+    ///
+    /// If `code` is not in [`LINTS`](super::LINTS).
+    pub fn level(mut self, code: &str, level: LintLevel) -> Self {
+        let lint = Lint::find(code).unwrap_or_else(|| panic!("unknown warning code `{}`", code));
+        self.levels.insert(lint.code, level);
+        self
+    }
+
+    /// Sets [`label_style`](Self::label_style).
+    pub fn label_style(mut self, style: LabelStyle) -> Self {
+        self.label_style = style;
+        self
+    }
+
+    /// Caps the text at `words` words; see [`max_text`](Self::max_text).
+    pub fn max_text(mut self, words: usize) -> Self {
+        self.max_text = Some(words);
+        self
+    }
+
+    /// Caps the data at `words` words; see [`max_data`](Self::max_data).
+    pub fn max_data(mut self, words: usize) -> Self {
+        self.max_data = Some(words);
+        self
+    }
+}
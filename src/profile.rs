@@ -0,0 +1,196 @@
+//! Dynamic instruction counts and cycle estimates, for `run --profile`.
+//!
+//! ```
+//! use single_address_assembler::assemble;
+//! use single_address_assembler::emulator::{Machine, StepResult};
+//! use single_address_assembler::profile::Profile;
+//!
+//! let program = assemble(
+//!     ".data .label n .number 3
+//!      .text .label loop clac add n subi 1 stor n beqz done br loop .label done halt",
+//! )
+//! .unwrap();
+//! let mut machine = Machine::new(&program);
+//! let mut profile = Profile::new(&program);
+//! while let Ok(StepResult::Executed(step)) = machine.step() {
+//!     profile.record(&step);
+//! }
+//!
+//! assert_eq!(profile.steps, 17);
+//! assert_eq!(profile.cycles, 17);
+//! assert_eq!(profile.mix(&program)[0], ("add", 3, 3));
+//! assert_eq!(profile.hottest(2), vec![(0, 3), (1, 3)]);
+//! ```
+
+use crate::diagnostic::SourceMap;
+use crate::emulator::Step;
+use crate::{json, AddressedProgram};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// How many instructions the report lists as hottest.
+pub const HOTTEST: usize = 10;
+
+/// Execution counts for one run of a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    /// Times the instruction at each text address executed.
+    pub counts: Vec<usize>,
+    pub steps: usize,
+    /// Estimated clock cycles, from [`CpuSpec::cycles`](crate::cpu::CpuSpec::cycles).
+    pub cycles: u64,
+    /// Cycles the instruction at each text address takes.
+    costs: Vec<u32>,
+}
+
+impl Profile {
+    /// An empty profile for `program`, costing instructions by its CPU spec.
+    pub fn new(program: &AddressedProgram) -> Self {
+        Self {
+            counts: vec![0; program.text.len()],
+            steps: 0,
+            cycles: 0,
+            costs: program
+                .text
+                .iter()
+                .map(|instr| program.cpu.cycles(instr.mnemonic()))
+                .collect(),
+        }
+    }
+
+    /// Counts one executed instruction.
+    pub fn record(&mut self, step: &Step) {
+        self.counts[step.pc] += 1;
+        self.steps += 1;
+        self.cycles += u64::from(self.costs[step.pc]);
+    }
+
+    /// Each mnemonic that executed, with how many times and the cycles spent on it, most
+    /// frequent first.
+    pub fn mix(&self, program: &AddressedProgram) -> Vec<(&'static str, usize, u64)> {
+        let mut mix = BTreeMap::new();
+        for (pc, &count) in self.counts.iter().enumerate().filter(|(_, &n)| n > 0) {
+            let entry = mix.entry(program.text[pc].mnemonic()).or_insert((0, 0));
+            entry.0 += count;
+            entry.1 += count as u64 * u64::from(self.costs[pc]);
+        }
+        let mut mix: Vec<_> = mix
+            .into_iter()
+            .map(|(mnemonic, (count, cycles))| (mnemonic, count, cycles))
+            .collect();
+        mix.sort_by_key(|&(_, count, _)| Reverse(count));
+        mix
+    }
+
+    /// Up to `n` text addresses that executed, with their counts, most frequent first.
+    pub fn hottest(&self, n: usize) -> Vec<(usize, usize)> {
+        let mut hottest: Vec<_> = self
+            .counts
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        hottest.sort_by_key(|&(_, count)| Reverse(count));
+        hottest.truncate(n);
+        hottest
+    }
+
+    /// The profile as aligned tables, with the source of each hot instruction.
+    pub fn report(&self, program: &AddressedProgram, sources: &SourceMap) -> String {
+        let mut out = String::new();
+        writeln!(out, "{} steps, {} cycles", self.steps, self.cycles).unwrap();
+        writeln!(
+            out,
+            "\n{:<14} {:>8} {:>8}",
+            "instruction", "count", "cycles"
+        )
+        .unwrap();
+        for (mnemonic, count, cycles) in self.mix(program) {
+            writeln!(out, "{:<14} {:>8} {:>8}", mnemonic, count, cycles).unwrap();
+        }
+        writeln!(out, "\n{:<6} {:>8}  source", "pc", "count").unwrap();
+        for (pc, count) in self.hottest(HOTTEST) {
+            let (file, line, source) = source_line(program, sources, pc);
+            writeln!(
+                out,
+                "{:<6} {:>8}  {}:{}: {}",
+                format!("{:#04x}", pc),
+                count,
+                file,
+                line,
+                source
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    /// The profile as JSON:
+    ///
+    /// ```text
+    /// {
+    ///   "steps": 17,
+    ///   "cycles": 17,
+    ///   "mix": [{ "mnemonic", "count", "cycles" }, ...],
+    ///   "hottest": [{ "address", "count", "file", "line", "source" }, ...]
+    /// }
+    /// ```
+    pub fn to_json(&self, program: &AddressedProgram, sources: &SourceMap) -> String {
+        let mix: Vec<String> = self
+            .mix(program)
+            .into_iter()
+            .map(|(mnemonic, count, cycles)| {
+                format!(
+                    "    {{ \"mnemonic\": {}, \"count\": {}, \"cycles\": {} }}",
+                    json::string(mnemonic),
+                    count,
+                    cycles
+                )
+            })
+            .collect();
+        let hottest: Vec<String> = self
+            .hottest(HOTTEST)
+            .into_iter()
+            .map(|(pc, count)| {
+                let (file, line, source) = source_line(program, sources, pc);
+                format!(
+                    "    {{ \"address\": {}, \"count\": {}, \"file\": {}, \"line\": {}, \"source\": {} }}",
+                    pc,
+                    count,
+                    json::string(file),
+                    line,
+                    json::string(source)
+                )
+            })
+            .collect();
+        format!(
+            "{{\n  \"steps\": {},\n  \"cycles\": {},\n  \"mix\": {},\n  \"hottest\": {}\n}}\n",
+            self.steps,
+            self.cycles,
+            array(&mix),
+            array(&hottest)
+        )
+    }
+}
+
+/// The file, line, and source text of the instruction at `pc`.
+fn source_line<'s>(
+    program: &AddressedProgram,
+    sources: &'s SourceMap,
+    pc: usize,
+) -> (&'s str, usize, &'s str) {
+    let span = program.text_spans[pc].clone();
+    let (file, line, _) = sources.location(span.start);
+    (file, line, sources.text.get(span).unwrap_or("").trim())
+}
+
+/// A JSON array of already-indented elements, one per line.
+fn array(elements: &[String]) -> String {
+    if elements.is_empty() {
+        "[]".to_owned()
+    } else {
+        format!("[\n{}\n  ]", elements.join(",\n"))
+    }
+}
@@ -0,0 +1,166 @@
+//! Saved emulator state, for resuming long runs with `run --snapshot-at` and `run --resume`.
+//!
+//! A snapshot is a short text file:
+//!
+//! ```text
+//! one-address snapshot 1
+//! program 5a1c0e7d3b29f604
+//! ac 002a
+//! pc 12
+//! steps 100000
+//! prefix -
+//! data 256
+//! 0005 0000 ffff ...
+//! ```
+//!
+//! `program` is [`program_hash`] of the program the machine was running, `prefix` is the
+//! high byte a `prefix` instruction left for the next branch, or `-`, and the data words
+//! follow in hex, sixteen to a line.
+//!
+//! ```
+//! use single_address_assembler::assemble;
+//! use single_address_assembler::emulator::Machine;
+//! use single_address_assembler::snapshot::MachineState;
+//!
+//! let program = assemble(".data .label n 3 .text .label top clac add n subi 1 stor n beqz end br top .label end halt").unwrap();
+//! let mut machine = Machine::new(&program);
+//! machine.run(4).unwrap_err();
+//! let saved = machine.snapshot().to_string();
+//!
+//! let mut resumed = Machine::new(&program);
+//! resumed.restore(&saved.parse::<MachineState>().unwrap()).unwrap();
+//! assert_eq!((resumed.pc, resumed.steps, resumed.read(0)), (4, 4, 2));
+//! resumed.run(100).unwrap();
+//! machine.run(100).unwrap();
+//! assert_eq!(resumed.steps, machine.steps);
+//!
+//! let other = assemble(".text clac halt").unwrap();
+//! let err = Machine::new(&other).restore(&saved.parse().unwrap()).unwrap_err();
+//! assert_eq!(err, "the snapshot was taken from a different program");
+//! ```
+
+use crate::AddressedProgram;
+use std::fmt;
+use std::str::FromStr;
+
+/// Version written on the first line of a snapshot; bump on incompatible changes.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Everything needed to continue a run where it left off.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachineState {
+    /// [`program_hash`] of the program being run.
+    pub program: u64,
+    pub ac: i16,
+    pub pc: usize,
+    pub steps: usize,
+    pub prefix: Option<u8>,
+    pub data: Vec<i16>,
+}
+
+/// A fingerprint of a program's encoded text and initial data, so a snapshot is only
+/// restored into the program it came from. This is 64-bit FNV-1a, which is stable across
+/// builds, unlike the standard library's hasher.
+pub fn program_hash(program: &AddressedProgram) -> u64 {
+    let words = program
+        .text_words()
+        .into_iter()
+        .chain(Some(0xffff))
+        .chain(program.data.iter().map(|&word| word as u16));
+    fnv1a(words.flat_map(u16::to_be_bytes))
+}
+
+/// 64-bit FNV-1a of `bytes`.
+pub(crate) fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+impl fmt::Display for MachineState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "one-address snapshot {}", SNAPSHOT_VERSION)?;
+        writeln!(f, "program {:016x}", self.program)?;
+        writeln!(f, "ac {:04x}", self.ac as u16)?;
+        writeln!(f, "pc {}", self.pc)?;
+        writeln!(f, "steps {}", self.steps)?;
+        match self.prefix {
+            Some(high) => writeln!(f, "prefix {:02x}", high)?,
+            None => writeln!(f, "prefix -")?,
+        }
+        writeln!(f, "data {}", self.data.len())?;
+        for row in self.data.chunks(16) {
+            let words: Vec<String> = row.iter().map(|&w| format!("{:04x}", w as u16)).collect();
+            writeln!(f, "{}", words.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MachineState {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().enumerate();
+        let mut field = |name: &str| -> Result<(usize, &str), String> {
+            let (index, line) = lines
+                .next()
+                .ok_or_else(|| format!("the snapshot ends before its `{}` line", name))?;
+            line.strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix(' '))
+                .map(|value| (index + 1, value.trim()))
+                .ok_or_else(|| format!("line {}: expected `{} ...`", index + 1, name))
+        };
+        let invalid =
+            |line: usize, value: &str| format!("line {}: `{}` is not valid here", line, value);
+
+        let (line, version) = field("one-address snapshot")?;
+        if version != SNAPSHOT_VERSION.to_string() {
+            return Err(format!(
+                "line {}: snapshot version {} is not supported; expected {}",
+                line, version, SNAPSHOT_VERSION
+            ));
+        }
+        let (line, value) = field("program")?;
+        let program = u64::from_str_radix(value, 16).map_err(|_| invalid(line, value))?;
+        let (line, value) = field("ac")?;
+        let ac = u16::from_str_radix(value, 16).map_err(|_| invalid(line, value))? as i16;
+        let (line, value) = field("pc")?;
+        let pc = value.parse().map_err(|_| invalid(line, value))?;
+        let (line, value) = field("steps")?;
+        let steps = value.parse().map_err(|_| invalid(line, value))?;
+        let (line, value) = field("prefix")?;
+        let prefix = match value {
+            "-" => None,
+            _ => Some(u8::from_str_radix(value, 16).map_err(|_| invalid(line, value))?),
+        };
+        let (line, value) = field("data")?;
+        let len: usize = value.parse().map_err(|_| invalid(line, value))?;
+
+        let mut data = Vec::with_capacity(len);
+        for (index, line) in lines {
+            for word in line.split_whitespace() {
+                let value = u16::from_str_radix(word, 16).map_err(|_| invalid(index + 1, word))?;
+                data.push(value as i16);
+            }
+        }
+        if data.len() != len {
+            return Err(format!(
+                "the snapshot has {} data words, but its `data` line says {}",
+                data.len(),
+                len
+            ));
+        }
+        Ok(Self {
+            program,
+            ac,
+            pc,
+            steps,
+            prefix,
+            data,
+        })
+    }
+}
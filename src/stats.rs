@@ -0,0 +1,74 @@
+use super::AddressedProgram;
+use std::fmt;
+
+/// Names for each opcode produced by `AddressedInstruction::opcode()`.
+const OPCODE_NAMES: [&str; 10] = [
+    "noop",
+    "alu immediate",
+    "alu memory",
+    "clac",
+    "stor",
+    "beqz",
+    "br",
+    "prefix",
+    "alu indirect",
+    "stor indirect",
+];
+
+/// Size and instruction-mix statistics for an assembled program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stats {
+    /// Words of text, counting the `prefix` of each long branch.
+    pub instructions: usize,
+    /// Words of text the CPU has room for.
+    pub text_words: usize,
+    pub data_words: usize,
+    /// Words of data the CPU has room for.
+    pub data_capacity: usize,
+    /// Number of instructions with each opcode, indexed by opcode.
+    pub opcodes: [usize; 16],
+}
+
+impl Stats {
+    pub fn new(program: &AddressedProgram) -> Self {
+        let mut opcodes = [0; 16];
+        for instr in &program.text {
+            opcodes[usize::from(instr.opcode())] += 1;
+        }
+
+        Stats {
+            instructions: program.text.len(),
+            text_words: program.cpu.text_words(),
+            data_words: program.data.len(),
+            data_capacity: program.cpu.data_words(),
+            opcodes,
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "instructions: {:>3} ({} of {} remaining)",
+            self.instructions,
+            self.text_words - self.instructions,
+            self.text_words
+        )?;
+        writeln!(
+            f,
+            "data words:   {:>3} ({} of {} remaining)",
+            self.data_words,
+            self.data_capacity - self.data_words,
+            self.data_capacity
+        )?;
+        write!(f, "by opcode:")?;
+        for (opcode, count) in self.opcodes.iter().enumerate() {
+            if *count > 0 {
+                let name = OPCODE_NAMES.get(opcode).copied().unwrap_or("unknown");
+                write!(f, "\n  {:x} {:<14} {:>3}", opcode, name, count)?;
+            }
+        }
+        Ok(())
+    }
+}
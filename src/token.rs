@@ -8,8 +8,18 @@ impl fmt::Display for Token<'_> {
             Self::Data => write!(f, ".data"),
             Self::Label => write!(f, ".label"),
             Self::Number => write!(f, ".number"),
+            Self::JumpTable => write!(f, ".jumptable"),
+            Self::Equ => write!(f, ".equ"),
+            Self::Alias => write!(f, ".alias"),
+            Self::IncBin => write!(f, ".incbin"),
+            Self::Csv => write!(f, ".csv"),
+            Self::String => write!(f, ".string"),
+            Self::StringZ => write!(f, ".stringz"),
+            Self::StringLiteral(path) => write!(f, "\"{}\"", path),
             Self::NumLiteral(i) => write!(f, "{}", i),
             Self::LabelIdent(label) => write!(f, "{}", label),
+            Self::AddressLiteral(literal) => write!(f, "{}", literal),
+            Self::TextAddress(label) => write!(f, "@{}", label),
             Self::Add => write!(f, "add"),
             Self::AddImmediate => write!(f, "addi"),
             Self::Subtract => write!(f, "sub"),
@@ -21,6 +31,8 @@ impl fmt::Display for Token<'_> {
             Self::Remainder => write!(f, "rem"),
             Self::RemainderImmediate => write!(f, "remi"),
             Self::Shift => write!(f, "shift"),
+            Self::ShiftLeft => write!(f, "shl"),
+            Self::ShiftRight => write!(f, "shr"),
             Self::And => write!(f, "and"),
             Self::AndImmediate => write!(f, "andi"),
             Self::BranchZero => write!(f, "beqz"),
@@ -28,12 +40,125 @@ impl fmt::Display for Token<'_> {
             Self::ClearAc => write!(f, "clac"),
             Self::Store => write!(f, "stor"),
             Self::NoOp => write!(f, "noop"),
+            Self::Halt => write!(f, "halt"),
+            Self::LBracket => write!(f, "["),
+            Self::RBracket => write!(f, "]"),
+            Self::LParen => write!(f, "("),
+            Self::RParen => write!(f, ")"),
+            Self::Plus => write!(f, "+"),
+            Self::Minus => write!(f, "-"),
+            Self::Star => write!(f, "*"),
+            Self::Slash => write!(f, "/"),
+            Self::Percent => write!(f, "%"),
+            Self::ShiftLeftOp => write!(f, "<<"),
+            Self::ShiftRightOp => write!(f, ">>"),
+            Self::Ampersand => write!(f, "&"),
+            Self::Caret => write!(f, "^"),
+            Self::Pipe => write!(f, "|"),
+            Self::Tilde => write!(f, "~"),
             Self::Error => write!(f, "Error"),
         }
     }
 }
 
-#[derive(Logos, Debug, PartialEq, Clone)]
+impl<'a> Token<'a> {
+    /// Turns identifiers that are unsigned decimal or `0x` hex literals into `NumLiteral`s, or
+    /// `Error` if they do not fit in an `i64`; the parser checks the 16-bit range, so that
+    /// it can say a literal is too large. The lexer leaves these to the parser because rules that
+    /// overlap `LabelIdent` make the generated lexer recurse once per character, which
+    /// overflows the stack on long digit strings.
+    pub fn classify(self) -> Self {
+        let ident = match self {
+            Self::LabelIdent(ident) => ident,
+            other => return other,
+        };
+        let parsed = if ident.bytes().all(|b| b.is_ascii_digit()) {
+            ident.parse()
+        } else {
+            match ident.strip_prefix("0x") {
+                Some(hex)
+                    if !hex.is_empty()
+                        && hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) =>
+                {
+                    i64::from_str_radix(hex, 16)
+                }
+                _ => return self,
+            }
+        };
+        parsed.map_or(Self::Error, Self::NumLiteral)
+    }
+
+    /// Whether this is one of the [`MNEMONICS`].
+    pub fn is_mnemonic(&self) -> bool {
+        matches!(
+            self,
+            Self::Add
+                | Self::AddImmediate
+                | Self::Subtract
+                | Self::SubtractImmediate
+                | Self::Multiply
+                | Self::MultiplyImmediate
+                | Self::Divide
+                | Self::DivideImmediate
+                | Self::Remainder
+                | Self::RemainderImmediate
+                | Self::Shift
+                | Self::ShiftLeft
+                | Self::ShiftRight
+                | Self::And
+                | Self::AndImmediate
+                | Self::BranchZero
+                | Self::Branch
+                | Self::ClearAc
+                | Self::Store
+                | Self::NoOp
+                | Self::Halt
+        )
+    }
+
+    /// Whether this is a directive such as `.text` or `.equ`.
+    pub fn is_directive(&self) -> bool {
+        matches!(
+            self,
+            Self::Text
+                | Self::Data
+                | Self::Label
+                | Self::Number
+                | Self::JumpTable
+                | Self::Equ
+                | Self::Alias
+                | Self::IncBin
+                | Self::Csv
+                | Self::String
+                | Self::StringZ
+        )
+    }
+
+    /// The token for `name` if it is one of the [`MNEMONICS`].
+    pub fn from_mnemonic(name: &str) -> Option<Token<'static>> {
+        let index = MNEMONICS.iter().position(|&mnemonic| mnemonic == name)?;
+        let mut lexer = Token::lexer(MNEMONICS[index]);
+        lexer.next().filter(Token::is_mnemonic)
+    }
+}
+
+/// The value of a negative literal such as `-12` or `-0x1f`.
+fn negative_literal<'a>(lex: &mut logos::Lexer<'a, Token<'a>>) -> Option<i64> {
+    let magnitude = &lex.slice()[1..];
+    let value = match magnitude.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16),
+        None => magnitude.parse(),
+    };
+    value.ok().map(|value| -value)
+}
+
+/// Every instruction mnemonic the lexer recognizes.
+pub const MNEMONICS: &[&str] = &[
+    "add", "addi", "sub", "subi", "mul", "muli", "div", "divi", "rem", "remi", "shift", "shl",
+    "shr", "and", "andi", "beqz", "br", "clac", "stor", "noop", "halt",
+];
+
+#[derive(Logos, Debug, PartialEq, Clone, Copy)]
 pub enum Token<'a> {
     // Sections
     #[token(".text")]
@@ -44,14 +169,41 @@ pub enum Token<'a> {
     Label,
     #[token(".number")]
     Number,
+    #[token(".jumptable")]
+    JumpTable,
+    #[token(".equ")]
+    Equ,
+    #[token(".alias")]
+    Alias,
+    #[token(".incbin")]
+    IncBin,
+    #[token(".csv")]
+    Csv,
+    #[token(".string")]
+    String,
+    #[token(".stringz")]
+    StringZ,
+
+    /// A double-quoted string on one line, without its quotes.
+    #[regex(r#""[^"\n]*""#, |lex| { let s = lex.slice(); &s[1..s.len() - 1] })]
+    StringLiteral(&'a str),
 
-    #[regex("[0-9]+", |lex| i16::from_str_radix(lex.slice(), 10).ok(), priority=2)]
-    #[regex("0x[0-9a-f]+", |lex| i16::from_str_radix(&lex.slice()[2..], 16).ok())]
-    NumLiteral(i16),
+    /// Only negative literals, decimal or `0x` hex, are lexed directly; see
+    /// [`Token::classify`] for the rest.
+    #[regex("-[0-9]+|-0x[0-9a-f]+", negative_literal)]
+    NumLiteral(i64),
 
     #[regex("[_a-zA-Z0-9]+")]
     LabelIdent(&'a str),
 
+    /// An absolute operand address, `@N` or `@0xN`, in place of a label.
+    #[regex("@[0-9]+|@0x[0-9a-f]+")]
+    AddressLiteral(&'a str),
+
+    /// `@label` in a data value: the text address of `label`.
+    #[regex("@[_a-zA-Z][_a-zA-Z0-9]*", |lex| &lex.slice()[1..])]
+    TextAddress(&'a str),
+
     // mnemonics
     #[token("add")]
     Add,
@@ -75,6 +227,10 @@ pub enum Token<'a> {
     RemainderImmediate,
     #[token("shift")]
     Shift,
+    #[token("shl")]
+    ShiftLeft,
+    #[token("shr")]
+    ShiftRight,
     #[token("and")]
     And,
     #[token("andi")]
@@ -90,9 +246,46 @@ pub enum Token<'a> {
     Store,
     #[token("noop")]
     NoOp,
+    #[token("halt")]
+    Halt,
+
+    /// Brackets around a data operand, `add [ptr]`, make it indirect.
+    #[token("[")]
+    LBracket,
+    #[token("]")]
+    RBracket,
+
+    // Operators in constant expressions. A `-` directly followed by digits lexes as a
+    // negative `NumLiteral`; see `Parser::parse_value` for how that is told apart.
+    #[token("(")]
+    LParen,
+    #[token(")")]
+    RParen,
+    #[token("+")]
+    Plus,
+    #[token("-")]
+    Minus,
+    #[token("*")]
+    Star,
+    #[token("/")]
+    Slash,
+    #[token("%")]
+    Percent,
+    #[token("<<")]
+    ShiftLeftOp,
+    #[token(">>")]
+    ShiftRightOp,
+    #[token("&")]
+    Ampersand,
+    #[token("^")]
+    Caret,
+    #[token("|")]
+    Pipe,
+    #[token("~")]
+    Tilde,
 
     #[error]
-    #[regex("[ \t\n\r]+", logos::skip)]
+    #[regex("[ \t\n\r\u{feff}]+", logos::skip)]
     #[regex("#.*", logos::skip)]
     Error,
 }
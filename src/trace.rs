@@ -0,0 +1,86 @@
+//! Per-instruction execution traces for `run --trace`.
+
+use crate::emulator::Step;
+use std::io::{self, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// Aligned columns for reading in a terminal.
+    Text,
+    /// Comma-separated values with a header row, for spreadsheets.
+    Csv,
+}
+
+impl TraceFormat {
+    /// The format named by `--trace-format`.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "csv" => Self::Csv,
+            _ => Self::Text,
+        }
+    }
+}
+
+/// Writes whatever precedes the first step: the column names for CSV, nothing for text.
+pub fn write_header<W: Write>(out: &mut W, format: TraceFormat) -> io::Result<()> {
+    match format {
+        TraceFormat::Text => Ok(()),
+        TraceFormat::Csv => writeln!(
+            out,
+            "step,pc,instr,ac_before,ac_after,write_address,write_old,write_new"
+        ),
+    }
+}
+
+/// Writes one line describing `step`.
+///
+/// ```
+/// use single_address_assembler::assemble;
+/// use single_address_assembler::emulator::{Machine, StepResult};
+/// use single_address_assembler::trace::{write_step, TraceFormat};
+///
+/// let program = assemble(".data .label x .number 2 .text addi 5 stor x halt").unwrap();
+/// let mut machine = Machine::new(&program);
+/// let mut out = Vec::new();
+/// while let Ok(StepResult::Executed(step)) = machine.step() {
+///     write_step(&mut out, &step, TraceFormat::Csv).unwrap();
+/// }
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "1,0,addi 5,0,5,,,\n2,1,stor @0x0,5,5,0,2,5\n"
+/// );
+/// ```
+pub fn write_step<W: Write>(out: &mut W, step: &Step, format: TraceFormat) -> io::Result<()> {
+    match format {
+        TraceFormat::Text => {
+            write!(
+                out,
+                "{:>8}  {:02x}  {:<16}  ac {:>6} -> {:>6}",
+                step.step,
+                step.pc,
+                step.instr.to_string(),
+                step.ac_before,
+                step.ac_after
+            )?;
+            if let Some(write) = step.write {
+                write!(
+                    out,
+                    "  [{:#04x}] {} -> {}",
+                    write.address, write.old, write.new
+                )?;
+            }
+            writeln!(out)
+        }
+        TraceFormat::Csv => {
+            write!(
+                out,
+                "{},{},{},{},{},",
+                step.step, step.pc, step.instr, step.ac_before, step.ac_after
+            )?;
+            match step.write {
+                Some(write) => writeln!(out, "{},{},{}", write.address, write.old, write.new),
+                None => writeln!(out, ",,"),
+            }
+        }
+    }
+}
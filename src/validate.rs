@@ -0,0 +1,128 @@
+//! Consistency checks on an assembled program that the parser cannot make on its own.
+
+use crate::diagnostic::Severity;
+use crate::{Address, AddressedInstruction, AddressedProgram, Span};
+use std::fmt;
+
+/// A problem found by [`AddressedProgram::validate`]. The `usize` in each variant is the
+/// index of the offending instruction in `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// A branch whose target, including any `prefix` in front of it, is not the address of
+    /// any instruction.
+    BranchPastEnd(usize, usize),
+    /// A memory operand whose address is not one of the defined data words.
+    UndefinedData(usize, Address),
+    /// An instruction that decodes to something else once packed into a word.
+    Unencodable(usize, AddressedInstruction),
+}
+
+impl ValidationIssue {
+    /// How serious the issue is when not running in strict mode.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::BranchPastEnd(..) | Self::UndefinedData(..) => Severity::Warning,
+            Self::Unencodable(..) => Severity::Error,
+        }
+    }
+
+    /// The index in `text` of the instruction the issue is about.
+    pub fn index(&self) -> usize {
+        match self {
+            Self::BranchPastEnd(index, _)
+            | Self::UndefinedData(index, _)
+            | Self::Unencodable(index, _) => *index,
+        }
+    }
+
+    /// The source span of the offending instruction, if `program` has spans.
+    pub fn span(&self, program: &AddressedProgram) -> Option<Span> {
+        program.text_spans.get(self.index()).cloned()
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::BranchPastEnd(_, target) => write!(
+                f,
+                "branch target {:#04x} is past the last instruction",
+                target
+            ),
+            Self::UndefinedData(_, address) => write!(
+                f,
+                "address {:#04x} is not one of the defined data words",
+                address
+            ),
+            Self::Unencodable(_, instr) => {
+                write!(f, "`{}` does not survive encoding as a word", instr)
+            }
+        }
+    }
+}
+
+impl AddressedProgram {
+    /// Checks every instruction against the program's layout: branch targets must be
+    /// instructions, memory operands must be defined data words, and each instruction must
+    /// decode back to itself from its encoded word.
+    ///
+    /// ```
+    /// use single_address_assembler::validate::ValidationIssue;
+    /// use single_address_assembler::{AddressedInstruction::*, AddressedProgram};
+    ///
+    /// let program = AddressedProgram {
+    ///     text: vec![Add(0), Store(5), BranchZero(0), Branch(4)],
+    ///     data: vec![7],
+    ///     symbols: vec![],
+    ///     text_spans: vec![],
+    ///     data_spans: vec![],
+    ///     text_base: 0,
+    ///     data_base: 0,
+    ///     code_addresses: vec![],
+    ///     synthesized: vec![],
+    ///     cpu: Default::default(),
+    /// };
+    /// assert_eq!(
+    ///     program.validate(),
+    ///     vec![ValidationIssue::UndefinedData(1, 5), ValidationIssue::BranchPastEnd(3, 4)]
+    /// );
+    ///
+    /// let moved = AddressedProgram { text_base: 2, data_base: 5, ..program };
+    /// assert_eq!(
+    ///     moved.validate(),
+    ///     vec![
+    ///         ValidationIssue::UndefinedData(0, 0),
+    ///         ValidationIssue::BranchPastEnd(2, 0),
+    ///     ]
+    /// );
+    /// ```
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let text = self.text_base..self.text_base + self.text.len();
+        let data = self.data_base..self.data_base + self.data.len();
+
+        let mut issues = Vec::new();
+        let mut high = 0;
+        for (index, instr) in self.text.iter().enumerate() {
+            match self.cpu.try_encode(instr) {
+                Ok(bytes) if self.cpu.decode(u16::from_be_bytes(bytes)) == Ok(*instr) => {}
+                _ => issues.push(ValidationIssue::Unencodable(index, *instr)),
+            }
+            if let Some(low) = instr.branch_target() {
+                let target = high | usize::from(low);
+                if !text.contains(&target) {
+                    issues.push(ValidationIssue::BranchPastEnd(index, target));
+                }
+            }
+            high = match instr {
+                AddressedInstruction::Prefix(byte) => usize::from(*byte) << 8,
+                _ => 0,
+            };
+            if let Some(address) = instr.data_address() {
+                if !data.contains(&usize::from(address)) {
+                    issues.push(ValidationIssue::UndefinedData(index, address));
+                }
+            }
+        }
+        issues
+    }
+}
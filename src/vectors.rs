@@ -0,0 +1,339 @@
+//! Logisim-evolution test vectors from an emulator run, for the `vectors` subcommand.
+//!
+//! A pin file says which piece of machine state each of the circuit's output pins shows.
+//! It is a small TOML file with one `[[pin]]` table per column, in order:
+//!
+//! ```toml
+//! every = 1            # steps between rows; optional
+//!
+//! [[pin]]
+//! name = "PC"
+//! state = "pc"         # `pc`, `ac`, `mem:LABEL`, or `mem:ADDRESS`
+//! width = 8            # optional; 8 for `pc`, otherwise 16
+//!
+//! [[pin]]
+//! name = "ACC"
+//! state = "ac"
+//! ```
+//!
+//! The vector file has a row for the starting state, one after every `every` steps, and
+//! one for the state the program stops in, each value written in binary:
+//!
+//! ```
+//! use single_address_assembler::emulator::Machine;
+//! use single_address_assembler::vectors::{self, PinFile};
+//! use single_address_assembler::assemble;
+//!
+//! let program = assemble(".text clac addi 5 addi 2 halt").unwrap();
+//! let pins = PinFile::parse(
+//!     "[[pin]]\nname = \"PC\"\nstate = \"pc\"\nwidth = 4\n\n\
+//!      [[pin]]\nname = \"ACC\"\nstate = \"ac\"\nwidth = 8\n",
+//! )
+//! .unwrap();
+//! let rows = vectors::record(&mut Machine::new(&program), &program, &pins, 100).unwrap();
+//! assert_eq!(
+//!     vectors::render(&pins, &rows),
+//!     "PC[4] ACC[8]\n0000 00000000\n0001 00000000\n0010 00000101\n0011 00000111\n"
+//! );
+//!
+//! let err = PinFile::parse("[[pin]]\nname = \"PC\"\nstate = \"ir\"\n").unwrap_err();
+//! assert_eq!(
+//!     err.to_string(),
+//!     "line 3: `ir` is not `pc`, `ac`, `mem:LABEL`, or `mem:ADDRESS`"
+//! );
+//! ```
+
+use crate::emulator::{Machine, StepResult, Trap};
+use crate::grade::{parse_number, Expectation, Location};
+use crate::AddressedProgram;
+use std::fmt;
+use std::fmt::Write;
+
+/// The machine state an output pin shows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum State {
+    /// The address of the next instruction, including the text base.
+    Pc,
+    /// The accumulator, or a data word.
+    Value(Location),
+}
+
+/// One column of the vector file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pin {
+    pub name: String,
+    pub state: State,
+    pub width: u32,
+}
+
+/// The columns of a vector file and how often to write a row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinFile {
+    pub pins: Vec<Pin>,
+    /// Steps between rows.
+    pub every: usize,
+}
+
+/// A problem in a pin file, with the 1-based line it was found on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinFileError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for PinFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// A `[[pin]]` table as read, before its keys are checked.
+#[derive(Default)]
+struct PinTable {
+    line: usize,
+    name: Option<String>,
+    state: Option<State>,
+    width: Option<u32>,
+}
+
+impl PinFile {
+    /// Reads a file in the format described in the [module docs](self).
+    pub fn parse(contents: &str) -> Result<Self, PinFileError> {
+        let mut every = 1;
+        let mut tables: Vec<PinTable> = Vec::new();
+        for (index, line) in contents.lines().enumerate() {
+            let line_number = index + 1;
+            let error = |message: String| PinFileError {
+                line: line_number,
+                message,
+            };
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') {
+                if line != "[[pin]]" {
+                    return Err(error(format!(
+                        "unknown table `{}`; each pin is a `[[pin]]` table",
+                        line
+                    )));
+                }
+                tables.push(PinTable {
+                    line: line_number,
+                    ..PinTable::default()
+                });
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error(format!("`{}` is not of the form key = value", line)))?;
+            let (key, value) = (key.trim(), value.trim());
+            let table = match tables.last_mut() {
+                Some(table) => table,
+                None if key == "every" => {
+                    every = parse_integer(value)
+                        .filter(|&every| every > 0)
+                        .ok_or_else(|| error(format!("`{}` is not a positive step count", value)))?
+                        as usize;
+                    continue;
+                }
+                None => return Err(error(format!("unknown key `{}`", key))),
+            };
+            match key {
+                "name" => table.name = Some(parse_string(value).map_err(error)?),
+                "state" => {
+                    let spec = parse_string(value).map_err(error)?;
+                    table.state = Some(parse_state(&spec).map_err(error)?);
+                }
+                "width" => {
+                    table.width = Some(
+                        parse_integer(value)
+                            .filter(|width| (1..=16).contains(width))
+                            .ok_or_else(|| {
+                                error(format!("`{}` is not a pin width from 1 to 16", value))
+                            })? as u32,
+                    )
+                }
+                _ => {
+                    return Err(error(format!(
+                        "unknown pin key `{}`; expected `name`, `state`, or `width`",
+                        key
+                    )))
+                }
+            }
+        }
+
+        let mut pins = Vec::new();
+        for table in tables {
+            let line = table.line;
+            let missing = |key: &str| PinFileError {
+                line,
+                message: format!("this pin has no `{}`", key),
+            };
+            let name = table.name.ok_or_else(|| missing("name"))?;
+            let state = table.state.ok_or_else(|| missing("state"))?;
+            let width = table.width.unwrap_or(match state {
+                State::Pc => 8,
+                State::Value(_) => 16,
+            });
+            pins.push(Pin { name, state, width });
+        }
+        if pins.is_empty() {
+            return Err(PinFileError {
+                line: 1,
+                message: "the file has no `[[pin]]` tables".to_owned(),
+            });
+        }
+        Ok(Self { pins, every })
+    }
+}
+
+/// `line` up to a `#` that is not inside a string.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// A double-quoted string value; pin files have no need for escapes.
+fn parse_string(value: &str) -> Result<String, String> {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .filter(|value| !value.contains('"') && !value.contains('\\'))
+        .map(str::to_owned)
+        .ok_or_else(|| format!("`{}` is not a quoted string", value))
+}
+
+fn parse_integer(value: &str) -> Option<i64> {
+    parse_number(&value.replace('_', ""))
+}
+
+/// The state named by `pc`, `ac`, `mem:LABEL`, or `mem:ADDRESS`.
+fn parse_state(spec: &str) -> Result<State, String> {
+    if spec == "pc" {
+        return Ok(State::Pc);
+    }
+    // Reuse the `grade` location syntax by giving it a placeholder value.
+    Some(format!("{}=0", spec))
+        .filter(|_| !spec.contains('='))
+        .and_then(|spec| spec.parse::<Expectation>().ok())
+        .map(|expectation| State::Value(expectation.location))
+        .ok_or_else(|| {
+            format!(
+                "`{}` is not `pc`, `ac`, `mem:LABEL`, or `mem:ADDRESS`",
+                spec
+            )
+        })
+}
+
+/// Why a run could not be recorded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordError {
+    /// The program trapped, or was still running at the step limit.
+    Trap(Trap),
+    /// A pin's state could not be read or does not fit its width.
+    Pin(String),
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Trap(trap) => write!(f, "{}", trap),
+            Self::Pin(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Runs `machine` to a stop, reading every pin at the start, after every `every` steps,
+/// and at the end. Each row holds one value per pin.
+pub fn record(
+    machine: &mut Machine,
+    program: &AddressedProgram,
+    pins: &PinFile,
+    max_steps: usize,
+) -> Result<Vec<Vec<u16>>, RecordError> {
+    let mut rows = vec![read_pins(machine, program, pins)?];
+    loop {
+        match machine.step_within(max_steps).map_err(RecordError::Trap)? {
+            StepResult::Executed(_) => {
+                if machine.steps.is_multiple_of(pins.every) {
+                    rows.push(read_pins(machine, program, pins)?);
+                }
+            }
+            StepResult::Stopped(_) => {
+                if !machine.steps.is_multiple_of(pins.every) {
+                    rows.push(read_pins(machine, program, pins)?);
+                }
+                return Ok(rows);
+            }
+        }
+    }
+}
+
+/// The value on each pin for the current state of `machine`.
+fn read_pins(
+    machine: &Machine,
+    program: &AddressedProgram,
+    pins: &PinFile,
+) -> Result<Vec<u16>, RecordError> {
+    pins.pins
+        .iter()
+        .map(|pin| {
+            let value = match &pin.state {
+                State::Pc => {
+                    let pc = program.text_base + machine.pc;
+                    if pc >> pin.width != 0 {
+                        return Err(RecordError::Pin(format!(
+                            "pc {:#04x} does not fit in the {}-bit pin `{}`",
+                            pc, pin.width, pin.name
+                        )));
+                    }
+                    pc as u16
+                }
+                State::Value(location) => {
+                    let expectation = Expectation {
+                        location: location.clone(),
+                        expected: 0,
+                    };
+                    let value = expectation
+                        .actual(machine, program)
+                        .map_err(|err| RecordError::Pin(format!("pin `{}`: {}", pin.name, err)))?;
+                    // A pin narrower than a word shows its low bits.
+                    value as u16 & mask(pin.width)
+                }
+            };
+            Ok(value)
+        })
+        .collect()
+}
+
+fn mask(width: u32) -> u16 {
+    (u32::from(u16::MAX) >> (16 - width)) as u16
+}
+
+/// The vector file: a header naming each pin and its width, then the rows in binary.
+pub fn render(pins: &PinFile, rows: &[Vec<u16>]) -> String {
+    let mut out = String::new();
+    let header: Vec<String> = pins
+        .pins
+        .iter()
+        .map(|pin| format!("{}[{}]", pin.name, pin.width))
+        .collect();
+    writeln!(out, "{}", header.join(" ")).unwrap();
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&pins.pins)
+            .map(|(value, pin)| format!("{:0width$b}", value, width = pin.width as usize))
+            .collect();
+        writeln!(out, "{}", cells.join(" ")).unwrap();
+    }
+    out
+}
@@ -0,0 +1,195 @@
+//! Checks that the encoder and decoder agree, for the `verify` subcommand: a program's text
+//! words are decoded, written out as assembly, and assembled again, and the result must
+//! match the original word for word.
+//!
+//! ```
+//! use single_address_assembler::{assemble, verify};
+//!
+//! let program = assemble(
+//!     ".data .label x 5 .text .label top clac add x shl 2 shr 1 stor x beqz top halt",
+//! )
+//! .unwrap();
+//! assert!(verify::round_trip(&program).is_ok());
+//!
+//! let mut broken = program.clone();
+//! broken.text.push(single_address_assembler::AddressedInstruction::Prefix(1));
+//! let err = verify::round_trip(&broken).unwrap_err();
+//! assert_eq!(err.address(), 7);
+//! assert_eq!(err.to_string(), "word 7001 at 0x07 does not decode: invalid opcode 0x7");
+//! ```
+
+use crate::{AddressedInstruction, AddressedProgram, DecodeError, Parser};
+use std::fmt;
+
+/// The first place a program's text failed to survive the round trip.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Divergence {
+    /// The word at `address` does not decode.
+    Undecodable {
+        address: usize,
+        word: u16,
+        error: DecodeError,
+    },
+    /// The disassembly of the instruction at `address` does not assemble.
+    Unparsable {
+        address: usize,
+        line: String,
+        error: String,
+    },
+    /// The reassembled word at `address` differs, or is missing.
+    Mismatch {
+        address: usize,
+        line: String,
+        original: u16,
+        reassembled: Option<u16>,
+    },
+}
+
+impl Divergence {
+    /// The text address of the original instruction responsible.
+    pub fn address(&self) -> usize {
+        match self {
+            Self::Undecodable { address, .. }
+            | Self::Unparsable { address, .. }
+            | Self::Mismatch { address, .. } => *address,
+        }
+    }
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Undecodable {
+                address,
+                word,
+                error,
+            } => write!(
+                f,
+                "word {:04x} at {:#04x} does not decode: {}",
+                word, address, error
+            ),
+            Self::Unparsable {
+                address,
+                line,
+                error,
+            } => write!(
+                f,
+                "`{}`, the disassembly of {:#04x}, does not assemble: {}",
+                line, address, error
+            ),
+            Self::Mismatch {
+                address,
+                line,
+                original,
+                reassembled: Some(reassembled),
+            } => write!(
+                f,
+                "`{}` at {:#04x} encodes as {:04x} but reassembles to {:04x}",
+                line, address, original, reassembled
+            ),
+            Self::Mismatch {
+                address,
+                line,
+                original,
+                reassembled: None,
+            } => write!(
+                f,
+                "`{}` at {:#04x} encodes as {:04x} but reassembles to nothing",
+                line, address, original
+            ),
+        }
+    }
+}
+
+/// Decodes, disassembles, and reassembles `program`'s text with its CPU spec, returning
+/// the first divergence from the original words.
+pub fn round_trip(program: &AddressedProgram) -> Result<(), Divergence> {
+    let words = program.text_words();
+    let (source, lines) = disassemble(&words, program)?;
+
+    let mut parser = Parser::new(&source);
+    parser.cpu = program.cpu.clone();
+    parser.options.allow_div_zero = true;
+    let reassembled = parser
+        .parse_program()
+        .and_then(|()| parser.address_program())
+        .map_err(|err| {
+            // Each line of `source` after the `.text` holds one instruction.
+            let index = source[..err.span().map_or(0, |span| span.start)]
+                .matches('\n')
+                .count()
+                .saturating_sub(1)
+                .min(lines.len() - 1);
+            let (address, line) = lines[index].clone();
+            Divergence::Unparsable {
+                address,
+                line,
+                error: err.to_string(),
+            }
+        })?
+        .text_words();
+
+    for (address, &original) in words.iter().enumerate() {
+        if reassembled.get(address) != Some(&original) {
+            // Blame the instruction a long branch's `prefix` belongs to.
+            let (_, line) = lines
+                .iter()
+                .rev()
+                .find(|(start, _)| *start <= address)
+                .unwrap()
+                .clone();
+            return Err(Divergence::Mismatch {
+                address,
+                line,
+                original,
+                reassembled: reassembled.get(address).copied(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Assembly for `words`, one instruction per line after `.text`, with the address and
+/// text of each line. A `prefix` and the branch after it become one long branch.
+fn disassemble(
+    words: &[u16],
+    program: &AddressedProgram,
+) -> Result<(String, Vec<(usize, String)>), Divergence> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    let mut prefix = None;
+    for (address, &word) in words.iter().enumerate() {
+        let instr = program
+            .cpu
+            .decode(word)
+            .map_err(|error| Divergence::Undecodable {
+                address,
+                word,
+                error,
+            })?;
+        if let Some((start, high)) = prefix.take() {
+            if instr.branch_target().is_some() {
+                let target = usize::from(high) << 8 | usize::from(instr.value());
+                lines.push((start, format!("{} @{:#x}", instr.mnemonic(), target)));
+                continue;
+            }
+            lines.push((start, AddressedInstruction::Prefix(high).to_string()));
+        }
+        match instr {
+            AddressedInstruction::Prefix(high) => prefix = Some((address, high)),
+            instr => lines.push((address, instr.to_string())),
+        }
+    }
+    if let Some((address, high)) = prefix {
+        lines.push((address, AddressedInstruction::Prefix(high).to_string()));
+    }
+    if lines.is_empty() {
+        return Ok((".text\n".to_owned(), vec![(0, String::new())]));
+    }
+
+    let mut source = ".text\n".to_owned();
+    for (_, line) in &lines {
+        source.push_str(line);
+        source.push('\n');
+    }
+    Ok((source, lines))
+}
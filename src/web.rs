@@ -0,0 +1,124 @@
+//! An entry point for embedding the assembler in a web page.
+//!
+//! [`assemble`] takes source text and returns a JSON document instead of Rust types, so only
+//! strings cross the boundary. It is exported to JavaScript with `wasm-bindgen`, so a page
+//! can load the package `wasm-pack build --target web -- --features wasm` produces, call
+//! `assemble(source)`, and `JSON.parse` the result. On success it holds the v2.0 raw text and data images, ready
+//! to offer as `.mc`/`.dat` downloads, along with the symbol table and any warnings:
+//!
+//! ```text
+//! { "ok": true, "text": "...", "data": "...",
+//!   "symbols": [{ "name", "section", "address" }, ...], "diagnostics": [...] }
+//! { "ok": false, "diagnostics": [...] }
+//! ```
+//!
+//! Each diagnostic is `{ "severity", "code", "message", "line", "column", "end_line",
+//! "end_column" }` with 1-based positions, or `null` positions when it has no location.
+
+use crate::diagnostic::line_col;
+use crate::image::{ImageOptions, OutputFormat};
+use crate::json::string;
+use crate::{Parser, Span};
+use std::fmt::Write;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// Assembles `source` and describes the outcome as JSON.
+///
+/// WebAssembly builds abort on a panic, so nothing here may panic: every input, however
+/// malformed, gives a JSON document. The `web` fuzz target checks this for parsing,
+/// addressing, and rendering the images alike.
+///
+/// ```
+/// use single_address_assembler::web::assemble;
+///
+/// let out = assemble(".text clac halt");
+/// assert!(out.starts_with("{\"ok\":true,\"text\":\"v2.0 raw\\n3000\\n6001\\n\""));
+///
+/// let out = assemble(".text br nowhere");
+/// assert!(out.starts_with("{\"ok\":false,\"diagnostics\":[{\"severity\":\"error\""));
+/// assert!(out.contains("\"line\":1,\"column\":10,\"end_line\":1,\"end_column\":17"));
+/// ```
+#[wasm_bindgen]
+pub fn assemble(source: &str) -> String {
+    let mut parser = Parser::new(source);
+    let result = parser
+        .parse_program()
+        .and_then(|()| parser.address_program());
+    let mut diagnostics: Vec<String> = parser
+        .warnings
+        .iter()
+        .map(|warning| diagnostic(source, "warning", warning.code(), warning, warning.span()))
+        .collect();
+
+    let program = match result {
+        Ok(program) => program,
+        Err(err) => {
+            if parser.errors.is_empty() {
+                parser.errors.push(err);
+            }
+            let errors = parser
+                .errors
+                .iter()
+                .map(|err| diagnostic(source, "error", "", err, err.span()));
+            diagnostics.splice(0..0, errors);
+            return format!(
+                "{{\"ok\":false,\"diagnostics\":[{}]}}",
+                diagnostics.join(",")
+            );
+        }
+    };
+
+    let options = ImageOptions::new(OutputFormat::V2Raw);
+    let image = |bytes: Vec<u8>| string(&String::from_utf8_lossy(&bytes));
+    let symbols: Vec<String> = program
+        .symbols
+        .iter()
+        .map(|symbol| {
+            format!(
+                "{{\"name\":{},\"section\":\"{}\",\"address\":{}}}",
+                string(&symbol.name),
+                symbol.section,
+                symbol.address
+            )
+        })
+        .collect();
+    format!(
+        "{{\"ok\":true,\"text\":{},\"data\":{},\"symbols\":[{}],\"diagnostics\":[{}]}}",
+        image(program.text_image(&options)),
+        image(program.data_image(&options)),
+        symbols.join(","),
+        diagnostics.join(",")
+    )
+}
+
+/// One diagnostic as a JSON object, with `span` converted to line and column numbers.
+fn diagnostic<M: ToString>(
+    source: &str,
+    severity: &str,
+    code: &str,
+    message: M,
+    span: Option<Span>,
+) -> String {
+    let mut out = format!(
+        "{{\"severity\":\"{}\",\"code\":{},\"message\":{}",
+        severity,
+        string(code),
+        string(&message.to_string())
+    );
+    match span {
+        Some(span) => {
+            let (line, column) = line_col(source, span.start);
+            let (end_line, end_column) = line_col(source, span.end);
+            write!(
+                out,
+                ",\"line\":{},\"column\":{},\"end_line\":{},\"end_column\":{}}}",
+                line, column, end_line, end_column
+            )
+            .unwrap();
+        }
+        None => {
+            out.push_str(",\"line\":null,\"column\":null,\"end_line\":null,\"end_column\":null}")
+        }
+    }
+    out
+}
@@ -0,0 +1,295 @@
+//! A cross-reference of every label: where it is defined and which instructions use it,
+//! and the map file listing each label's address and size.
+//!
+//! ```
+//! use single_address_assembler::diagnostic::SourceMap;
+//! use single_address_assembler::{xref, Parser, Section};
+//!
+//! let mut sources = SourceMap::new();
+//! sources.add_file(
+//!     "prog.s".to_owned(),
+//!     ".data .label n .number 2\n.text .label top\nadd n\nstor n\nbr top\n",
+//! );
+//! let mut parser = Parser::new(&sources.text);
+//! parser.parse_program().unwrap();
+//! let program = parser.address_program().unwrap();
+//!
+//! let xrefs = xref::cross_reference(&parser, &program);
+//! assert_eq!(xrefs[0].name, "top");
+//! assert_eq!(xrefs[1].section, Section::Data);
+//! assert_eq!(xrefs[1].references.len(), 2);
+//! assert_eq!(
+//!     xref::render_text(&xrefs, &sources),
+//!     "\
+//! text labels:
+//!   top = 0x00  defined at prog.s:2
+//!     prog.s:5  br top
+//!
+//! data labels:
+//!   n = 0x00  defined at prog.s:1
+//!     prog.s:3  add n
+//!     prog.s:4  stor n
+//! "
+//! );
+//! ```
+
+use crate::diagnostic::SourceMap;
+use crate::json::string;
+use crate::{AddressedProgram, DataWord, Instruction, Parser, Section, Span};
+use std::fmt::Write;
+
+/// One use of a label as an operand, or as the address stored in a data word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    /// The label as written in the operand.
+    pub operand: Span,
+    /// The whole instruction, or the data value.
+    pub instr: Span,
+}
+
+/// A label with its definition and every reference to it, in source order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Xref {
+    pub name: String,
+    pub section: Section,
+    pub address: usize,
+    pub definition: Span,
+    pub references: Vec<Reference>,
+}
+
+/// Collects every label of `parser`, text labels first, each section sorted by address
+/// and then name.
+pub fn cross_reference(parser: &Parser, program: &AddressedProgram) -> Vec<Xref> {
+    let mut xrefs: Vec<Xref> = program
+        .symbols
+        .iter()
+        .filter_map(|symbol| {
+            let labels = match symbol.section {
+                Section::Text => &parser.text_labels,
+                Section::Data => &parser.data_labels,
+            };
+            let (_, definition) = labels.get(symbol.name.as_str())?;
+            Some(Xref {
+                name: symbol.name.clone(),
+                section: symbol.section,
+                address: symbol.address,
+                definition: definition.clone(),
+                references: Vec::new(),
+            })
+        })
+        .collect();
+    xrefs.sort_by(|a, b| (a.section, a.address, &a.name).cmp(&(b.section, b.address, &b.name)));
+
+    for (instr, instr_span) in parser.text.iter().zip(&parser.text_spans) {
+        let (label, operand) = match instr.label() {
+            Some(label) => label,
+            None => continue,
+        };
+        let section = match instr {
+            Instruction::Branch(..) | Instruction::BranchZero(..) => Section::Text,
+            _ => Section::Data,
+        };
+        if let Some(xref) = xrefs
+            .iter_mut()
+            .find(|xref| xref.section == section && xref.name == label)
+        {
+            xref.references.push(Reference {
+                operand: operand.clone(),
+                instr: instr_span.clone(),
+            });
+        }
+    }
+    for (word, word_span) in parser.data.iter().zip(&parser.data_spans) {
+        let (label, operand, section) = match word {
+            DataWord::Value(_) => continue,
+            DataWord::DataAddress(label, span) => (label, span, Section::Data),
+            DataWord::TextAddress(label, span) => (label, span, Section::Text),
+        };
+        if let Some(xref) = xrefs
+            .iter_mut()
+            .find(|xref| xref.section == section && xref.name == *label)
+        {
+            xref.references.push(Reference {
+                operand: operand.clone(),
+                instr: word_span.clone(),
+            });
+        }
+    }
+    for xref in &mut xrefs {
+        xref.references
+            .sort_by_key(|reference| reference.operand.start);
+        // Every copy a `rep` makes holds the same reference.
+        xref.references.dedup();
+    }
+    xrefs
+}
+
+/// Renders the cross-reference as indented text, one section at a time, with each location
+/// as `file:line`.
+pub fn render_text(xrefs: &[Xref], sources: &SourceMap) -> String {
+    let mut out = String::new();
+    for (index, section) in [Section::Text, Section::Data].iter().enumerate() {
+        if index > 0 {
+            writeln!(out).unwrap();
+        }
+        writeln!(out, "{} labels:", section).unwrap();
+        for xref in xrefs.iter().filter(|xref| xref.section == *section) {
+            let (file, line, _) = sources.location(xref.definition.start);
+            writeln!(
+                out,
+                "  {} = {:#04x}  defined at {}:{}",
+                xref.name, xref.address, file, line
+            )
+            .unwrap();
+            if xref.references.is_empty() {
+                writeln!(out, "    (no references)").unwrap();
+            }
+            for reference in &xref.references {
+                let (file, line, _) = sources.location(reference.operand.start);
+                writeln!(
+                    out,
+                    "    {}:{}  {}",
+                    file,
+                    line,
+                    instr_text(sources, reference)
+                )
+                .unwrap();
+            }
+        }
+    }
+    out
+}
+
+/// Renders a map file for other tools: one line per label, text labels first, each as
+///
+/// ```text
+/// kind name address size file:line
+/// ```
+///
+/// where `kind` is `text` or `data`, `address` is `0x`-prefixed hex, and `size` is the
+/// number of words up to the next label at a higher address or the end of the section.
+///
+/// ```
+/// use single_address_assembler::diagnostic::SourceMap;
+/// use single_address_assembler::{xref, Parser};
+///
+/// let mut sources = SourceMap::new();
+/// sources.add_file(
+///     "prog.s".to_owned(),
+///     ".data .label n .number 2\n.label buf 0 rep 4\n.text .label top\nadd n\n.label end halt\n",
+/// );
+/// let mut parser = Parser::new(&sources.text);
+/// parser.parse_program().unwrap();
+/// let program = parser.address_program().unwrap();
+///
+/// let xrefs = xref::cross_reference(&parser, &program);
+/// assert_eq!(
+///     xref::render_map(&xrefs, &program, &sources),
+///     "\
+/// text top 0x0000 1 prog.s:3
+/// text end 0x0001 1 prog.s:5
+/// data n 0x0000 1 prog.s:1
+/// data buf 0x0001 4 prog.s:2
+/// "
+/// );
+/// ```
+pub fn render_map(xrefs: &[Xref], program: &AddressedProgram, sources: &SourceMap) -> String {
+    let mut out = String::new();
+    for xref in xrefs {
+        let end = match xref.section {
+            Section::Text => program.text_base + program.text.len(),
+            Section::Data => program.data_base + program.data.len(),
+        };
+        // `xrefs` is sorted by address within each section.
+        let next = xrefs
+            .iter()
+            .filter(|other| other.section == xref.section)
+            .map(|other| other.address)
+            .find(|&address| address > xref.address)
+            .unwrap_or(end);
+        let (file, line, _) = sources.location(xref.definition.start);
+        writeln!(
+            out,
+            "{} {} {:#06x} {} {}:{}",
+            xref.section,
+            xref.name,
+            xref.address,
+            next.saturating_sub(xref.address),
+            file,
+            line
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Renders the cross-reference as JSON:
+///
+/// ```text
+/// { "text": [{ "name", "address", "defined", "references": [...] }, ...], "data": [...] }
+/// ```
+///
+/// where `defined` and each reference are `{ "file", "line", "column" }`, and references
+/// also carry the `instr` that makes them.
+pub fn render_json(xrefs: &[Xref], sources: &SourceMap) -> String {
+    let location = |offset: usize| {
+        let (file, line, column) = sources.location(offset);
+        format!(
+            "\"file\": {}, \"line\": {}, \"column\": {}",
+            string(file),
+            line,
+            column
+        )
+    };
+
+    let mut out = String::from("{\n");
+    for (index, section) in [Section::Text, Section::Data].iter().enumerate() {
+        let entries: Vec<String> = xrefs
+            .iter()
+            .filter(|xref| xref.section == *section)
+            .map(|xref| {
+                let references: Vec<String> = xref
+                    .references
+                    .iter()
+                    .map(|reference| {
+                        format!(
+                            "{{ {}, \"instr\": {} }}",
+                            location(reference.operand.start),
+                            string(&instr_text(sources, reference))
+                        )
+                    })
+                    .collect();
+                format!(
+                    "    {{ \"name\": {}, \"address\": {}, \"defined\": {{ {} }}, \"references\": [{}] }}",
+                    string(&xref.name),
+                    xref.address,
+                    location(xref.definition.start),
+                    references.join(", ")
+                )
+            })
+            .collect();
+        let separator = if index == 0 { "," } else { "" };
+        if entries.is_empty() {
+            writeln!(out, "  \"{}\": []{}", section, separator).unwrap();
+        } else {
+            writeln!(
+                out,
+                "  \"{}\": [\n{}\n  ]{}",
+                section,
+                entries.join(",\n"),
+                separator
+            )
+            .unwrap();
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// The referencing instruction's source on one line, however it was spread out.
+fn instr_text(sources: &SourceMap, reference: &Reference) -> String {
+    let words: Vec<&str> = sources.text[reference.instr.clone()]
+        .split_whitespace()
+        .collect();
+    words.join(" ")
+}
@@ -0,0 +1,115 @@
+//! The exit code of each kind of failure, which scripts and autograders rely on.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Writes `source` to a fresh file named after the test and returns its path.
+fn source_file(test: &str, source: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("exit-codes-{}-{}", std::process::id(), test));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("prog.s");
+    fs::write(&path, source).unwrap();
+    path
+}
+
+/// Runs the assembler with `args` and returns its exit code.
+fn exit_code(args: &[&str]) -> i32 {
+    Command::new(env!("CARGO_BIN_EXE_single-address-assembler"))
+        .args(args)
+        .output()
+        .unwrap()
+        .status
+        .code()
+        .unwrap()
+}
+
+fn run_with(test: &str, source: &str, args: &[&str]) -> i32 {
+    let path = source_file(test, source);
+    let mut all = args.to_vec();
+    all.push(path.to_str().unwrap());
+    exit_code(&all)
+}
+
+#[test]
+fn success() {
+    assert_eq!(run_with("success", ".text clac halt", &["check"]), 0);
+    assert_eq!(run_with("success-run", ".text clac halt", &["run"]), 0);
+}
+
+#[test]
+fn syntax_error() {
+    assert_eq!(run_with("syntax", ".text bogus", &["check"]), 1);
+}
+
+#[test]
+fn bad_command_line() {
+    assert_eq!(exit_code(&["run", "--no-such-flag"]), 1);
+}
+
+#[test]
+fn unresolved_label() {
+    assert_eq!(run_with("unknown", ".text br nowhere", &["check"]), 2);
+    let duplicate = ".text .label a halt .label a halt";
+    assert_eq!(run_with("duplicate", duplicate, &["check"]), 2);
+}
+
+#[test]
+fn overflow() {
+    let source = ".data .label x 0 rep 5000 .text halt";
+    assert_eq!(run_with("overflow", source, &["check"]), 3);
+}
+
+#[test]
+fn io_error() {
+    assert_eq!(exit_code(&["check", "/nonexistent/prog.s"]), 4);
+}
+
+#[test]
+fn trap() {
+    let source = ".data .label z 0 .text clac addi 1 div z halt";
+    assert_eq!(run_with("trap", source, &["run"]), 6);
+}
+
+#[test]
+fn step_limit() {
+    let source = ".text .label a addi 1 br a";
+    assert_eq!(
+        run_with("run-limit", source, &["run", "--max-steps", "10"]),
+        7
+    );
+    let args = ["grade", "--max-steps", "10", "--expect", "ac=0"];
+    assert_eq!(run_with("grade-limit", source, &args), 7);
+}
+
+#[test]
+fn failed_check() {
+    let args = ["grade", "--expect", "ac=1"];
+    assert_eq!(run_with("grade", ".text clac halt", &args), 8);
+}
+
+#[test]
+fn each_input_fails_like_its_first_failure() {
+    let ok = source_file("each-ok", ".text clac halt");
+    let unknown = source_file("each-unknown", ".text br nowhere");
+    let overflow = source_file("each-overflow", ".data .label x 0 rep 5000 .text halt");
+    let paths: Vec<&str> = [&ok, &unknown, &overflow]
+        .iter()
+        .map(|path| path.to_str().unwrap())
+        .collect();
+    assert_eq!(exit_code(&["check", "--each", paths[0], paths[0]]), 0);
+    let args = ["check", "--each", "-j", "2", paths[0], paths[2], paths[1]];
+    assert_eq!(exit_code(&args), 3);
+}
+
+#[test]
+fn denied_warning() {
+    let unused = ".data .label x 0 .text halt";
+    assert_eq!(run_with("warn", unused, &["check"]), 0);
+    assert_eq!(run_with("deny", unused, &["check", "--deny", "W001"]), 1);
+    let allowed = format!("#![allow(w001)]\n{}", unused);
+    assert_eq!(run_with("allow", &allowed, &["check", "--deny", "W001"]), 0);
+
+    let args = ["check", "--require-halt", "--allow", "W004"];
+    assert_eq!(run_with("require-halt", ".text clac", &args), 1);
+}
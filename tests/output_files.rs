@@ -0,0 +1,97 @@
+//! What the assembler leaves in the files it writes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A fresh directory named after the test.
+fn test_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("output-files-{}-{}", std::process::id(), test));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Assembles `source` from `dir` with `args` before the input, which must succeed.
+fn assemble(dir: &Path, source: &str, args: &[&str]) {
+    let input = dir.join("prog.s");
+    fs::write(&input, source).unwrap();
+    let status = Command::new(env!("CARGO_BIN_EXE_single-address-assembler"))
+        .arg("assemble")
+        .args(args)
+        .arg(&input)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn rewriting_with_a_shorter_program_leaves_only_its_output() {
+    let long = format!(
+        ".data .label xs {} .text {} .label end br end",
+        "7 ".repeat(100),
+        "add xs ".repeat(100)
+    );
+    let short = ".data .label x 1 .text add x halt";
+
+    let reused = test_dir("reused");
+    let (text, data) = (reused.join("prog.text"), reused.join("prog.data"));
+    let args = ["-t", text.to_str().unwrap(), "-d", data.to_str().unwrap()];
+    assemble(&reused, &long, &args);
+    let long_text = fs::read_to_string(&text).unwrap();
+    assemble(&reused, short, &args);
+
+    let fresh = test_dir("fresh");
+    let (fresh_text, fresh_data) = (fresh.join("prog.text"), fresh.join("prog.data"));
+    let args = [
+        "-t",
+        fresh_text.to_str().unwrap(),
+        "-d",
+        fresh_data.to_str().unwrap(),
+    ];
+    assemble(&fresh, short, &args);
+
+    let short_text = fs::read_to_string(&text).unwrap();
+    assert!(short_text.len() < long_text.len());
+    assert_eq!(short_text, fs::read_to_string(&fresh_text).unwrap());
+    assert_eq!(
+        fs::read_to_string(&data).unwrap(),
+        fs::read_to_string(&fresh_data).unwrap()
+    );
+}
+
+#[test]
+fn binary_images_hold_each_word_big_endian() {
+    let dir = test_dir("bin");
+    let (text, data) = (dir.join("prog.text"), dir.join("prog.data"));
+    let args = [
+        "--format",
+        "bin",
+        "-t",
+        text.to_str().unwrap(),
+        "-d",
+        data.to_str().unwrap(),
+    ];
+    assemble(
+        &dir,
+        ".data .label x 5 -2 .text clac add x stor x halt",
+        &args,
+    );
+    // clac, add 0, stor 0, and the `br 3` that `halt` becomes.
+    assert_eq!(
+        fs::read(&text).unwrap(),
+        [0x30, 0x00, 0x20, 0x00, 0x40, 0x00, 0x60, 0x03]
+    );
+    assert_eq!(fs::read(&data).unwrap(), [0x00, 0x05, 0xff, 0xfe]);
+}
+
+#[test]
+fn combined_images_reach_the_data_by_default() {
+    let dir = test_dir("combined");
+    let combined = dir.join("prog.bin");
+    let args = ["--format", "bin", "--combined", combined.to_str().unwrap()];
+    assemble(&dir, ".data .label x .number 7 .text add x halt", &args);
+    let bytes = fs::read(&combined).unwrap();
+    // `add 0x80`, then the `br 1` that `halt` becomes.
+    assert_eq!(bytes[..4], [0x20, 0x80, 0x60, 0x01]);
+    assert_eq!(bytes[0x100..0x102], [0x00, 0x07]);
+}
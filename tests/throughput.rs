@@ -0,0 +1,43 @@
+//! How long a full-size program takes to assemble, for batch graders that assemble
+//! thousands of files per run. Ignored by default; run it in release mode with
+//! `cargo test --release --test throughput -- --ignored --nocapture`.
+
+use single_address_assembler::Parser;
+use std::time::Instant;
+
+const RUNS: usize = 10_000;
+
+/// A program that fills both memories, 256 data words behind 128 labels and 256
+/// instructions behind 127 more, and uses every label.
+fn full_program() -> String {
+    let mut source = String::from(".data\n");
+    for i in 0..128 {
+        source.push_str(&format!(".label d{} {} -{}\n", i, i, i));
+    }
+    source.push_str(".text\n");
+    for i in 0..127 {
+        source.push_str(&format!(".label t{} add d{} beqz t{}\n", i, i, i));
+    }
+    source.push_str("stor d127 halt\n");
+    source
+}
+
+#[test]
+#[ignore]
+fn assemble_full_program() {
+    let source = full_program();
+    let program = Parser::parse(&source).unwrap().into_program().unwrap();
+    assert_eq!((program.text.len(), program.data.len()), (256, 256));
+
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        Parser::parse(&source).unwrap().into_program().unwrap();
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{} runs in {:?} ({:?} per program)",
+        RUNS,
+        elapsed,
+        elapsed / RUNS as u32
+    );
+}